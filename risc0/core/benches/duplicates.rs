@@ -0,0 +1,27 @@
+//! Duplicate detection: `Vec::contains` scan vs `CellSet` bitset
+//!
+//! Run with `cargo bench -p gridzero-core --features bench`. Histories
+//! are all-unique so both implementations do the full amount of work.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use gridzero_core::cells::{first_duplicate, first_duplicate_vec};
+
+/// Large enough to hold 10k distinct cells
+const CAPACITY: usize = 128 * 128;
+
+fn duplicates(c: &mut Criterion) {
+    let mut group = c.benchmark_group("duplicate_detection");
+    for records in [100usize, 1_000, 10_000] {
+        let indices: Vec<usize> = (0..records).map(|i| (i * 7919) % CAPACITY).collect();
+        group.bench_with_input(BenchmarkId::new("vec", records), &indices, |b, indices| {
+            b.iter(|| first_duplicate_vec(black_box(indices)))
+        });
+        group.bench_with_input(BenchmarkId::new("bitset", records), &indices, |b, indices| {
+            b.iter(|| first_duplicate(black_box(indices), CAPACITY))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, duplicates);
+criterion_main!(benches);
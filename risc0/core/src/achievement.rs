@@ -0,0 +1,111 @@
+//! Ordered achievements
+//!
+//! Milestones only look at final counts; an `OrderedAchievement` depends
+//! on the order of discovery. It is a sequence of steps, each "mine
+//! `count` records of `ore_type`", that must complete one after another
+//! in nonce order. While a step is in progress, mining the ore of any
+//! later step breaks the order and the achievement fails; ores no step
+//! names are ignored. "Three diamonds before any mythril" is therefore
+//! `[Diamond × 3, Mythril × 1]`, met by the first mythril found after
+//! the third diamond.
+//!
+//! Only in-window records count, like every other tally.
+
+use crate::error::ValidationError;
+use crate::types::*;
+
+/// Whether each achievement is met by `by_nonce` (records in nonce
+/// order, already validated), in the order they were given; every step
+/// must name one of the `ore_count` ore types
+pub fn ordered_achievements_met(
+    achievements: &[OrderedAchievement],
+    by_nonce: &[&MiningRecord],
+    ore_count: usize,
+) -> Result<Vec<bool>, ValidationError> {
+    achievements
+        .iter()
+        .enumerate()
+        .map(|(index, achievement)| {
+            if achievement.steps.iter().any(|step| step.ore_type as usize >= ore_count) {
+                return Err(ValidationError::InvalidAchievement { index });
+            }
+            Ok(is_met(achievement, by_nonce))
+        })
+        .collect()
+}
+
+fn is_met(achievement: &OrderedAchievement, by_nonce: &[&MiningRecord]) -> bool {
+    let steps = &achievement.steps;
+    let mut step = 0;
+    let mut mined: u64 = 0;
+    // Steps that need nothing are complete before any record
+    let skip_empty = |step: &mut usize| {
+        while *step < steps.len() && steps[*step].count == 0 {
+            *step += 1;
+        }
+    };
+    skip_empty(&mut step);
+    for record in by_nonce {
+        if step == steps.len() {
+            break;
+        }
+        if record.ore_type == steps[step].ore_type {
+            mined += 1;
+            if mined == steps[step].count {
+                step += 1;
+                mined = 0;
+                skip_empty(&mut step);
+            }
+        } else if steps[step + 1..].iter().any(|later| later.ore_type == record.ore_type) {
+            return false;
+        }
+    }
+    step == steps.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::record;
+
+    const DIAMOND: u8 = 6;
+    const MYTHRIL: u8 = 7;
+
+    /// Three diamonds before any mythril
+    fn diamonds_first() -> Vec<OrderedAchievement> {
+        vec![OrderedAchievement {
+            steps: vec![
+                OrderedStep { ore_type: DIAMOND, count: 3 },
+                OrderedStep { ore_type: MYTHRIL, count: 1 },
+            ],
+        }]
+    }
+
+    fn met(ores: &[u8]) -> bool {
+        let records: Vec<MiningRecord> =
+            ores.iter().enumerate().map(|(i, &ore)| record(i as u8, 0, ore, i as u64)).collect();
+        let by_nonce: Vec<&MiningRecord> = records.iter().collect();
+        ordered_achievements_met(&diamonds_first(), &by_nonce, ORE_TYPES).unwrap()[0]
+    }
+
+    #[test]
+    fn steps_must_complete_in_order() {
+        // Unnamed ores in between do not break the order
+        assert!(met(&[DIAMOND, 0, DIAMOND, DIAMOND, 1, MYTHRIL]));
+        // A mythril before the third diamond does
+        assert!(!met(&[DIAMOND, DIAMOND, MYTHRIL, DIAMOND, MYTHRIL]));
+        // And an unfinished sequence is not met
+        assert!(!met(&[DIAMOND, DIAMOND, DIAMOND]));
+    }
+
+    #[test]
+    fn a_step_naming_an_unknown_ore_is_invalid() {
+        let achievements = vec![OrderedAchievement {
+            steps: vec![OrderedStep { ore_type: ORE_TYPES as u8, count: 1 }],
+        }];
+        assert_eq!(
+            ordered_achievements_met(&achievements, &[], ORE_TYPES),
+            Err(ValidationError::InvalidAchievement { index: 0 })
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Mined-cell tracking
+//!
+//! Duplicate detection used to scan a `Vec` of seen cells for every
+//! record, which is quadratic in history length. `CellSet` is a bitset
+//! over linear cell indices instead, so each check is O(1) and the whole
+//! grid fits in a few words.
+
+/// Bitset of mined cells, addressed by linear cell index
+#[derive(Clone, Debug)]
+pub struct CellSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl CellSet {
+    /// Empty set able to hold indices `0..capacity`
+    pub fn with_capacity(capacity: usize) -> Self {
+        CellSet {
+            words: vec![0; capacity.div_ceil(64)],
+            len: 0,
+        }
+    }
+
+    /// Mark `index` as mined, returning false if it already was
+    pub fn insert(&mut self, index: usize) -> bool {
+        let (word, bit) = (index / 64, 1u64 << (index % 64));
+        if self.words[word] & bit != 0 {
+            return false;
+        }
+        self.words[word] |= bit;
+        self.len += 1;
+        true
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.words[index / 64] & (1u64 << (index % 64)) != 0
+    }
+
+    /// Number of distinct cells in the set
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Backing words, bit `i % 64` of word `i / 64` marking index `i`
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Rebuild a set from its backing words
+    pub fn from_words(words: Vec<u64>) -> Self {
+        let len = words.iter().map(|word| word.count_ones() as usize).sum();
+        CellSet { words, len }
+    }
+
+    /// First index present in both sets
+    pub fn first_overlap(&self, other: &CellSet) -> Option<usize> {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .enumerate()
+            .find(|(_, (a, b))| *a & *b != 0)
+            .map(|(word, (a, b))| word * 64 + (a & b).trailing_zeros() as usize)
+    }
+
+    /// Add every index of `other` to this set
+    pub fn union_with(&mut self, other: &CellSet) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, theirs) in self.words.iter_mut().zip(&other.words) {
+            *word |= theirs;
+        }
+        self.len = self.words.iter().map(|word| word.count_ones() as usize).sum();
+    }
+}
+
+/// Position of the first index that repeats an earlier one
+pub fn first_duplicate(indices: &[usize], capacity: usize) -> Option<usize> {
+    let mut seen = CellSet::with_capacity(capacity);
+    indices.iter().position(|&index| !seen.insert(index))
+}
+
+/// The original `Vec::contains` scan, kept only so the benchmark can
+/// compare against it
+#[cfg(feature = "bench")]
+pub fn first_duplicate_vec(indices: &[usize]) -> Option<usize> {
+    let mut seen: Vec<usize> = Vec::new();
+    for (position, &index) in indices.iter().enumerate() {
+        if seen.contains(&index) {
+            return Some(position);
+        }
+        seen.push(index);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserts_report_repeats() {
+        let mut cells = CellSet::with_capacity(130);
+        assert!(cells.insert(0) && cells.insert(129));
+        assert!(!cells.insert(129));
+        assert_eq!(cells.len(), 2);
+        assert!(cells.contains(129) && !cells.contains(64));
+        assert_eq!(CellSet::from_words(cells.words().to_vec()).len(), 2);
+    }
+
+    #[test]
+    fn the_first_repeat_is_found() {
+        assert_eq!(first_duplicate(&[3, 70, 5, 70, 3], 128), Some(3));
+        assert_eq!(first_duplicate(&[3, 70, 5], 128), None);
+    }
+
+    #[test]
+    fn unions_track_overlaps() {
+        let mut a = CellSet::with_capacity(128);
+        let mut b = CellSet::with_capacity(128);
+        a.insert(1);
+        b.insert(100);
+        assert_eq!(a.first_overlap(&b), None);
+        a.union_with(&b);
+        assert_eq!(a.len(), 2);
+        assert_eq!(a.first_overlap(&b), Some(100));
+    }
+
+    /// Only built with the `bench` feature, which keeps the old scan
+    #[cfg(feature = "bench")]
+    mod against_the_vec_scan {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn the_bitset_finds_the_same_repeat(indices in proptest::collection::vec(0usize..256, 0..64)) {
+                prop_assert_eq!(first_duplicate(&indices, 256), first_duplicate_vec(&indices));
+            }
+        }
+    }
+}
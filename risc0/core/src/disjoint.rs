@@ -0,0 +1,110 @@
+//! Cross-player disjointness
+//!
+//! For anti-Sybil checks, the disjointness guest proves that two players
+//! mined no cell in common. `LeaderboardOutput` does not commit cells,
+//! but a `ShardOutput` does: its `cells` bitset has bit `i % 64` of word
+//! `i / 64` set for every linear cell index `i` the shard mined. Each
+//! player is therefore proven as a single shard spanning the full nonce
+//! range (`FULL_HISTORY`), so the bitset covers every record in the
+//! history, time window or not.
+//!
+//! Linear indices are layer-major with a fixed `GRID_CELLS` per layer,
+//! so a cell has the same index at any `grid_depth`, and a shallower
+//! map's bitset is simply shorter. The sets intersect exactly when some
+//! pair of words at the same position has a common bit (`a & b != 0`);
+//! words past the end of the shorter bitset are unmined there and cannot
+//! intersect.
+
+use crate::cells::CellSet;
+use crate::error::ValidationError;
+use crate::types::*;
+
+/// Shard range a disjointness member must cover: every possible nonce
+pub const FULL_HISTORY: NonceRange = NonceRange {
+    start: 0,
+    end: u64::MAX,
+};
+
+/// Compare two verified whole-history shard outputs
+pub fn check_disjoint(input: &DisjointInput) -> Result<DisjointOutput, ValidationError> {
+    if let Some(member) = input.members.iter().position(|member| member.range != FULL_HISTORY) {
+        return Err(ValidationError::PartialHistory { member });
+    }
+    let [a, b] = &input.members;
+    let overlap = CellSet::from_words(a.cells.clone()).first_overlap(&CellSet::from_words(b.cells.clone()));
+
+    Ok(DisjointOutput {
+        member_image_id: input.member_image_id,
+        player_addresses: [a.output.player_address, b.output.player_address],
+        history_roots: [a.output.history_root, b.output.history_root],
+        disjoint: overlap.is_none(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shard::compute_shard;
+    use crate::testing::{input, record};
+
+    /// A whole-history shard of `address` mining `cells` (as `(x, y, z)`)
+    fn member(address: u8, cells: &[(u8, u8, u8)], grid_depth: u8) -> ShardOutput {
+        let records = cells
+            .iter()
+            .enumerate()
+            .map(|(nonce, &(x, y, grid_z))| MiningRecord {
+                grid_z,
+                ..record(x, y, 0, nonce as u64)
+            })
+            .collect();
+        compute_shard(&LeaderboardInput {
+            player_address: [address; 20],
+            shard: Some(FULL_HISTORY),
+            grid_depth,
+            ..input(records)
+        })
+        .unwrap()
+    }
+
+    fn disjoint(a: ShardOutput, b: ShardOutput) -> Result<bool, ValidationError> {
+        check_disjoint(&DisjointInput {
+            member_image_id: [0; 8],
+            members: [a, b],
+        })
+        .map(|output| output.disjoint)
+    }
+
+    #[test]
+    fn disjoint_histories_are_disjoint() {
+        let a = member(0xaa, &[(0, 0, 0), (5, 5, 0)], 0);
+        let b = member(0xbb, &[(0, 1, 0), (31, 31, 0)], 0);
+        assert_eq!(disjoint(a, b), Ok(true));
+
+        // The same (x, y) on another layer is another cell
+        let flat = member(0xaa, &[(3, 3, 0)], 0);
+        let deep = member(0xbb, &[(3, 3, 1)], 2);
+        assert_eq!(disjoint(flat, deep), Ok(true));
+    }
+
+    #[test]
+    fn overlapping_histories_are_not() {
+        let a = member(0xaa, &[(0, 0, 0), (5, 5, 0)], 0);
+        let b = member(0xbb, &[(7, 1, 0), (5, 5, 0)], 0);
+        assert_eq!(disjoint(a, b), Ok(false));
+
+        // Surface cells keep their index in a deeper map
+        let flat = member(0xaa, &[(3, 3, 0)], 0);
+        let deep = member(0xbb, &[(3, 3, 1), (3, 3, 0)], 2);
+        assert_eq!(disjoint(flat, deep), Ok(false));
+    }
+
+    #[test]
+    fn partial_histories_are_rejected() {
+        let mut partial = member(0xbb, &[(1, 1, 0)], 0);
+        partial.range = NonceRange { start: 0, end: 10 };
+        assert_eq!(
+            disjoint(member(0xaa, &[(0, 0, 0)], 0), partial),
+            Err(ValidationError::PartialHistory { member: 1 })
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! Validation errors
+//!
+//! Every way a history can be rejected. The guest turns these into a
+//! panic (which aborts proving); the host renders them before spending
+//! any proving time.
+
+use serde::Serialize;
+use std::fmt;
+
+use crate::types::{MAX_HISTORY_RECORDS, MAX_INPUT_BYTES, MAX_ORE_TYPES};
+
+/// Serializes as `{"Variant": {fields}}` for machine-readable reports
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// A cell was mined more than once
+    DuplicateCell { x: u8, y: u8, z: u8 },
+    /// Record `index` lies outside the grid
+    OutOfBounds { index: usize },
+    /// Record `index` names an ore type that does not exist
+    InvalidOreType { index: usize, value: u8 },
+    /// Record `index` repeats the nonce of an earlier record
+    NonceNotMonotonic { index: usize },
+    /// Record `index` was authorized by an earlier block than the
+    /// record before it in nonce order
+    BlockHeightDecreasing { index: usize },
+    /// The input selects a commitment hasher that does not exist
+    UnknownHasher { value: u8 },
+    /// A score computation overflowed `u64`
+    Overflow,
+    /// The computed inventories differ from the expected snapshot
+    InventoryCommitmentMismatch,
+    /// A record is timestamped after the input's `now`
+    RecordAfterNow { timestamp: u64 },
+    /// The newest record is `age` seconds old, more than `max_age`
+    StaleHistory { age: u64, max_age: u64 },
+    /// Record `index` has an `is_rare` flag its VRF output contradicts
+    RarityMismatch { index: usize },
+    /// Record `index` has a nonce outside the shard's range
+    NonceOutsideShard { index: usize },
+    /// An aggregate was requested over no shards at all
+    NoShards,
+    /// No shard covers the nonces right after `after`
+    ShardGap { after: u64 },
+    /// Two shards both cover nonce `at`
+    ShardOverlap { at: u64 },
+    /// A shard belongs to another player or used different settings
+    ShardMismatch { start: u64 },
+    /// The shard starting at nonce `start` begins at a lower block height
+    /// than the shard before it ends at
+    ShardBlockHeightDecreasing { start: u64 },
+    /// The milestones of ore type `ore` are not ascending, or too many
+    InvalidMilestones { ore: usize },
+    /// The map manifest's signature does not verify under its authority
+    InvalidMapSignature,
+    /// The map layout does not hash to the manifest's `map_root`, or does
+    /// not cover the grid
+    MapLayoutMismatch,
+    /// Record `index` has an ore type the map does not place at its cell
+    OreNotOnMap { index: usize },
+    /// Disjointness member `member` covers only part of its history
+    PartialHistory { member: usize },
+    /// The player address is all zeros and `allow_zero_address` is unset
+    ZeroAddress,
+    /// The checkpoint exceeds the inventory, or is not the inventory of
+    /// the history's first `total_mined` records
+    CheckpointMismatch,
+    /// The history mines `cells` distinct cells, more than the `max` the
+    /// grid, map or `max_unique_cells` allows
+    TooManyCells { cells: u64, max: u64 },
+    /// The history has `records` records, more than `MAX_HISTORY_RECORDS`
+    TooManyRecords { records: usize },
+    /// The serialized input is `bytes` long, more than `MAX_INPUT_BYTES`
+    InputTooLarge { bytes: usize },
+    /// The records do not hash to the client's `client_checksum`
+    ClientChecksumMismatch,
+    /// Ordered achievement `index` names an ore type that does not exist
+    InvalidAchievement { index: usize },
+    /// With `no_gaps`, no record has the nonce right after `after`
+    NonceGap { after: u64 },
+    /// `region_rows` or `region_cols` does not divide the grid evenly
+    InvalidRegions { rows: u8, cols: u8 },
+    /// With `allow_remine`, a cell was mined again before the cooldown
+    RemineTooSoon { x: u8, y: u8, z: u8 },
+    /// A history that re-mines cells cannot be split into shards, whose
+    /// cell sets must be disjoint
+    RemineAcrossShards,
+    /// The input sets `field`, which only a proof over the whole history
+    /// can check, so it cannot be split into shards
+    UnshardableField { field: &'static str },
+    /// The input names both a signed map and a seeded one
+    ConflictingMaps,
+    /// The economy defines no ore types, or more than `MAX_ORE_TYPES`
+    InvalidOreCount { count: usize },
+    /// Boost window `index` names an unknown ore type, ends before it
+    /// starts or has a zero multiplier
+    InvalidBoost { index: usize },
+    /// Record `index` names a party member that does not exist
+    InvalidMiner { index: usize, value: u8 },
+    /// `party_addresses` repeats an address or has more than 256 members
+    InvalidParty,
+    /// `trust_input` with `minimal_journal`, which could not carry
+    /// `validated: false`
+    TrustedMinimalJournal,
+    /// `tier_thresholds` does not strictly ascend, or has more than 255
+    /// entries
+    InvalidTierThresholds,
+    /// A per-ore vector does not have one entry per ore type
+    OreVecLength { field: &'static str, len: usize, expected: usize },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::DuplicateCell { x, y, z: 0 } => {
+                write!(f, "duplicate cell ({}, {})", x, y)
+            }
+            ValidationError::DuplicateCell { x, y, z } => {
+                write!(f, "duplicate cell ({}, {}, {})", x, y, z)
+            }
+            ValidationError::OutOfBounds { index } => {
+                write!(f, "record {} is outside the grid", index)
+            }
+            ValidationError::InvalidOreType { index, value } => {
+                write!(f, "record {} has invalid ore type {}", index, value)
+            }
+            ValidationError::NonceNotMonotonic { index } => {
+                write!(f, "record {} repeats an earlier nonce", index)
+            }
+            ValidationError::BlockHeightDecreasing { index } => {
+                write!(f, "record {} has a lower block height than the previous nonce", index)
+            }
+            ValidationError::UnknownHasher { value } => {
+                write!(f, "unknown hasher {}", value)
+            }
+            ValidationError::Overflow => write!(f, "score overflowed u64"),
+            ValidationError::InventoryCommitmentMismatch => {
+                write!(f, "inventories do not match the expected snapshot commitment")
+            }
+            ValidationError::RecordAfterNow { timestamp } => {
+                write!(f, "a record is timestamped {}, after now", timestamp)
+            }
+            ValidationError::StaleHistory { age, max_age } => write!(
+                f,
+                "newest record is {}s old, older than the max age of {}s",
+                age, max_age
+            ),
+            ValidationError::RarityMismatch { index } => {
+                write!(f, "record {} has a rarity its VRF output contradicts", index)
+            }
+            ValidationError::NonceOutsideShard { index } => {
+                write!(f, "record {} has a nonce outside the shard range", index)
+            }
+            ValidationError::NoShards => write!(f, "no shards to aggregate"),
+            ValidationError::ShardGap { after } => {
+                write!(f, "no shard covers the nonces after {}", after)
+            }
+            ValidationError::ShardOverlap { at } => {
+                write!(f, "more than one shard covers nonce {}", at)
+            }
+            ValidationError::ShardMismatch { start } => {
+                write!(f, "shard starting at nonce {} does not match the others", start)
+            }
+            ValidationError::ShardBlockHeightDecreasing { start } => write!(
+                f,
+                "shard starting at nonce {} has a lower block height than the shard before it",
+                start
+            ),
+            ValidationError::InvalidMilestones { ore } => {
+                write!(f, "milestones for ore type {} are not ascending or exceed 254", ore)
+            }
+            ValidationError::InvalidMapSignature => {
+                write!(f, "map manifest is not signed by its map authority")
+            }
+            ValidationError::MapLayoutMismatch => {
+                write!(f, "map layout does not match the manifest's map root")
+            }
+            ValidationError::OreNotOnMap { index } => {
+                write!(f, "record {} has an ore type the map does not place there", index)
+            }
+            ValidationError::PartialHistory { member } => {
+                write!(f, "member {} does not cover its whole history", member)
+            }
+            ValidationError::ZeroAddress => write!(
+                f,
+                "player address is the zero address (set allow_zero_address if intended)"
+            ),
+            ValidationError::CheckpointMismatch => {
+                write!(f, "checkpoint does not match the start of the history")
+            }
+            ValidationError::TooManyCells { cells, max } => {
+                write!(f, "history mines {} distinct cells, more than the {} allowed", cells, max)
+            }
+            ValidationError::TooManyRecords { records } => write!(
+                f,
+                "history exceeds {} records ({} given); prove it in shards",
+                MAX_HISTORY_RECORDS, records
+            ),
+            ValidationError::InputTooLarge { bytes } => {
+                write!(f, "input exceeds {} bytes ({} given)", MAX_INPUT_BYTES, bytes)
+            }
+            ValidationError::ClientChecksumMismatch => {
+                write!(f, "records do not match the client checksum")
+            }
+            ValidationError::InvalidAchievement { index } => {
+                write!(f, "ordered achievement {} names an unknown ore type", index)
+            }
+            ValidationError::NonceGap { after } => write!(f, "no record has the nonce after {}", after),
+            ValidationError::InvalidRegions { rows, cols } => {
+                write!(f, "{}x{} regions do not divide the grid evenly", rows, cols)
+            }
+            ValidationError::RemineTooSoon { x, y, z } => {
+                write!(f, "cell ({}, {}, {}) was re-mined before its cooldown", x, y, z)
+            }
+            ValidationError::RemineAcrossShards => {
+                write!(f, "histories with allow_remine cannot be proven in shards")
+            }
+            ValidationError::UnshardableField { field } => {
+                write!(f, "inputs that set {} cannot be proven in shards", field)
+            }
+            ValidationError::ConflictingMaps => {
+                write!(f, "input sets both map_manifest and map_seed")
+            }
+            ValidationError::InvalidOreCount { count } => {
+                write!(f, "economy defines {} ore types, outside 1..={}", count, MAX_ORE_TYPES)
+            }
+            ValidationError::InvalidBoost { index } => {
+                write!(f, "boost window {} is invalid", index)
+            }
+            ValidationError::InvalidMiner { index, value } => {
+                write!(f, "record {} names party member {}, who does not exist", index, value)
+            }
+            ValidationError::InvalidParty => {
+                write!(f, "party addresses must be distinct, with at most 256 members")
+            }
+            ValidationError::TrustedMinimalJournal => write!(
+                f,
+                "trust_input needs the full journal, which commits validated: false"
+            ),
+            ValidationError::InvalidTierThresholds => {
+                write!(f, "tier thresholds must strictly ascend, with at most 255 tiers")
+            }
+            ValidationError::OreVecLength { field, len, expected } => {
+                write!(f, "{} has {} entries, not one per ore type ({})", field, len, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_render_for_reports() {
+        let cases = [
+            (ValidationError::DuplicateCell { x: 1, y: 2, z: 0 }, "duplicate cell (1, 2)"),
+            (ValidationError::OutOfBounds { index: 3 }, "record 3 is outside the grid"),
+            (
+                ValidationError::InvalidOreType { index: 0, value: 16 },
+                "record 0 has invalid ore type 16",
+            ),
+            (ValidationError::NonceNotMonotonic { index: 4 }, "record 4 repeats an earlier nonce"),
+            (
+                ValidationError::BlockHeightDecreasing { index: 5 },
+                "record 5 has a lower block height than the previous nonce",
+            ),
+            (ValidationError::UnknownHasher { value: 9 }, "unknown hasher 9"),
+            (ValidationError::Overflow, "score overflowed u64"),
+        ];
+        for (error, message) in cases {
+            assert_eq!(error.to_string(), message);
+        }
+    }
+}
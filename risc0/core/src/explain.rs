@@ -0,0 +1,159 @@
+//! Score explanations
+//!
+//! A per-record breakdown of how a score was reached, for player
+//! support. It is built from the same `record_points` used by
+//! `compute_leaderboard`, and the final score is taken from
+//! `compute_leaderboard` itself, so the explanation is authoritative.
+//! Records outside the input's time window score nothing and are left
+//! out.
+
+use serde::Serialize;
+
+use crate::error::ValidationError;
+use crate::score::{self, compute_leaderboard, record_points};
+use crate::types::*;
+
+/// What one record added to the per-record total
+#[derive(Serialize, Clone, Debug)]
+pub struct RecordContribution {
+    pub index: usize,
+    pub nonce: u64,
+    pub grid_x: u8,
+    pub grid_y: u8,
+    pub grid_z: u8,
+    pub ore: String,
+    pub base: u64,
+    pub rare_multiplier: u64,
+    /// `depth_bonus_per_layer` times the record's layer
+    pub depth_bonus: u64,
+    /// Multiplier of the boost window the record fell in (1 if none),
+    /// applied to base and depth bonus
+    pub boost: u64,
+    /// Onboarding bonus earned, if among the first records by nonce
+    pub onboarding_bonus: u64,
+    /// Comeback bonus earned, if this record resumed mining after a gap
+    pub comeback_bonus: u64,
+    pub points: u64,
+    pub running_total: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ScoreExplanation {
+    pub records: Vec<RecordContribution>,
+    /// Sum of `records[..].points`
+    pub per_record_total: u64,
+    pub scoring_mode: ScoringMode,
+    /// The committed score; equals `per_record_total` in `PerRecord` mode
+    /// unless an economy score cap was reached
+    pub score: u64,
+}
+
+/// Explain how `input` scores, failing exactly when proving would
+pub fn explain_score(input: &LeaderboardInput) -> Result<ScoreExplanation, ValidationError> {
+    let output = compute_leaderboard(input)?;
+
+    // Onboarding goes to the first records in nonce order, not input
+    // order, and only to records inside the time window
+    let counted = |index: usize| {
+        score::in_window(&input.mining_history[index], input.window_start, input.window_end)
+    };
+    let mut by_nonce: Vec<usize> = (0..input.mining_history.len())
+        .filter(|&index| counted(index))
+        .collect();
+    by_nonce.sort_by_key(|&index| input.mining_history[index].nonce);
+    let mut onboarded = vec![false; input.mining_history.len()];
+    for &index in by_nonce.iter().take(usize::try_from(input.onboarding_count).unwrap_or(usize::MAX)) {
+        onboarded[index] = true;
+    }
+    let in_window_by_nonce: Vec<&MiningRecord> =
+        by_nonce.iter().map(|&index| &input.mining_history[index]).collect();
+    let mut comeback = vec![false; input.mining_history.len()];
+    if input.comeback_bonus > 0 {
+        for position in score::comeback_records(&in_window_by_nonce, input.comeback_gap) {
+            comeback[by_nonce[position]] = true;
+        }
+    }
+
+    let mut running_total: u64 = 0;
+    let mut records = Vec::with_capacity(input.mining_history.len());
+    for (index, record) in input.mining_history.iter().enumerate() {
+        if !counted(index) {
+            continue;
+        }
+        let onboarding_bonus = if onboarded[index] { input.onboarding_bonus } else { 0 };
+        let comeback_bonus = if comeback[index] { input.comeback_bonus } else { 0 };
+        let points = record_points(record, input)?
+            .checked_add(onboarding_bonus)
+            .and_then(|points| points.checked_add(comeback_bonus))
+            .ok_or(ValidationError::Overflow)?;
+        running_total = running_total
+            .checked_add(points)
+            .ok_or(ValidationError::Overflow)?;
+        records.push(RecordContribution {
+            index,
+            nonce: record.nonce,
+            grid_x: record.grid_x,
+            grid_y: record.grid_y,
+            grid_z: record.grid_z,
+            ore: ore_name(record.ore_type as usize),
+            base: input.economy.base_scores[record.ore_type as usize],
+            rare_multiplier: if record.is_rare {
+                input.economy.rare_multipliers[record.ore_type as usize]
+            } else {
+                1
+            },
+            depth_bonus: input.depth_bonus_per_layer * record.grid_z as u64,
+            boost: score::boost_multiplier(record, &input.boost_windows),
+            onboarding_bonus,
+            comeback_bonus,
+            points,
+            running_total,
+        });
+    }
+
+    Ok(ScoreExplanation {
+        records,
+        per_record_total: running_total,
+        scoring_mode: output.scoring_mode,
+        score: output.score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{input, valid_history};
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn the_breakdown_sums_to_the_score(
+            records in valid_history(),
+            onboarding_count in 0..8u64,
+            onboarding_bonus in 0..100u64,
+            comeback in prop::option::of((1..10u64, 1..100u64)),
+        ) {
+            // Mines 7s apart, so some comeback gaps are exceeded
+            let records = records
+                .into_iter()
+                .map(|record| MiningRecord { timestamp: record.nonce * 7, ..record })
+                .collect();
+            let (comeback_gap, comeback_bonus) = comeback.unwrap_or((0, 0));
+            let input = LeaderboardInput {
+                onboarding_count,
+                onboarding_bonus,
+                comeback_gap,
+                comeback_bonus,
+                ..input(records)
+            };
+            let explanation = explain_score(&input).unwrap();
+            let points: u64 = explanation.records.iter().map(|record| record.points).sum();
+            prop_assert_eq!(points, explanation.per_record_total);
+            prop_assert_eq!(
+                explanation.records.last().map_or(0, |record| record.running_total),
+                explanation.per_record_total
+            );
+            prop_assert_eq!(explanation.per_record_total, explanation.score);
+        }
+    }
+}
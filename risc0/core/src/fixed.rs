@@ -0,0 +1,75 @@
+//! Integer division with an explicit rounding mode
+//!
+//! Every division that feeds a committed value goes through `div_round`,
+//! so the rounding is one documented choice, taken from the input's
+//! `rounding` and committed next to the result, rather than whatever
+//! `/` happens to do. Today that is `coverage_bps`. Floor is the
+//! default and matches plain integer division.
+
+use crate::types::RoundingMode;
+
+/// `numerator / denominator` rounded per `mode`. `Nearest` rounds a
+/// remainder of exactly half up: 1/2 is 1 and 5/2 is 3. Operands are
+/// `u128` so products of `u64`s divide without overflowing.
+///
+/// Panics if `denominator` is zero; callers rule that out first.
+pub fn div_round(numerator: u128, denominator: u128, mode: RoundingMode) -> u128 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    let round_up = match mode {
+        RoundingMode::Floor => false,
+        RoundingMode::Ceil => remainder > 0,
+        RoundingMode::Nearest => remainder >= denominator - remainder,
+    };
+    quotient + round_up as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::score::{compute_leaderboard, coverage_bps};
+    use crate::testing::{input, record};
+    use crate::types::LeaderboardInput;
+    use RoundingMode::*;
+
+    #[test]
+    fn each_mode_rounds_remainders_its_own_way() {
+        // (numerator, denominator, floor, ceil, nearest)
+        let cases = [
+            (0, 7, 0, 0, 0),
+            (6, 3, 2, 2, 2), // exact: every mode agrees
+            (1, 3, 0, 1, 0), // below half
+            (2, 3, 0, 1, 1), // above half
+            (1, 2, 0, 1, 1), // exactly half rounds up
+            (5, 2, 2, 3, 3),
+            (7, 4, 1, 2, 2),
+            (u128::MAX, 2, u128::MAX / 2, u128::MAX / 2 + 1, u128::MAX / 2 + 1),
+        ];
+        for (numerator, denominator, floor, ceil, nearest) in cases {
+            let rounded = [Floor, Ceil, Nearest].map(|mode| div_round(numerator, denominator, mode));
+            assert_eq!(rounded, [floor, ceil, nearest], "{} / {}", numerator, denominator);
+        }
+    }
+
+    #[test]
+    fn coverage_differs_by_at_most_one_basis_point_between_modes() {
+        // 1 of 3 cells: 3333.33 bps
+        assert_eq!([Floor, Ceil, Nearest].map(|mode| coverage_bps(1, 3, mode)), [3333, 3334, 3333]);
+        // 2 of 3 cells: 6666.67 bps
+        assert_eq!([Floor, Ceil, Nearest].map(|mode| coverage_bps(2, 3, mode)), [6666, 6667, 6667]);
+        // 1 of 20000 cells: half a basis point
+        assert_eq!([Floor, Ceil, Nearest].map(|mode| coverage_bps(1, 20_000, mode)), [0, 1, 1]);
+        // A whole number of basis points is the same in every mode
+        assert_eq!([Floor, Ceil, Nearest].map(|mode| coverage_bps(1, 4, mode)), [2500; 3]);
+
+        // One cell of the 1024-cell grid is 9.77 bps, committed with its mode
+        for (rounding, expected) in [(Floor, 9), (Ceil, 10), (Nearest, 10)] {
+            let output = compute_leaderboard(&LeaderboardInput {
+                rounding,
+                ..input(vec![record(0, 0, 0, 1)])
+            })
+            .unwrap();
+            assert_eq!((output.coverage_bps, output.rounding), (expected, rounding));
+        }
+    }
+}
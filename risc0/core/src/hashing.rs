@@ -0,0 +1,313 @@
+//! Commitment hashing
+//!
+//! The Merkle roots committed to the journal (`provenance_root` and
+//! `history_root`) are built with a selectable hash, chosen by the
+//! input's `hasher` field:
+//!
+//! * `0` — SHA-256. Accelerated by the zkVM's SHA precompile, so it is
+//!   by far the cheapest option inside the guest, but expensive to
+//!   re-hash inside another circuit.
+//! * `1` — Poseidon over BN254 (circom parameters). Costs many more
+//!   guest cycles since it is plain field arithmetic, but is cheap to
+//!   recompute in SNARK-friendly on-chain verifiers and rollups.
+//!
+//! Poseidon works over field elements, so leaf bytes are first reduced
+//! to one by taking their SHA-256 and clearing the top byte (< 2^248,
+//! below the BN254 modulus). Internal nodes are already field elements.
+
+use crate::cbor;
+use crate::types::{BoostWindow, Economy, LeaderboardInput, MiningRecord, NonceRange};
+use ark_bn254::Fr;
+use light_poseidon::{Poseidon, PoseidonBytesHasher};
+use sha2::{Digest, Sha256};
+
+pub const HASHER_SHA256: u8 = 0;
+pub const HASHER_POSEIDON: u8 = 1;
+
+pub trait CommitmentHasher {
+    /// Hash arbitrary leaf data to a 32-byte node
+    fn leaf(&self, data: &[u8]) -> [u8; 32];
+    /// Hash two child nodes into their parent
+    fn node(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32];
+}
+
+pub struct Sha256Hasher;
+
+impl CommitmentHasher for Sha256Hasher {
+    fn leaf(&self, data: &[u8]) -> [u8; 32] {
+        Sha256::digest(data).into()
+    }
+
+    fn node(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().into()
+    }
+}
+
+pub struct PoseidonHasher;
+
+impl CommitmentHasher for PoseidonHasher {
+    fn leaf(&self, data: &[u8]) -> [u8; 32] {
+        let mut element: [u8; 32] = Sha256::digest(data).into();
+        element[0] = 0;
+        let mut poseidon = Poseidon::<Fr>::new_circom(1).expect("valid width");
+        poseidon.hash_bytes_be(&[element.as_slice()]).expect("element below modulus")
+    }
+
+    fn node(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut poseidon = Poseidon::<Fr>::new_circom(2).expect("valid width");
+        poseidon.hash_bytes_be(&[left.as_slice(), right.as_slice()]).expect("nodes are field elements")
+    }
+}
+
+/// Look up the hasher selected by an input's `hasher` field
+pub fn select(hasher: u8) -> Option<&'static dyn CommitmentHasher> {
+    match hasher {
+        HASHER_SHA256 => Some(&Sha256Hasher),
+        HASHER_POSEIDON => Some(&PoseidonHasher),
+        _ => None,
+    }
+}
+
+/// Binary Merkle root over `leaves`. An odd node at the end of a level
+/// is carried up unchanged; an empty list has the all-zero root.
+pub fn merkle_root(hasher: &dyn CommitmentHasher, leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hasher.node(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// One level of a Merkle inclusion proof: the sibling to hash with, and
+/// whether it sits to the left. Levels where the node was carried up
+/// unpaired have no step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MerkleStep {
+    pub sibling: [u8; 32],
+    pub sibling_on_left: bool,
+}
+
+/// Inclusion proof for `leaves[index]` in `merkle_root(hasher, leaves)`
+pub fn merkle_proof(
+    hasher: &dyn CommitmentHasher,
+    leaves: &[[u8; 32]],
+    mut index: usize,
+) -> Vec<MerkleStep> {
+    let mut steps = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if let Some(node) = level.get(sibling) {
+            steps.push(MerkleStep {
+                sibling: *node,
+                sibling_on_left: sibling < index,
+            });
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hasher.node(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+        index /= 2;
+    }
+    steps
+}
+
+/// Whether `proof` leads from `leaf` to `root`
+pub fn verify_merkle_proof(
+    hasher: &dyn CommitmentHasher,
+    leaf: [u8; 32],
+    proof: &[MerkleStep],
+    root: [u8; 32],
+) -> bool {
+    let computed = proof.iter().fold(leaf, |node, step| {
+        if step.sibling_on_left {
+            hasher.node(&step.sibling, &node)
+        } else {
+            hasher.node(&node, &step.sibling)
+        }
+    });
+    computed == root
+}
+
+/// Content identifier of an input: SHA-256 over its canonical bincode
+/// encoding. Unlike the record roots this covers every input field, so
+/// anyone holding the original input can confirm a proof was made from
+/// exactly that input.
+pub fn input_cid(input: &LeaderboardInput) -> [u8; 32] {
+    let canonical = bincode::serialize(input).expect("input is always serializable");
+    Sha256::digest(canonical).into()
+}
+
+/// Commitment to a player's inventories, for reconciling against a
+/// contract's recorded snapshot: SHA-256 over the `ore_count` counts of
+/// `ore_inventory` then the `ore_count` counts of `rare_inventory`, each
+/// a little-endian `u64` (8 bytes) in canonical ore-index order, so
+/// `16 * ore_count` bytes in all. Always SHA-256, regardless of
+/// `hasher`, so it has one fixed on-chain definition.
+pub fn inventory_commitment(ore_inventory: &[u64], rare_inventory: &[u64]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for count in ore_inventory.iter().chain(rare_inventory) {
+        hasher.update(count.to_le_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Hiding commitment to a player's inventories (`hide_inventory`):
+/// SHA-256 over the 32-byte private salt, then the same `16 * ore_count`
+/// bytes as `inventory_commitment`. Anyone later shown the inventories and salt
+/// can recompute it; without the salt the counts cannot be brute-forced
+/// from it.
+pub fn salted_inventory_commitment(
+    ore_inventory: &[u64],
+    rare_inventory: &[u64],
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    for count in ore_inventory.iter().chain(rare_inventory) {
+        hasher.update(count.to_le_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Checksum a client can compute over the records it uploaded: SHA-256
+/// over the canonical CBOR encoding of each record (see `cbor`),
+/// concatenated in nonce order.
+/// Always SHA-256 and independent of every other input field, so it only
+/// says which records were proven.
+pub fn history_checksum(by_nonce: &[&MiningRecord]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for record in by_nonce {
+        hasher.update(cbor::record(record));
+    }
+    hasher.finalize().into()
+}
+
+/// Lease a proof claims over its records' nonces: SHA-256 of the player
+/// address and the range's first and last nonce (little-endian). The
+/// token binds the committed `lease_range` to the player, so a contract
+/// can key accepted leases by it and reject any new proof whose range
+/// overlaps one it already holds for that player.
+pub fn lease_token(player_address: &[u8; 20], range: &NonceRange) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(player_address);
+    hasher.update(range.start.to_le_bytes());
+    hasher.update(range.end.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// Hash of the boost windows a score was computed with: SHA-256 over
+/// their bincode encoding, in input order, or zero with none
+pub fn boosts_hash(boost_windows: &[BoostWindow]) -> [u8; 32] {
+    if boost_windows.is_empty() {
+        return [0u8; 32];
+    }
+    let canonical = bincode::serialize(boost_windows).expect("boost windows are always serializable");
+    Sha256::digest(canonical).into()
+}
+
+/// Hash of an economy: SHA-256 over its bincode encoding. Committed next
+/// to the raw values so a verifier can check the rules a score was
+/// computed under with one comparison.
+pub fn economy_hash(economy: &Economy) -> [u8; 32] {
+    let canonical = bincode::serialize(economy).expect("economy is always serializable");
+    Sha256::digest(canonical).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{input, record};
+    use crate::types::*;
+
+    fn root(hasher: &dyn CommitmentHasher) -> String {
+        let leaves: Vec<[u8; 32]> = [b"a", b"b", b"c"].map(|data| hasher.leaf(data)).to_vec();
+        hex::encode(merkle_root(hasher, &leaves))
+    }
+
+    #[test]
+    fn sha256_roots_are_stable() {
+        assert_eq!(
+            root(&Sha256Hasher),
+            "7075152d03a5cd92104887b476862778ec0c87be5c2fa1c0a90f87c49fad6eff"
+        );
+    }
+
+    #[test]
+    fn poseidon_roots_are_stable() {
+        assert_eq!(
+            root(&PoseidonHasher),
+            "0979df0580dbebbe92d6c6f7bd7eea7d9f5f6c10b1463bbe6c1bfb59122af79a"
+        );
+    }
+
+    #[test]
+    fn every_input_field_changes_the_cid() {
+        let base = input(vec![record(0, 0, 0, 1), record(1, 2, 3, 2)]);
+        let mut economy = Economy::default();
+        economy.base_scores[0] += 1;
+        let mut moved = base.mining_history.clone();
+        moved[1].grid_y = 3;
+        let changed = [
+            LeaderboardInput { player_address: [0x22; 20], ..base.clone() },
+            LeaderboardInput { mining_history: moved, ..base.clone() },
+            LeaderboardInput { mining_history: base.mining_history[..1].to_vec(), ..base.clone() },
+            LeaderboardInput { session_gap: 60, ..base.clone() },
+            LeaderboardInput { hasher: 1, ..base.clone() },
+            LeaderboardInput { include_input_cid: true, ..base.clone() },
+            LeaderboardInput { scoring_mode: ScoringMode::InventoryBonus, ..base.clone() },
+            LeaderboardInput { economy, ..base.clone() },
+            LeaderboardInput { window_end: Some(10), ..base.clone() },
+            LeaderboardInput { now: 10, ..base.clone() },
+        ];
+        let cid = input_cid(&base);
+        assert_eq!(input_cid(&base.clone()), cid);
+        for other in &changed {
+            assert_ne!(input_cid(other), cid, "{:?}", other);
+        }
+    }
+
+    #[test]
+    fn every_economy_field_changes_the_hash() {
+        let base = Economy::default();
+        let mut changed = Vec::new();
+        for ore in [0, ORE_TYPES - 1] {
+            let mut economy = base.clone();
+            economy.base_scores[ore] += 1;
+            changed.push(economy);
+            let mut economy = base.clone();
+            economy.rare_multipliers[ore] += 1;
+            changed.push(economy);
+            let mut economy = base.clone();
+            economy.score_caps[ore] -= 1;
+            changed.push(economy);
+        }
+        changed.push(Economy {
+            rarity_cutoffs: Some(vec![u16::MAX; ORE_TYPES]),
+            ..base.clone()
+        });
+
+        let hash = economy_hash(&base);
+        assert_eq!(economy_hash(&Economy::default()), hash);
+        for economy in &changed {
+            assert_ne!(economy_hash(economy), hash, "{:?}", economy);
+        }
+    }
+}
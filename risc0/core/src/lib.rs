@@ -0,0 +1,29 @@
+//! GridZero Leaderboard Core
+//!
+//! Scoring and validation shared by the zkVM guest and the host. The
+//! guest runs `compute_leaderboard` to produce the journal; the host runs
+//! the very same function natively to pre-check inputs and explain
+//! results, so the two can never disagree about what a history scores.
+
+pub mod achievement;
+pub mod cbor;
+pub mod cells;
+pub mod disjoint;
+pub mod error;
+pub mod explain;
+pub mod fixed;
+pub mod hashing;
+pub mod map;
+pub mod markers;
+pub mod score;
+pub mod shard;
+#[cfg(test)]
+mod testing;
+pub mod types;
+
+pub use disjoint::check_disjoint;
+pub use error::ValidationError;
+pub use explain::{explain_score, ScoreExplanation};
+pub use score::{compute_leaderboard, compute_leaderboard_marked};
+pub use shard::{aggregate_input, aggregate_shards, check_shardable, compute_shard, split_into_shards};
+pub use types::*;
@@ -0,0 +1,164 @@
+//! Signed map manifests
+//!
+//! A map authority publishes each official map as its ore layout (one
+//! ore type byte per linear cell index, layer by layer; the economy's ore
+//! count or above for a cell with nothing to mine) and a manifest
+//! signing the layout's SHA-256 (`map_root`) under a `map_id`. With a
+//! manifest in the input, the guest checks the Ed25519 signature, checks
+//! that the supplied layout hashes to `map_root` and covers every cell
+//! of the grid, and then requires every record's ore type to be the one
+//! the map places at its cell. The manifest's id, root and authority key
+//! are committed, so a verifier that pins the authority knows the score
+//! was earned on an authentic map rather than a favorable hand-made one.
+//!
+//! A seeded map is derived instead of supplied: the ore at a cell is the
+//! first byte of `SHA-256(SEEDED_MAP_DOMAIN || map_seed || beacon ||
+//! cell index as a little-endian u32)` modulo the ore count, so every cell
+//! holds some ore. The beacon is a public randomness value published per
+//! round; nobody chooses it, so nobody can grind seeds for a favorable
+//! map before the round starts. Both the seed and the beacon are
+//! committed for verifiers to check against the official round values.
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+
+use crate::error::ValidationError;
+use crate::score::{grid_layers, GRID_CELLS};
+use crate::types::*;
+
+/// Domain separator prefixed to every signed manifest
+pub const MAP_DOMAIN: &[u8] = b"gridzero-map-v1";
+
+/// Domain separator for seeded map derivation
+pub const SEEDED_MAP_DOMAIN: &[u8] = b"gridzero-seeded-map-v1";
+
+/// Ore type a seeded map of `ore_count` ore types places at linear cell
+/// index `cell`
+pub fn seeded_ore(map_seed: &[u8; 32], beacon: &[u8; 32], cell: usize, ore_count: usize) -> u8 {
+    let mut hasher = Sha256::new();
+    hasher.update(SEEDED_MAP_DOMAIN);
+    hasher.update(map_seed);
+    hasher.update(beacon);
+    hasher.update((cell as u32).to_le_bytes());
+    hasher.finalize()[0] % ore_count as u8
+}
+
+/// SHA-256 of a map layout
+pub fn map_root(layout: &[u8]) -> [u8; 32] {
+    Sha256::digest(layout).into()
+}
+
+/// Bytes the authority signs: `MAP_DOMAIN`, then `map_id` as a
+/// little-endian `u64`, then `map_root`
+pub fn signed_message(map_id: u64, map_root: &[u8; 32]) -> Vec<u8> {
+    let mut message = MAP_DOMAIN.to_vec();
+    message.extend_from_slice(&map_id.to_le_bytes());
+    message.extend_from_slice(map_root);
+    message
+}
+
+/// Verify a manifest and the layout it signs for a map of `grid_depth`
+pub fn verify_manifest(
+    manifest: &MapManifest,
+    layout: &[u8],
+    grid_depth: u8,
+) -> Result<MapCommitment, ValidationError> {
+    let authority =
+        VerifyingKey::from_bytes(&manifest.authority).map_err(|_| ValidationError::InvalidMapSignature)?;
+    let signature =
+        Signature::from_slice(&manifest.signature).map_err(|_| ValidationError::InvalidMapSignature)?;
+    authority
+        .verify_strict(&signed_message(manifest.map_id, &manifest.map_root), &signature)
+        .map_err(|_| ValidationError::InvalidMapSignature)?;
+
+    if layout.len() != GRID_CELLS * grid_layers(grid_depth) || map_root(layout) != manifest.map_root {
+        return Err(ValidationError::MapLayoutMismatch);
+    }
+    Ok(MapCommitment {
+        map_id: manifest.map_id,
+        map_root: manifest.map_root,
+        authority: manifest.authority,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::score::compute_leaderboard;
+    use crate::testing::{input, record};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    const AUTHORITY: [u8; 32] = [7; 32];
+
+    /// A flat map of stone signed by `signer` under `AUTHORITY`'s name
+    fn stone_map(signer: [u8; 32]) -> (MapManifest, Vec<u8>) {
+        let layout = vec![0; GRID_CELLS];
+        let map_root = map_root(&layout);
+        let signature = SigningKey::from_bytes(&signer).sign(&signed_message(1, &map_root));
+        let manifest = MapManifest {
+            map_id: 1,
+            map_root,
+            authority: SigningKey::from_bytes(&AUTHORITY).verifying_key().to_bytes(),
+            signature: signature.to_bytes().to_vec(),
+        };
+        (manifest, layout)
+    }
+
+    fn on_map(manifest: MapManifest, map_layout: Vec<u8>) -> Result<LeaderboardOutput, ValidationError> {
+        compute_leaderboard(&LeaderboardInput {
+            map_manifest: Some(manifest),
+            map_layout,
+            ..input(vec![record(0, 0, 0, 1)])
+        })
+    }
+
+    #[test]
+    fn a_signed_manifest_is_committed() {
+        let (manifest, layout) = stone_map(AUTHORITY);
+        let map = on_map(manifest.clone(), layout).unwrap().map.unwrap();
+        assert_eq!((map.map_id, map.map_root, map.authority), (1, manifest.map_root, manifest.authority));
+    }
+
+    #[test]
+    fn forged_manifests_are_rejected() {
+        let (unsigned, layout) = stone_map(AUTHORITY);
+        let unsigned = MapManifest {
+            signature: vec![0; 64],
+            ..unsigned
+        };
+        let (signed_by_another, _) = stone_map([8; 32]);
+        let (mut rerooted, _) = stone_map(AUTHORITY);
+        rerooted.map_root[0] ^= 1;
+        for forged in [unsigned, signed_by_another, rerooted] {
+            assert_eq!(on_map(forged, layout.clone()), Err(ValidationError::InvalidMapSignature));
+        }
+
+        // A genuine manifest does not vouch for another layout
+        let (manifest, mut layout) = stone_map(AUTHORITY);
+        layout[1] = 1;
+        assert_eq!(on_map(manifest, layout), Err(ValidationError::MapLayoutMismatch));
+    }
+
+    #[test]
+    fn the_beacon_decides_the_seeded_ores() {
+        let (seed, first, second) = ([3; 32], [1; 32], [2; 32]);
+        let ores = |beacon| -> Vec<u8> {
+            (0..GRID_CELLS).map(|cell| seeded_ore(&seed, beacon, cell, ORE_TYPES)).collect()
+        };
+        let (under_first, under_second) = (ores(&first), ores(&second));
+        let changed = (0..GRID_CELLS).filter(|&cell| under_first[cell] != under_second[cell]).count();
+        // Independent draws of 8 ores agree on about an eighth of the cells
+        assert!(changed > GRID_CELLS / 2, "only {} cells changed", changed);
+
+        // A record true to one beacon's map is not on the other's
+        let cell = (0..GRID_CELLS).find(|&cell| under_first[cell] != under_second[cell]).unwrap();
+        let mined = record((cell % 32) as u8, (cell / 32) as u8, under_first[cell], 1);
+        let seeded = |beacon| LeaderboardInput {
+            map_seed: Some(seed),
+            beacon,
+            ..input(vec![mined.clone()])
+        };
+        assert_eq!(compute_leaderboard(&seeded(first)).unwrap().beacon, first);
+        assert_eq!(compute_leaderboard(&seeded(second)), Err(ValidationError::OreNotOnMap { index: 0 }));
+    }
+}
@@ -0,0 +1,47 @@
+//! Cycle markers
+//!
+//! With `CYCLE_MARKERS_ENV` set, the guest writes one marker line to
+//! stderr after each phase of `compute_leaderboard_marked`, carrying the
+//! cycle count at that point. The host's `--dump-cycles-by-segment`
+//! parses them back into a per-phase breakdown. Markers never reach the
+//! journal, so they do not change what is proven.
+
+/// Guest environment variable that turns cycle markers on
+pub const CYCLE_MARKERS_ENV: &str = "GRIDZERO_CYCLE_MARKERS";
+
+/// Leading word of every marker line
+const MARKER_PREFIX: &str = "cycle-marker";
+
+/// The marker line for `phase` finishing at `cycles`
+pub fn format_marker(phase: &str, cycles: u64) -> String {
+    format!("{} {} {}", MARKER_PREFIX, phase, cycles)
+}
+
+/// `(phase, cycles)` from a marker line, or `None` for any other output
+pub fn parse_marker(line: &str) -> Option<(&str, u64)> {
+    let mut words = line.split_whitespace();
+    if words.next()? != MARKER_PREFIX {
+        return None;
+    }
+    let phase = words.next()?;
+    let cycles = words.next()?.parse().ok()?;
+    words.next().is_none().then_some((phase, cycles))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markers_round_trip() {
+        assert_eq!(parse_marker(&format_marker("records", 1234)), Some(("records", 1234)));
+    }
+
+    #[test]
+    fn other_output_is_not_a_marker() {
+        let lines = ["", "records 12", "cycle-marker records", "cycle-marker records x", "cycle-marker a 1 2"];
+        for line in lines {
+            assert_eq!(parse_marker(line), None, "{:?}", line);
+        }
+    }
+}
@@ -0,0 +1,1809 @@
+//! Scoring and validation
+//!
+//! `compute_leaderboard` is the single source of truth for how a mining
+//! history turns into a `LeaderboardOutput`.
+//!
+//! With a time window set, every record is still validated (ore type,
+//! bounds, duplicates, rarity) and still covered by the roots, sessions
+//! and freshness check, but only records inside the window are counted
+//! or scored.
+//!
+//! With a checkpoint, the history is still scored from scratch and
+//! `score_delta` is what the records after the checkpoint's first
+//! `total_mined` in-window records (in nonce order) add on top of them.
+//! The checkpoint's inventory must be no more than the full inventory
+//! and exactly that of those first records. The delta is never
+//! negative: if the later records lower the score it is 0.
+//!
+//! With `hide_inventory`, `ore_inventory` and `rare_inventory` are
+//! committed as zeros and `inventory_commitment` is the salted
+//! `hashing::salted_inventory_commitment` instead, so the score is public
+//! but the ores behind it are not. Everything is computed (and the
+//! expected snapshot checked) on the real inventories first; fields
+//! derived from them, such as `prestige_level` and `milestones_reached`,
+//! still reveal what their thresholds ask about.
+//!
+//! With `region_rows` × `region_cols` set, the grid is cut into equal
+//! rectangular regions (the same on every layer) and `region_scores`
+//! holds each region's in-window record points, before score caps and
+//! the onboarding bonus, row-major from the region at (0, 0).
+//!
+//! With `allow_remine`, a cell may appear more than once as long as, in
+//! nonce order, each mine of it is at least `remine_cooldown` seconds
+//! after the previous one. Every mine counts towards `total_mined` and
+//! the score; `unique_cells` still counts distinct cells.
+//!
+//! With `comeback_bonus` set, every in-window record that follows the
+//! previous in-window record (in nonce order) by more than `comeback_gap`
+//! seconds earns the bonus, once per gap. Like the onboarding bonus it is
+//! added after score caps, and `comeback_bonus_total` is what it added.
+//!
+//! Boost windows multiply a record's points (depth bonus included) while
+//! it falls inside one for its ore type. Where windows overlap the
+//! largest multiplier wins; boosts never stack. They apply before score
+//! caps, and only in `ScoringMode::PerRecord`, since `InventoryBonus`
+//! values ores by count alone. The windows are committed by hash.
+//!
+//! With `tier_thresholds` (strictly ascending), `tier` is how many of
+//! them the score reaches: 0 below the lowest, up to one per threshold.
+//! `hide_score` then commits `score` and `score_delta` as 0, so a tiered
+//! leaderboard learns only the bracket. The inventories, region scores
+//! and bonus totals still reveal what they count; pair it with
+//! `hide_inventory` and leave regions and bonuses off.
+//!
+//! With `party_addresses` set, the history is a co-op party's: each
+//! record's `miner_index` names the member who mined it, and `score` is
+//! the party's combined score. The party shares one map, so the cell
+//! checks apply across all members. `member_scores` holds each member's
+//! in-window record points, before caps and bonuses, and `member_cells`
+//! the distinct in-window cells each mined. A solo player's records all
+//! have `miner_index` 0 and both breakdowns are empty.
+//!
+//! `trust_input` is INSECURE and meant only for internal analytics over
+//! inputs validated elsewhere. It skips the per-record checks that cost
+//! the most cycles: duplicate cells, map agreement, rarity against the
+//! VRF output, the distinct-cell bound and the re-mine cooldown. Ore
+//! types and grid bounds are still checked, since the aggregates index
+//! by them. The aggregates are computed as usual, but a proof of them
+//! says nothing about whether the history was legitimate: a forged or
+//! duplicated history proves just as well. The output commits
+//! `validated: false` so nothing downstream can mistake it for a
+//! validated proof; anything that ranks or pays out on a proof must
+//! require `validated`.
+//!
+//! Every output claims a lease over its records: `lease_range` runs from
+//! the lowest nonce to the highest, window or not, and `lease_token`
+//! binds it to the player (see `hashing::lease_token`).
+
+use crate::achievement;
+use crate::cbor;
+use crate::cells::CellSet;
+use crate::error::ValidationError;
+use crate::fixed;
+use crate::hashing::{self, CommitmentHasher};
+use crate::map;
+use crate::types::*;
+use std::collections::BTreeMap;
+
+/// Points per distinct ore type for every complete set in
+/// `ScoringMode::InventoryBonus`
+pub const SET_COMPLETION_MULTIPLIER: u64 = 10;
+
+/// Cells in one layer of the grid
+pub const GRID_CELLS: usize = GRID_SIZE as usize * GRID_SIZE as usize;
+
+/// Layers on a map of `grid_depth`; 0 (the default) and 1 are both a
+/// flat 2D grid
+pub fn grid_layers(grid_depth: u8) -> usize {
+    grid_depth.max(1) as usize
+}
+
+/// Whether a record falls inside the input's time window (inclusive at
+/// both ends; an unset end is unbounded)
+pub fn in_window(record: &MiningRecord, window_start: Option<u64>, window_end: Option<u64>) -> bool {
+    window_start.is_none_or(|start| record.timestamp >= start)
+        && window_end.is_none_or(|end| record.timestamp <= end)
+}
+
+/// Validate a mining history and compute its public output
+pub fn compute_leaderboard(input: &LeaderboardInput) -> Result<LeaderboardOutput, ValidationError> {
+    compute_full(input).map(|computed| computed.output)
+}
+
+/// `compute_leaderboard`, calling `mark` with each phase's name as it
+/// finishes: `map`, `records`, `nonce-order`, `roots` and `output`. The
+/// guest uses this to read its cycle counter between phases.
+pub fn compute_leaderboard_marked(
+    input: &LeaderboardInput,
+    mark: &mut dyn FnMut(&'static str),
+) -> Result<LeaderboardOutput, ValidationError> {
+    compute_marked(input, mark).map(|computed| computed.output)
+}
+
+/// Everything `compute_full` works out, beyond the output itself
+pub(crate) struct Computed {
+    pub output: LeaderboardOutput,
+    pub cells: CellSet,
+    /// Uncapped record points per ore type
+    pub ore_points: OreVec<u64>,
+    pub mineable_cells: u64,
+    pub max_unique_cells: u64,
+}
+
+/// `compute_leaderboard`, also returning the intermediate state shards
+/// need to be aggregated exactly
+pub(crate) fn compute_full(input: &LeaderboardInput) -> Result<Computed, ValidationError> {
+    compute_marked(input, &mut |_| {})
+}
+
+fn compute_marked(
+    input: &LeaderboardInput,
+    mark: &mut dyn FnMut(&'static str),
+) -> Result<Computed, ValidationError> {
+    if input.player_address == [0u8; 20] && !input.allow_zero_address {
+        return Err(ValidationError::ZeroAddress);
+    }
+    let records = &input.mining_history;
+    let validate = !input.trust_input;
+    if input.trust_input && input.minimal_journal {
+        return Err(ValidationError::TrustedMinimalJournal);
+    }
+    let hasher = hashing::select(input.hasher)
+        .ok_or(ValidationError::UnknownHasher { value: input.hasher })?;
+
+    let economy = &input.economy;
+    let ore_count = input_ore_count(input)?;
+    check_boost_windows(&input.boost_windows, ore_count)?;
+    check_tier_thresholds(&input.tier_thresholds)?;
+    check_party(&input.party_addresses)?;
+    let party_size = input.party_addresses.len().max(1);
+    let mut ore_points: OreVec<u64> = vec![0; ore_count];
+    let mut ore_inventory: OreVec<u64> = vec![0; ore_count];
+    let mut rare_inventory: OreVec<u64> = vec![0; ore_count];
+    let layers = grid_layers(input.grid_depth);
+    let mut seen_cells = CellSet::with_capacity(GRID_CELLS * layers);
+    let mut window_cells = CellSet::with_capacity(GRID_CELLS * layers);
+    if input.map_manifest.is_some() && input.map_seed.is_some() {
+        return Err(ValidationError::ConflictingMaps);
+    }
+    let map = input
+        .map_manifest
+        .as_ref()
+        .map(|manifest| map::verify_manifest(manifest, &input.map_layout, input.grid_depth))
+        .transpose()?;
+    let mut region_scores = vec![0u64; region_count(input.region_rows, input.region_cols)?];
+    let mut member_scores = vec![0u64; input.party_addresses.len()];
+    let mut member_cells =
+        vec![CellSet::with_capacity(GRID_CELLS * layers); input.party_addresses.len()];
+    mark("map");
+    let mut total_mined: u64 = 0;
+
+    for (index, record) in records.iter().enumerate() {
+        // Validate ore type
+        if record.ore_type as usize >= ore_count {
+            return Err(ValidationError::InvalidOreType {
+                index,
+                value: record.ore_type,
+            });
+        }
+
+        // The miner must be a party member (member 0 of a solo player)
+        if record.miner_index as usize >= party_size {
+            return Err(ValidationError::InvalidMiner {
+                index,
+                value: record.miner_index,
+            });
+        }
+
+        // Validate grid bounds
+        if record.grid_x >= GRID_SIZE
+            || record.grid_y >= GRID_SIZE
+            || record.grid_z as usize >= layers
+        {
+            return Err(ValidationError::OutOfBounds { index });
+        }
+
+        // Check for duplicate cells (each cell can only be mined once,
+        // unless re-mining is allowed; its cooldown is checked below)
+        let cell = cell_index(record);
+        if !seen_cells.insert(cell) && !input.allow_remine && validate {
+            return Err(ValidationError::DuplicateCell {
+                x: record.grid_x,
+                y: record.grid_y,
+                z: record.grid_z,
+            });
+        }
+
+        if validate {
+            // The ore must be the one the signed map places here, if any
+            if map.is_some() && input.map_layout[cell] != record.ore_type {
+                return Err(ValidationError::OreNotOnMap { index });
+            }
+            if let Some(seed) = &input.map_seed {
+                if map::seeded_ore(seed, &input.beacon, cell, ore_count) != record.ore_type {
+                    return Err(ValidationError::OreNotOnMap { index });
+                }
+            }
+
+            // Rarity must agree with the VRF output, if the economy says how
+            if !rarity_consistent(record, economy) {
+                return Err(ValidationError::RarityMismatch { index });
+            }
+        }
+
+        // Records outside the window are validated but not counted
+        if !in_window(record, input.window_start, input.window_end) {
+            continue;
+        }
+        total_mined += 1;
+        window_cells.insert(cell);
+
+        // Calculate score
+        let ore = record.ore_type as usize;
+        let points = record_points(record, input)?;
+        ore_points[ore] = ore_points[ore]
+            .checked_add(points)
+            .ok_or(ValidationError::Overflow)?;
+        if !region_scores.is_empty() {
+            let region = region_index(record, input.region_rows, input.region_cols);
+            region_scores[region] = region_scores[region]
+                .checked_add(points)
+                .ok_or(ValidationError::Overflow)?;
+        }
+        if !member_scores.is_empty() {
+            let member = record.miner_index as usize;
+            member_scores[member] = member_scores[member]
+                .checked_add(points)
+                .ok_or(ValidationError::Overflow)?;
+            member_cells[member].insert(cell);
+        }
+
+        // Update inventory
+        ore_inventory[record.ore_type as usize] += 1;
+        if record.is_rare {
+            rare_inventory[record.ore_type as usize] += 1;
+        }
+    }
+
+    let score = total_score(input, &ore_points, &ore_inventory, total_mined)?;
+    mark("records");
+
+    let by_nonce = nonce_order(records)?;
+    let in_window_by_nonce: Vec<&MiningRecord> = by_nonce
+        .iter()
+        .copied()
+        .filter(|record| in_window(record, input.window_start, input.window_end))
+        .collect();
+    let comeback_bonus_total =
+        comeback_total(&in_window_by_nonce, input.comeback_gap, input.comeback_bonus)?;
+    let score = score
+        .checked_add(comeback_bonus_total)
+        .ok_or(ValidationError::Overflow)?;
+    if input.allow_remine && validate {
+        check_remine_cooldown(&by_nonce, input.remine_cooldown)?;
+    }
+    let unique_cells = window_cells.len() as u64;
+    let nonce_range = if input.no_gaps {
+        contiguous_nonces(&by_nonce)?
+    } else {
+        None
+    };
+    let lease_range = lease_range(&by_nonce);
+    let score_delta = match &input.checkpoint {
+        Some(checkpoint) => {
+            score_delta(input, &in_window_by_nonce, checkpoint, &ore_inventory, score)?
+        }
+        None => score,
+    };
+    mark("nonce-order");
+    let ordered_achievements_met =
+        achievement::ordered_achievements_met(&input.ordered_achievements, &in_window_by_nonce, ore_count)?;
+    if let Some(expected) = input.client_checksum {
+        if hashing::history_checksum(&by_nonce) != expected {
+            return Err(ValidationError::ClientChecksumMismatch);
+        }
+    }
+    let provenance_root = provenance_root(hasher, &by_nonce);
+    let history_root = history_root(hasher, &by_nonce);
+    mark("roots");
+    let (longest_session_len, longest_session_duration) =
+        longest_session(&by_nonce, input.session_gap);
+    let input_cid = if input.include_input_cid {
+        hashing::input_cid(input)
+    } else {
+        [0u8; 32]
+    };
+
+    let prestige_level = input
+        .prestige_thresholds
+        .as_ref()
+        .map_or(0, |thresholds| prestige_level(&ore_inventory, thresholds));
+
+    check_freshness(
+        records.iter().map(|record| record.timestamp).max(),
+        input.now,
+        input.max_age,
+    )?;
+
+    debug_assert_ore_indexed(input, &ore_inventory, &rare_inventory);
+
+    let inventory_commitment = checked_inventory_commitment(
+        &ore_inventory,
+        &rare_inventory,
+        input.expected_inventory_commitment,
+    )?;
+    let mineable_cells = mineable_cells(input, map.is_some());
+    let max_unique_cells = max_unique_cells(input, mineable_cells);
+    if validate {
+        check_cell_count(seen_cells.len() as u64, max_unique_cells)?;
+    }
+
+    let milestones_reached = milestones_reached(&ore_inventory, input.milestones.as_deref())?;
+
+    let mut output = LeaderboardOutput {
+        version: JOURNAL_VERSION,
+        player_address: input.player_address,
+        total_mined,
+        score,
+        ore_count: ore_count as u8,
+        ore_inventory,
+        rare_inventory,
+        unique_cells,
+        provenance_root,
+        history_root,
+        longest_session_len,
+        longest_session_duration,
+        hasher: input.hasher,
+        input_cid,
+        scoring_mode: input.scoring_mode,
+        prestige_level,
+        onboarding_count: input.onboarding_count,
+        onboarding_bonus: input.onboarding_bonus,
+        grid_depth: input.grid_depth,
+        depth_bonus_per_layer: input.depth_bonus_per_layer,
+        inventory_commitment,
+        now: input.now,
+        max_age: input.max_age,
+        economy: input.economy.clone(),
+        economy_hash: hashing::economy_hash(&input.economy),
+        window_start: input.window_start,
+        window_end: input.window_end,
+        core_version: CORE_VERSION,
+        milestones_reached,
+        map,
+        coverage_bps: coverage_bps(unique_cells, mineable_cells, input.rounding),
+        checkpoint: input.checkpoint.clone(),
+        score_delta,
+        client_checksum: input.client_checksum,
+        ordered_achievements_met,
+        tiebreak_mode: input.tiebreak_mode,
+        tiebreak: tiebreak(input.tiebreak_mode, unique_cells, in_window_by_nonce.last().copied()),
+        inventory_hidden: false,
+        no_gaps: input.no_gaps,
+        nonce_range,
+        region_rows: input.region_rows,
+        region_cols: input.region_cols,
+        region_scores,
+        allow_remine: input.allow_remine,
+        remine_cooldown: input.remine_cooldown,
+        map_seed: input.map_seed,
+        beacon: input.beacon,
+        comeback_gap: input.comeback_gap,
+        comeback_bonus: input.comeback_bonus,
+        comeback_bonus_total,
+        lease_range,
+        lease_token: lease_token(&input.player_address, lease_range.as_ref()),
+        boosts_hash: hashing::boosts_hash(&input.boost_windows),
+        tier: score_tier(score, &input.tier_thresholds),
+        tier_thresholds: input.tier_thresholds.clone(),
+        score_hidden: false,
+        validated: validate,
+        party_addresses: input.party_addresses.clone(),
+        member_scores,
+        member_cells: member_cells.iter().map(|cells| cells.len() as u64).collect(),
+        rounding: input.rounding,
+    };
+    assert_output_invariants(&output);
+    if input.hide_inventory {
+        hide_inventory(&mut output, &input.inventory_salt);
+    }
+    if input.hide_score {
+        hide_score(&mut output);
+    }
+    mark("output");
+    Ok(Computed {
+        output,
+        cells: seen_cells,
+        ore_points,
+        mineable_cells,
+        max_unique_cells,
+    })
+}
+
+/// `tiebreak` in `mode` for `unique_cells` distinct cells and the last
+/// in-window record in nonce order
+pub fn tiebreak(mode: TiebreakMode, unique_cells: u64, last: Option<&MiningRecord>) -> u64 {
+    match mode {
+        TiebreakMode::UniqueCells => unique_cells,
+        TiebreakMode::EarliestLastNonce => u64::MAX - last.map_or(0, |record| record.nonce),
+    }
+}
+
+/// Score in the input's scoring mode from per-ore points and counts of
+/// `total_mined` records, onboarding bonus included
+fn total_score(
+    input: &LeaderboardInput,
+    ore_points: &[u64],
+    ore_inventory: &[u64],
+    total_mined: u64,
+) -> Result<u64, ValidationError> {
+    match input.scoring_mode {
+        ScoringMode::PerRecord => capped_total(ore_points, &input.economy)?,
+        ScoringMode::InventoryBonus => inventory_bonus_score(ore_inventory, &input.economy)?,
+    }
+    .checked_add(onboarding_total(
+        input.onboarding_count,
+        input.onboarding_bonus,
+        total_mined,
+    )?)
+    .ok_or(ValidationError::Overflow)
+}
+
+/// `score` minus the score of the checkpoint's records, after checking
+/// the checkpoint against the (already validated) in-window history
+fn score_delta(
+    input: &LeaderboardInput,
+    in_window_by_nonce: &[&MiningRecord],
+    checkpoint: &Checkpoint,
+    ore_inventory: &[u64],
+    score: u64,
+) -> Result<u64, ValidationError> {
+    if checkpoint.ore_inventory.iter().zip(ore_inventory).any(|(prior, now)| prior > now) {
+        return Err(ValidationError::CheckpointMismatch);
+    }
+    let mut prior_points: OreVec<u64> = vec![0; ore_inventory.len()];
+    let mut prior_inventory: OreVec<u64> = vec![0; ore_inventory.len()];
+    let prior_records =
+        &in_window_by_nonce[..in_window_by_nonce.len().min(checkpoint.total_mined as usize)];
+    let mut prior_mined: u64 = 0;
+    for record in prior_records {
+        let ore = record.ore_type as usize;
+        // Cannot overflow: the full history's points already fit
+        prior_points[ore] += record_points(record, input)?;
+        prior_inventory[ore] += 1;
+        prior_mined += 1;
+    }
+    if prior_mined != checkpoint.total_mined || prior_inventory != checkpoint.ore_inventory {
+        return Err(ValidationError::CheckpointMismatch);
+    }
+    let prior_score = total_score(input, &prior_points, &prior_inventory, prior_mined)?
+        .checked_add(comeback_total(prior_records, input.comeback_gap, input.comeback_bonus)?)
+        .ok_or(ValidationError::Overflow)?;
+    // A new ore type can lower an InventoryBonus score (fewer complete
+    // sets), in which case the records since earned nothing
+    Ok(score.saturating_sub(prior_score))
+}
+
+/// Replace the plaintext inventories of a finished output by their salted
+/// commitment
+pub fn hide_inventory(output: &mut LeaderboardOutput, salt: &[u8; 32]) {
+    output.inventory_commitment =
+        hashing::salted_inventory_commitment(&output.ore_inventory, &output.rare_inventory, salt);
+    output.ore_inventory.fill(0);
+    output.rare_inventory.fill(0);
+    output.inventory_hidden = true;
+}
+
+/// Zero the exact score of a finished output, leaving its tier
+pub fn hide_score(output: &mut LeaderboardOutput) {
+    output.score = 0;
+    output.score_delta = 0;
+    output.score_hidden = true;
+}
+
+/// Invariant: inventories are in canonical ore-index order, i.e. slot
+/// `i` holds exactly the in-window records of ore type `i`. Guards
+/// against a refactor that sorts or compacts committed arrays.
+fn debug_assert_ore_indexed(
+    input: &LeaderboardInput,
+    ore_inventory: &[u64],
+    rare_inventory: &[u64],
+) {
+    if cfg!(debug_assertions) {
+        for ore_type in 0..ore_inventory.len() {
+            let of_type = input.mining_history.iter().filter(|r| {
+                r.ore_type as usize == ore_type && in_window(r, input.window_start, input.window_end)
+            });
+            debug_assert_eq!(ore_inventory[ore_type], of_type.clone().count() as u64);
+            debug_assert_eq!(rare_inventory[ore_type], of_type.filter(|r| r.is_rare).count() as u64);
+        }
+    }
+}
+
+/// Invariants every output satisfies whatever the scoring rules: each
+/// record mines one distinct cell and has one ore type, and under the
+/// default economy every record is worth at least the smallest base
+/// score of 1 in either scoring mode. On-chain consumers rely on no ore
+/// type having more rare mines than mines, so that one is checked in
+/// release builds (and so in the guest) too.
+pub(crate) fn assert_output_invariants(output: &LeaderboardOutput) {
+    for (rare, ore) in output.rare_inventory.iter().zip(&output.ore_inventory) {
+        assert!(rare <= ore, "rare_inventory exceeds ore_inventory for an ore type");
+    }
+    if cfg!(debug_assertions) {
+        let ores: u64 = output.ore_inventory.iter().sum();
+        debug_assert!(output.unique_cells <= output.total_mined);
+        debug_assert_eq!(ores, output.total_mined);
+        if output.economy == Economy::default() {
+            debug_assert!(output.score >= output.total_mined);
+        }
+    }
+}
+
+/// Linear index of a record's (in-bounds) cell, layer by layer
+fn cell_index(record: &MiningRecord) -> usize {
+    (record.grid_z as usize * GRID_SIZE as usize + record.grid_y as usize) * GRID_SIZE as usize
+        + record.grid_x as usize
+}
+
+/// `(x, y, z)` of a linear cell index
+pub fn cell_coords(index: usize) -> (u8, u8, u8) {
+    let size = GRID_SIZE as usize;
+    ((index % size) as u8, (index / size % size) as u8, (index / GRID_CELLS) as u8)
+}
+
+/// Score contributed by a single (already validated) record: its base
+/// score (times the ore's rare multiplier if rare) plus
+/// `depth_bonus_per_layer` for every layer below the surface
+pub fn record_score(
+    record: &MiningRecord,
+    economy: &Economy,
+    depth_bonus_per_layer: u64,
+) -> Result<u64, ValidationError> {
+    let ore = record.ore_type as usize;
+    let base_score = economy.base_scores[ore];
+    let rarity_adjusted = if record.is_rare {
+        base_score
+            .checked_mul(economy.rare_multipliers[ore])
+            .ok_or(ValidationError::Overflow)?
+    } else {
+        base_score
+    };
+    depth_bonus_per_layer
+        .checked_mul(record.grid_z as u64)
+        .and_then(|bonus| rarity_adjusted.checked_add(bonus))
+        .ok_or(ValidationError::Overflow)
+}
+
+/// `record_score` times the largest multiplier of any boost window the
+/// record falls in (1 outside all of them)
+pub fn record_points(record: &MiningRecord, input: &LeaderboardInput) -> Result<u64, ValidationError> {
+    record_score(record, &input.economy, input.depth_bonus_per_layer)?
+        .checked_mul(boost_multiplier(record, &input.boost_windows))
+        .ok_or(ValidationError::Overflow)
+}
+
+/// Largest multiplier among the boost windows active for `record`
+pub fn boost_multiplier(record: &MiningRecord, boost_windows: &[BoostWindow]) -> u64 {
+    boost_windows
+        .iter()
+        .filter(|boost| {
+            boost.ore_type == record.ore_type
+                && (boost.start..=boost.end).contains(&record.timestamp)
+        })
+        .map(|boost| boost.multiplier)
+        .max()
+        .unwrap_or(1)
+}
+
+/// Every boost window must name one of the `ore_count` ore types, end no
+/// earlier than it starts and multiply by at least 1
+fn check_boost_windows(boost_windows: &[BoostWindow], ore_count: usize) -> Result<(), ValidationError> {
+    let invalid = boost_windows.iter().position(|boost| {
+        boost.ore_type as usize >= ore_count || boost.start > boost.end || boost.multiplier == 0
+    });
+    match invalid {
+        Some(index) => Err(ValidationError::InvalidBoost { index }),
+        None => Ok(()),
+    }
+}
+
+/// Party addresses must be distinct, and each index fit `miner_index`
+fn check_party(party_addresses: &[[u8; 20]]) -> Result<(), ValidationError> {
+    let mut sorted = party_addresses.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    if sorted.len() != party_addresses.len() || party_addresses.len() > u8::MAX as usize + 1 {
+        return Err(ValidationError::InvalidParty);
+    }
+    Ok(())
+}
+
+/// Tier thresholds must strictly ascend and fit a `u8` tier
+pub fn check_tier_thresholds(thresholds: &[u64]) -> Result<(), ValidationError> {
+    if thresholds.len() > u8::MAX as usize || thresholds.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(ValidationError::InvalidTierThresholds);
+    }
+    Ok(())
+}
+
+/// Number of (ascending) thresholds `score` reaches: a score equal to a
+/// threshold is in that threshold's tier, one below it is not
+pub fn score_tier(score: u64, thresholds: &[u64]) -> u8 {
+    thresholds.partition_point(|threshold| *threshold <= score) as u8
+}
+
+/// With `rarity_cutoffs` set, `is_rare` must say whether the first two
+/// bytes of the VRF output (big-endian) fall below the ore's cutoff
+pub fn rarity_consistent(record: &MiningRecord, economy: &Economy) -> bool {
+    match &economy.rarity_cutoffs {
+        Some(cutoffs) => {
+            let roll = u16::from_be_bytes([record.random_output[0], record.random_output[1]]);
+            record.is_rare == (roll < cutoffs[record.ore_type as usize])
+        }
+        None => true,
+    }
+}
+
+/// Number of ore types in `economy`, after checking each of its per-ore
+/// vectors has an entry per ore type
+pub fn economy_ore_count(economy: &Economy) -> Result<usize, ValidationError> {
+    let count = economy.ore_count();
+    if count == 0 || count > MAX_ORE_TYPES {
+        return Err(ValidationError::InvalidOreCount { count });
+    }
+    check_ore_vec("rare_multipliers", economy.rare_multipliers.len(), count)?;
+    check_ore_vec("score_caps", economy.score_caps.len(), count)?;
+    if let Some(cutoffs) = &economy.rarity_cutoffs {
+        check_ore_vec("rarity_cutoffs", cutoffs.len(), count)?;
+    }
+    Ok(count)
+}
+
+/// `economy_ore_count` of the input's economy, also checking the input's
+/// own per-ore vectors
+fn input_ore_count(input: &LeaderboardInput) -> Result<usize, ValidationError> {
+    let count = economy_ore_count(&input.economy)?;
+    if let Some(thresholds) = &input.prestige_thresholds {
+        check_ore_vec("prestige_thresholds", thresholds.len(), count)?;
+    }
+    if let Some(milestones) = &input.milestones {
+        check_ore_vec("milestones", milestones.len(), count)?;
+    }
+    if let Some(checkpoint) = &input.checkpoint {
+        check_ore_vec("checkpoint.ore_inventory", checkpoint.ore_inventory.len(), count)?;
+    }
+    Ok(count)
+}
+
+/// Reject a per-ore vector `field` of `len` entries for `ore_count` ore types
+pub fn check_ore_vec(field: &'static str, len: usize, ore_count: usize) -> Result<(), ValidationError> {
+    if len != ore_count {
+        return Err(ValidationError::OreVecLength {
+            field,
+            len,
+            expected: ore_count,
+        });
+    }
+    Ok(())
+}
+
+/// Sum of per-ore points, each first limited to the ore's score cap
+pub fn capped_total(ore_points: &[u64], economy: &Economy) -> Result<u64, ValidationError> {
+    ore_points
+        .iter()
+        .zip(&economy.score_caps)
+        .try_fold(0u64, |total, (&points, &cap)| total.checked_add(points.min(cap)))
+        .ok_or(ValidationError::Overflow)
+}
+
+/// Score derived purely from inventory composition: every ore at its
+/// base value (rarity is ignored, caps still apply), plus a
+/// set-completion bonus. A "set" is one of each ore type held, so the number of sets is the smallest
+/// nonzero count; each set pays `SET_COMPLETION_MULTIPLIER` for every
+/// distinct type held beyond the first, so a pile of a single ore earns
+/// no bonus at all.
+pub fn inventory_bonus_score(
+    ore_inventory: &[u64],
+    economy: &Economy,
+) -> Result<u64, ValidationError> {
+    let mut ore_points: OreVec<u64> = vec![0; ore_inventory.len()];
+    for (points, (count, &value)) in ore_points.iter_mut().zip(ore_inventory.iter().zip(&economy.base_scores)) {
+        *points = count.checked_mul(value).ok_or(ValidationError::Overflow)?;
+    }
+    let base = capped_total(&ore_points, economy)?;
+
+    let held = ore_inventory.iter().filter(|&&count| count > 0);
+    let extra_types = (held.clone().count() as u64).saturating_sub(1);
+    let sets = held.min().copied().unwrap_or(0);
+    sets.checked_mul(extra_types)
+        .and_then(|bonus| bonus.checked_mul(SET_COMPLETION_MULTIPLIER))
+        .and_then(|bonus| base.checked_add(bonus))
+        .ok_or(ValidationError::Overflow)
+}
+
+/// Lowest to highest nonce of a history, if it has any records
+fn lease_range(by_nonce: &[&MiningRecord]) -> Option<NonceRange> {
+    Some(NonceRange {
+        start: by_nonce.first()?.nonce,
+        end: by_nonce.last()?.nonce,
+    })
+}
+
+/// `hashing::lease_token` over `range`, or zero when there is none
+pub fn lease_token(player_address: &[u8; 20], range: Option<&NonceRange>) -> [u8; 32] {
+    range.map_or([0u8; 32], |range| hashing::lease_token(player_address, range))
+}
+
+/// Records of `in_window_by_nonce` more than `comeback_gap` seconds after
+/// the record before them each earn `comeback_bonus`
+pub fn comeback_total(
+    in_window_by_nonce: &[&MiningRecord],
+    comeback_gap: u64,
+    comeback_bonus: u64,
+) -> Result<u64, ValidationError> {
+    if comeback_bonus == 0 {
+        return Ok(0);
+    }
+    let comebacks = comeback_records(in_window_by_nonce, comeback_gap).count() as u64;
+    comeback_bonus
+        .checked_mul(comebacks)
+        .ok_or(ValidationError::Overflow)
+}
+
+/// Positions in `in_window_by_nonce` of the records that resume mining
+/// after more than `comeback_gap` seconds
+pub fn comeback_records<'a>(
+    in_window_by_nonce: &'a [&MiningRecord],
+    comeback_gap: u64,
+) -> impl Iterator<Item = usize> + 'a {
+    in_window_by_nonce
+        .windows(2)
+        .enumerate()
+        .filter(move |(_, pair)| pair[1].timestamp.saturating_sub(pair[0].timestamp) > comeback_gap)
+        .map(|(i, _)| i + 1)
+}
+
+/// Onboarding points over `record_count` records: the first
+/// `onboarding_count` records in nonce order each earn `onboarding_bonus`
+/// (every record, if the history is shorter). The bonus is added on top
+/// of the score in either scoring mode.
+pub fn onboarding_total(
+    onboarding_count: u64,
+    onboarding_bonus: u64,
+    record_count: u64,
+) -> Result<u64, ValidationError> {
+    onboarding_bonus
+        .checked_mul(onboarding_count.min(record_count))
+        .ok_or(ValidationError::Overflow)
+}
+
+/// The inventory commitment, which must equal `expected` if one is given
+pub fn checked_inventory_commitment(
+    ore_inventory: &[u64],
+    rare_inventory: &[u64],
+    expected: Option<[u8; 32]>,
+) -> Result<[u8; 32], ValidationError> {
+    let commitment = hashing::inventory_commitment(ore_inventory, rare_inventory);
+    match expected {
+        Some(expected) if expected != commitment => Err(ValidationError::InventoryCommitmentMismatch),
+        _ => Ok(commitment),
+    }
+}
+
+/// With `max_age` set, no record may postdate `now` and the newest one
+/// must be at most `max_age` seconds old. An empty history is never
+/// stale.
+pub fn check_freshness(
+    newest_timestamp: Option<u64>,
+    now: u64,
+    max_age: Option<u64>,
+) -> Result<(), ValidationError> {
+    let (Some(max_age), Some(newest)) = (max_age, newest_timestamp) else {
+        return Ok(());
+    };
+    let age = now
+        .checked_sub(newest)
+        .ok_or(ValidationError::RecordAfterNow { timestamp: newest })?;
+    if age > max_age {
+        return Err(ValidationError::StaleHistory { age, max_age });
+    }
+    Ok(())
+}
+
+/// Prestige is cumulative over ore types in canonical order: level `L`
+/// requires `thresholds[i]` of every ore type `i < L`, so each level adds
+/// the next, more valuable ore to the requirements. Returns the highest
+/// level fully satisfied, from 0 to the number of ore types.
+pub fn prestige_level(ore_inventory: &[u64], thresholds: &[u64]) -> u8 {
+    ore_inventory
+        .iter()
+        .zip(thresholds)
+        .take_while(|(count, needed)| count >= needed)
+        .count() as u8
+}
+
+/// Cells a player could mine: those the signed map places an ore in, or
+/// with no map every cell of every layer
+pub fn mineable_cells(input: &LeaderboardInput, has_map: bool) -> u64 {
+    if has_map {
+        let ore_count = input.economy.ore_count();
+        input.map_layout.iter().filter(|&&ore| (ore as usize) < ore_count).count() as u64
+    } else {
+        (GRID_CELLS * grid_layers(input.grid_depth)) as u64
+    }
+}
+
+/// Regions in a `rows` × `cols` partition of the grid, 0 if either is 0
+/// (no regions); both must divide `GRID_SIZE` otherwise
+pub fn region_count(rows: u8, cols: u8) -> Result<usize, ValidationError> {
+    if rows == 0 || cols == 0 {
+        return Ok(0);
+    }
+    if !GRID_SIZE.is_multiple_of(rows) || !GRID_SIZE.is_multiple_of(cols) {
+        return Err(ValidationError::InvalidRegions { rows, cols });
+    }
+    Ok(rows as usize * cols as usize)
+}
+
+/// Row-major index of an (in-bounds) record's region
+fn region_index(record: &MiningRecord, rows: u8, cols: u8) -> usize {
+    let row = (record.grid_y / (GRID_SIZE / rows)) as usize;
+    let col = (record.grid_x / (GRID_SIZE / cols)) as usize;
+    row * cols as usize + col
+}
+
+/// Most distinct cells a history may mine: the mineable cells, or
+/// `max_unique_cells` if that is lower. Duplicate and map checks already
+/// imply the first bound; asserting it again is a cheap sanity check
+/// against forged or corrupted histories.
+pub fn max_unique_cells(input: &LeaderboardInput, mineable_cells: u64) -> u64 {
+    input.max_unique_cells.map_or(mineable_cells, |max| max.min(mineable_cells))
+}
+
+/// Reject a history that mines more than `max` distinct cells, counting
+/// records outside the window too
+pub fn check_cell_count(cells: u64, max: u64) -> Result<(), ValidationError> {
+    if cells > max {
+        return Err(ValidationError::TooManyCells { cells, max });
+    }
+    Ok(())
+}
+
+/// Reject serialized input longer than `MAX_INPUT_BYTES`; the guest
+/// checks this before decoding its input
+pub fn check_input_size(bytes: usize) -> Result<(), ValidationError> {
+    if bytes > MAX_INPUT_BYTES {
+        return Err(ValidationError::InputTooLarge { bytes });
+    }
+    Ok(())
+}
+
+/// Reject a history of more than `MAX_HISTORY_RECORDS` records. This
+/// bounds one guest run, not the rules: `compute_leaderboard` accepts
+/// longer histories, which must be proven in shards.
+pub fn check_record_count(records: usize) -> Result<(), ValidationError> {
+    if records > MAX_HISTORY_RECORDS {
+        return Err(ValidationError::TooManyRecords { records });
+    }
+    Ok(())
+}
+
+/// `unique_cells * 10000 / mineable_cells` in integer arithmetic,
+/// rounded per `rounding`: 1 cell of 3 is 3333 with `Floor` or `Nearest`
+/// and 3334 with `Ceil`, and 1 of 20000 is 0, 1 and 1. A map without
+/// mineable cells has 0 coverage.
+pub fn coverage_bps(unique_cells: u64, mineable_cells: u64, rounding: RoundingMode) -> u16 {
+    if mineable_cells == 0 {
+        return 0;
+    }
+    let bps = fixed::div_round(unique_cells as u128 * 10_000, mineable_cells as u128, rounding);
+    bps.min(10_000) as u16
+}
+
+/// Per ore, the index of the highest milestone `ore_inventory` reaches,
+/// or `NO_MILESTONE` if none (or none are set). Milestones must be
+/// strictly ascending and at most 254 per ore, so an index always fits
+/// below the sentinel.
+pub fn milestones_reached(
+    ore_inventory: &[u64],
+    milestones: Option<&[Vec<u64>]>,
+) -> Result<OreVec<u8>, ValidationError> {
+    let mut reached = vec![NO_MILESTONE; ore_inventory.len()];
+    let Some(milestones) = milestones else {
+        return Ok(reached);
+    };
+    for (ore, thresholds) in milestones.iter().enumerate() {
+        let ascending = thresholds.windows(2).all(|pair| pair[0] < pair[1]);
+        if !ascending || thresholds.len() >= NO_MILESTONE as usize {
+            return Err(ValidationError::InvalidMilestones { ore });
+        }
+        let count = thresholds.partition_point(|&needed| needed <= ore_inventory[ore]);
+        if count > 0 {
+            reached[ore] = (count - 1) as u8;
+        }
+    }
+    Ok(reached)
+}
+
+/// Records sorted by nonce, checking that nonces are unique and that
+/// provenance moves forward with the nonce (a later mine cannot be
+/// authorized by an earlier block)
+fn nonce_order(records: &[MiningRecord]) -> Result<Vec<&MiningRecord>, ValidationError> {
+    let mut order: Vec<usize> = (0..records.len()).collect();
+    order.sort_by_key(|&index| records[index].nonce);
+    for pair in order.windows(2) {
+        let (prev, next) = (&records[pair[0]], &records[pair[1]]);
+        if prev.nonce == next.nonce {
+            return Err(ValidationError::NonceNotMonotonic { index: pair[1] });
+        }
+        if prev.block_height > next.block_height {
+            return Err(ValidationError::BlockHeightDecreasing { index: pair[1] });
+        }
+    }
+    Ok(order.into_iter().map(|index| &records[index]).collect())
+}
+
+/// With re-mining allowed, each mine of a cell (in nonce order) must come
+/// at least `cooldown` seconds after the previous mine of that cell
+fn check_remine_cooldown(by_nonce: &[&MiningRecord], cooldown: u64) -> Result<(), ValidationError> {
+    let mut last_mined: BTreeMap<usize, u64> = BTreeMap::new();
+    for record in by_nonce {
+        if let Some(previous) = last_mined.insert(cell_index(record), record.timestamp) {
+            if record.timestamp < previous.saturating_add(cooldown) {
+                return Err(ValidationError::RemineTooSoon {
+                    x: record.grid_x,
+                    y: record.grid_y,
+                    z: record.grid_z,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The first and last nonce (`None` for no records), checking that every
+/// nonce in between is present. Covers the whole history, window or not,
+/// so a proof with `no_gaps` shows nothing was left out.
+fn contiguous_nonces(by_nonce: &[&MiningRecord]) -> Result<Option<NonceRange>, ValidationError> {
+    if let Some(pair) = by_nonce.windows(2).find(|pair| pair[1].nonce != pair[0].nonce + 1) {
+        return Err(ValidationError::NonceGap { after: pair[0].nonce });
+    }
+    Ok(by_nonce.first().zip(by_nonce.last()).map(|(first, last)| NonceRange {
+        start: first.nonce,
+        end: last.nonce,
+    }))
+}
+
+/// Leaf data for a record's provenance tuple
+fn provenance_leaf_data(record: &MiningRecord) -> Vec<u8> {
+    let mut data = Vec::with_capacity(40);
+    data.extend_from_slice(&record.block_height.to_le_bytes());
+    data.extend_from_slice(&record.tx_hash);
+    data
+}
+
+/// Merkle root over the (block_height, tx_hash) tuples in nonce order
+fn provenance_root(hasher: &dyn CommitmentHasher, by_nonce: &[&MiningRecord]) -> [u8; 32] {
+    let leaves: Vec<[u8; 32]> = by_nonce
+        .iter()
+        .map(|record| hasher.leaf(&provenance_leaf_data(record)))
+        .collect();
+    hashing::merkle_root(hasher, &leaves)
+}
+
+/// Merkle root over the full records (canonical CBOR, see `cbor`) in
+/// nonce order, binding the whole history rather than just provenance
+fn history_root(hasher: &dyn CommitmentHasher, by_nonce: &[&MiningRecord]) -> [u8; 32] {
+    let leaves: Vec<[u8; 32]> = by_nonce
+        .iter()
+        .map(|record| hasher.leaf(&cbor::record(record)))
+        .collect();
+    hashing::merkle_root(hasher, &leaves)
+}
+
+/// Sessions are runs of consecutive mines (nonce order) where each gap
+/// stays below `session_gap`. Returns the record count and duration of
+/// the longest one by record count.
+fn longest_session(by_nonce: &[&MiningRecord], session_gap: u64) -> (u64, u64) {
+    let mut longest_len: u64 = 0;
+    let mut longest_duration: u64 = 0;
+    let mut session_start = 0;
+    for i in 0..by_nonce.len() {
+        if i > 0 {
+            let gap = by_nonce[i].timestamp.saturating_sub(by_nonce[i - 1].timestamp);
+            if gap >= session_gap {
+                session_start = i;
+            }
+        }
+        let len = (i - session_start + 1) as u64;
+        if len > longest_len {
+            longest_len = len;
+            longest_duration = by_nonce[i].timestamp.saturating_sub(by_nonce[session_start].timestamp);
+        }
+    }
+    (longest_len, longest_duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{input, record, valid_input};
+    use proptest::prelude::*;
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn provenance_root_is_deterministic() {
+        let records = vec![
+            MiningRecord {
+                tx_hash: [0xaa; 32],
+                ..record(0, 0, 0, 1)
+            },
+            MiningRecord {
+                tx_hash: [0xbb; 32],
+                ..record(1, 0, 0, 2)
+            },
+        ];
+        let root = compute_leaderboard(&input(records.clone())).unwrap().provenance_root;
+
+        let reversed = records.iter().rev().cloned().collect();
+        assert_eq!(compute_leaderboard(&input(reversed)).unwrap().provenance_root, root);
+
+        let leaf = |record: &MiningRecord| Sha256::digest(provenance_leaf_data(record));
+        let mut node = Sha256::new();
+        node.update(leaf(&records[0]));
+        node.update(leaf(&records[1]));
+        assert_eq!(root, <[u8; 32]>::from(node.finalize()));
+    }
+
+    /// Records on distinct cells mined at `timestamps`, in nonce order
+    fn timed(timestamps: &[u64]) -> Vec<MiningRecord> {
+        timestamps
+            .iter()
+            .enumerate()
+            .map(|(i, &timestamp)| MiningRecord {
+                timestamp,
+                ..record(i as u8, 0, 0, i as u64)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_break_splits_the_history_into_sessions() {
+        for (timestamps, longest) in [
+            (vec![0, 10, 20, 1_000, 1_010], (3, 20)),
+            (vec![0, 10, 1_000, 1_030, 1_040], (3, 40)),
+        ] {
+            let input = LeaderboardInput {
+                session_gap: 60,
+                ..input(timed(&timestamps))
+            };
+            let output = compute_leaderboard(&input).unwrap();
+            assert_eq!((output.longest_session_len, output.longest_session_duration), longest);
+        }
+    }
+
+    /// The error `input` is rejected with
+    fn rejection(input: LeaderboardInput) -> ValidationError {
+        compute_leaderboard(&input).expect_err("input should be rejected")
+    }
+
+    #[test]
+    fn each_invalid_history_has_its_own_error() {
+        assert_eq!(
+            rejection(input(vec![record(3, 4, 0, 1), record(3, 4, 1, 2)])),
+            ValidationError::DuplicateCell { x: 3, y: 4, z: 0 }
+        );
+        assert_eq!(
+            rejection(input(vec![record(0, 0, 0, 1), record(GRID_SIZE, 0, 0, 2)])),
+            ValidationError::OutOfBounds { index: 1 }
+        );
+        assert_eq!(
+            rejection(input(vec![record(0, 0, ORE_TYPES as u8, 1)])),
+            ValidationError::InvalidOreType {
+                index: 0,
+                value: ORE_TYPES as u8
+            }
+        );
+        assert_eq!(
+            rejection(input(vec![record(0, 0, 0, 1), record(1, 0, 0, 1)])),
+            ValidationError::NonceNotMonotonic { index: 1 }
+        );
+        let earlier_block = MiningRecord {
+            block_height: 0,
+            ..record(1, 0, 0, 2)
+        };
+        assert_eq!(
+            rejection(input(vec![record(0, 0, 0, 1), earlier_block])),
+            ValidationError::BlockHeightDecreasing { index: 1 }
+        );
+        assert_eq!(
+            rejection(LeaderboardInput {
+                hasher: 9,
+                ..input(vec![record(0, 0, 0, 1)])
+            }),
+            ValidationError::UnknownHasher { value: 9 }
+        );
+        let mut economy = Economy::default();
+        economy.base_scores[0] = u64::MAX;
+        assert_eq!(
+            rejection(LeaderboardInput {
+                economy,
+                ..input(vec![record(0, 0, 0, 1), record(1, 0, 0, 2)])
+            }),
+            ValidationError::Overflow
+        );
+    }
+
+    #[test]
+    fn both_scoring_modes_score_the_same_history() {
+        let rare_stone = MiningRecord {
+            is_rare: true,
+            ..record(1, 0, 0, 2)
+        };
+        let history = vec![
+            record(0, 0, 0, 1),
+            rare_stone,
+            record(2, 0, 1, 3),
+            record(3, 0, 1, 4),
+            record(4, 0, 2, 5),
+        ];
+
+        // 1 + 1 * 3 (stone) + 2 * 2 (coal) + 5 (iron)
+        let per_record = compute_leaderboard(&input(history.clone())).unwrap();
+        assert_eq!(per_record.scoring_mode, ScoringMode::PerRecord);
+        assert_eq!(per_record.score, 13);
+
+        // 2 * 1 + 2 * 2 + 5 at base value, plus one set of three types
+        let inventory_bonus = compute_leaderboard(&LeaderboardInput {
+            scoring_mode: ScoringMode::InventoryBonus,
+            ..input(history)
+        })
+        .unwrap();
+        assert_eq!(inventory_bonus.scoring_mode, ScoringMode::InventoryBonus);
+        assert_eq!(inventory_bonus.score, 11 + 2 * SET_COMPLETION_MULTIPLIER);
+        assert_eq!(inventory_bonus.ore_inventory, per_record.ore_inventory);
+    }
+
+    #[test]
+    fn committed_arrays_are_indexed_by_ore_type() {
+        // Ore type i is mined i + 1 times, highest ore first, and odd
+        // types once rarely, so sorting or compacting would show
+        let mut history = Vec::new();
+        for ore_type in (0..ORE_TYPES as u8).rev() {
+            for copy in 0..=ore_type {
+                history.push(MiningRecord {
+                    is_rare: ore_type % 2 == 1 && copy == 0,
+                    ..record(ore_type, copy, ore_type, history.len() as u64)
+                });
+            }
+        }
+        let output = compute_leaderboard(&input(history)).unwrap();
+        assert_eq!(output.ore_count as usize, ORE_TYPES);
+        assert_eq!(output.ore_inventory, (1..=ORE_TYPES as u64).collect::<Vec<_>>());
+        assert_eq!(output.rare_inventory, vec![0, 1, 0, 1, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn prestige_reports_the_highest_level_fully_met() {
+        // Two stone, a coal and an iron meet the first three levels; no
+        // copper stops level 4 however much silver follows
+        let history = vec![
+            record(0, 0, 0, 1),
+            record(1, 0, 0, 2),
+            record(2, 0, 1, 3),
+            record(3, 0, 2, 4),
+            record(4, 0, 4, 5),
+        ];
+        let output = compute_leaderboard(&LeaderboardInput {
+            prestige_thresholds: Some(vec![2, 1, 1, 1, 1, 1, 1, 1]),
+            ..input(history.clone())
+        })
+        .unwrap();
+        assert_eq!(output.prestige_level, 3);
+
+        assert_eq!(compute_leaderboard(&input(history)).unwrap().prestige_level, 0);
+    }
+
+    #[test]
+    fn only_the_first_onboarding_count_records_earn_the_bonus() {
+        let onboarding = |records| LeaderboardInput {
+            onboarding_count: 2,
+            onboarding_bonus: 10,
+            ..input(records)
+        };
+        // Stone scores 1: records 1 and 2 earn the bonus, record 3 does not
+        let three = vec![record(0, 0, 0, 3), record(1, 0, 0, 1), record(2, 0, 0, 2)];
+        let explanation = crate::explain::explain_score(&onboarding(three.clone())).unwrap();
+        let bonuses: Vec<(u64, u64)> =
+            explanation.records.iter().map(|record| (record.nonce, record.onboarding_bonus)).collect();
+        assert_eq!(bonuses, vec![(3, 0), (1, 10), (2, 10)]);
+        let output = compute_leaderboard(&onboarding(three)).unwrap();
+        assert_eq!(output.score, 3 + 2 * 10);
+        assert_eq!((output.onboarding_count, output.onboarding_bonus), (2, 10));
+
+        // A history shorter than onboarding_count earns it on every record
+        let one = vec![record(0, 0, 0, 1)];
+        assert_eq!(compute_leaderboard(&onboarding(one)).unwrap().score, 1 + 10);
+    }
+
+    #[test]
+    fn one_cell_at_two_depths_is_two_cells() {
+        let deep = MiningRecord {
+            grid_z: 2,
+            ..record(4, 4, 0, 2)
+        };
+        let layered = LeaderboardInput {
+            grid_depth: 3,
+            depth_bonus_per_layer: 5,
+            ..input(vec![record(4, 4, 0, 1), deep.clone()])
+        };
+        let output = compute_leaderboard(&layered).unwrap();
+        assert_eq!(output.unique_cells, 2);
+        // Stone scores 1 on the surface and 1 + 2 * 5 two layers down
+        assert_eq!(output.score, 1 + 11);
+
+        let again = MiningRecord {
+            nonce: 3,
+            block_height: 3,
+            ..deep.clone()
+        };
+        assert_eq!(
+            compute_leaderboard(&LeaderboardInput {
+                mining_history: vec![record(4, 4, 0, 1), deep.clone(), again],
+                ..layered
+            }),
+            Err(ValidationError::DuplicateCell { x: 4, y: 4, z: 2 })
+        );
+
+        // A flat grid has no second layer
+        assert_eq!(
+            compute_leaderboard(&input(vec![record(4, 4, 0, 1), deep])),
+            Err(ValidationError::OutOfBounds { index: 1 })
+        );
+    }
+
+    #[test]
+    fn a_mismatched_inventory_commitment_is_rejected() {
+        let history = vec![record(0, 0, 0, 1), record(1, 0, 5, 2)];
+        let snapshot = compute_leaderboard(&input(history.clone())).unwrap().inventory_commitment;
+        let expecting = |commitment| LeaderboardInput {
+            expected_inventory_commitment: Some(commitment),
+            ..input(history.clone())
+        };
+        assert_eq!(compute_leaderboard(&expecting(snapshot)).unwrap().inventory_commitment, snapshot);
+
+        let mut stale = snapshot;
+        stale[0] ^= 1;
+        assert_eq!(
+            compute_leaderboard(&expecting(stale)),
+            Err(ValidationError::InventoryCommitmentMismatch)
+        );
+    }
+
+    #[test]
+    fn stale_histories_fail_and_fresh_ones_pass() {
+        // The newest record is mined at second 3
+        let history = vec![record(0, 0, 0, 1), record(1, 0, 0, 3), record(2, 0, 0, 2)];
+        let checked = |now, max_age| {
+            compute_leaderboard(&LeaderboardInput {
+                now,
+                max_age: Some(max_age),
+                ..input(history.clone())
+            })
+        };
+        let fresh = checked(13, 10).unwrap();
+        assert_eq!((fresh.now, fresh.max_age), (13, Some(10)));
+        assert_eq!(checked(14, 10), Err(ValidationError::StaleHistory { age: 11, max_age: 10 }));
+        assert_eq!(checked(2, 10), Err(ValidationError::RecordAfterNow { timestamp: 3 }));
+    }
+
+    #[test]
+    fn moving_the_window_excludes_records_outside_it() {
+        // Stone at second 1, coal at 2, iron at 3
+        let history = vec![record(0, 0, 0, 1), record(1, 0, 1, 2), record(2, 0, 2, 3)];
+        let windowed = |window_start, window_end| {
+            compute_leaderboard(&LeaderboardInput {
+                window_start,
+                window_end,
+                ..input(history.clone())
+            })
+            .unwrap()
+        };
+        assert_eq!(windowed(None, None).score, 1 + 2 + 5);
+        let early = windowed(Some(1), Some(2));
+        assert_eq!((early.score, early.total_mined, early.unique_cells), (1 + 2, 2, 2));
+        assert_eq!((early.window_start, early.window_end), (Some(1), Some(2)));
+        assert_eq!(windowed(Some(2), Some(3)).score, 2 + 5);
+        assert_eq!(windowed(Some(4), None).score, 0);
+
+        // Records outside the window are still validated
+        let duplicate = vec![record(0, 0, 0, 1), record(0, 0, 1, 5)];
+        assert_eq!(
+            compute_leaderboard(&LeaderboardInput {
+                window_end: Some(2),
+                ..input(duplicate)
+            }),
+            Err(ValidationError::DuplicateCell { x: 0, y: 0, z: 0 })
+        );
+    }
+
+    #[test]
+    fn milestones_report_the_highest_threshold_reached() {
+        // 150 iron on distinct cells
+        let iron = (0..150u64)
+            .map(|nonce| record((nonce % 32) as u8, (nonce / 32) as u8, 2, nonce))
+            .collect();
+        let mut milestones = vec![Vec::new(); ORE_TYPES];
+        milestones[2] = vec![50, 100, 200];
+        milestones[0] = vec![1];
+        let output = compute_leaderboard(&LeaderboardInput {
+            milestones: Some(milestones),
+            ..input(iron)
+        })
+        .unwrap();
+        assert_eq!(output.milestones_reached[2], 1);
+        // No stone, and no thresholds for the rest
+        assert_eq!(output.milestones_reached[0], NO_MILESTONE);
+        assert_eq!(output.milestones_reached[1], NO_MILESTONE);
+    }
+
+    #[test]
+    fn coverage_is_unique_cells_over_mineable_cells() {
+        // A map with 8 mineable cells, the rest marked unmineable
+        let mut map_layout = vec![u8::MAX; GRID_CELLS];
+        map_layout[..8].fill(0);
+        let small = LeaderboardInput {
+            map_layout,
+            ..input(Vec::new())
+        };
+        assert_eq!(mineable_cells(&small, true), 8);
+        assert_eq!(coverage_bps(3, 8, RoundingMode::Floor), 3750);
+        assert_eq!(coverage_bps(8, 8, RoundingMode::Floor), 10_000);
+
+        // With no map the whole 32 × 32 grid is mineable: 4 / 1024 is 39.06 bps
+        let four = (0..4).map(|nonce| record(nonce as u8, 0, 0, nonce)).collect();
+        assert_eq!(compute_leaderboard(&input(four)).unwrap().coverage_bps, 39);
+    }
+
+    #[test]
+    fn the_delta_is_the_full_score_minus_the_snapshot_score() {
+        let history: Vec<MiningRecord> =
+            (0..6).map(|nonce| record(nonce as u8, 0, [0, 2, 4, 1, 6, 4][nonce as usize], nonce)).collect();
+        let snapshot = compute_leaderboard(&input(history[..3].to_vec())).unwrap();
+        let checkpoint = Checkpoint {
+            total_mined: snapshot.total_mined,
+            ore_inventory: snapshot.ore_inventory.clone(),
+        };
+        let full = compute_leaderboard(&LeaderboardInput {
+            checkpoint: Some(checkpoint.clone()),
+            ..input(history.clone())
+        })
+        .unwrap();
+        assert_eq!(full.score, compute_leaderboard(&input(history.clone())).unwrap().score);
+        assert_eq!(full.score_delta, full.score - snapshot.score);
+
+        // A snapshot that is not the history's first records is rejected
+        let mut forged = checkpoint;
+        forged.ore_inventory.swap(0, 1);
+        let forged = LeaderboardInput {
+            checkpoint: Some(forged),
+            ..input(history)
+        };
+        assert_eq!(compute_leaderboard(&forged), Err(ValidationError::CheckpointMismatch));
+    }
+
+    #[test]
+    fn a_history_cannot_claim_more_cells_than_the_grid_has() {
+        let flat = input(Vec::new());
+        assert_eq!(max_unique_cells(&flat, mineable_cells(&flat, false)), 1024);
+        assert_eq!(check_cell_count(1024, 1024), Ok(()));
+        assert_eq!(check_cell_count(1025, 1024), Err(ValidationError::TooManyCells { cells: 1025, max: 1024 }));
+
+        // A lower configured bound applies to the history itself
+        let five: Vec<MiningRecord> = (0..5).map(|nonce| record(nonce as u8, 0, 0, nonce)).collect();
+        let capped = |max| LeaderboardInput {
+            max_unique_cells: Some(max),
+            ..input(five.clone())
+        };
+        assert_eq!(compute_leaderboard(&capped(4)), Err(ValidationError::TooManyCells { cells: 5, max: 4 }));
+        assert_eq!(compute_leaderboard(&capped(5)).unwrap().unique_cells, 5);
+    }
+
+    #[test]
+    fn the_client_checksum_must_match_the_records() {
+        let history = vec![record(1, 0, 2, 2), record(0, 0, 0, 1)];
+        // What a client would compute: the records' CBOR in nonce order
+        let mut hasher = Sha256::new();
+        hasher.update(crate::cbor::record(&history[1]));
+        hasher.update(crate::cbor::record(&history[0]));
+        let checksum: [u8; 32] = hasher.finalize().into();
+        let with_checksum = |client_checksum| LeaderboardInput {
+            client_checksum: Some(client_checksum),
+            ..input(history.clone())
+        };
+        assert_eq!(compute_leaderboard(&with_checksum(checksum)).unwrap().client_checksum, Some(checksum));
+
+        let mut mismatched = checksum;
+        mismatched[0] ^= 1;
+        assert_eq!(compute_leaderboard(&with_checksum(mismatched)), Err(ValidationError::ClientChecksumMismatch));
+    }
+
+    #[test]
+    fn equal_scores_are_split_by_the_tiebreak() {
+        // Two coal (2 points each) against four stone (1 point each)
+        let coal = (0..2).map(|nonce| record(nonce as u8, 0, 1, nonce)).collect();
+        let stone = (0..4).map(|nonce| record(nonce as u8, 1, 0, nonce)).collect();
+        let tiebreaks = |tiebreak_mode| {
+            let [coal, stone] = [&coal, &stone].map(|history: &Vec<MiningRecord>| {
+                compute_leaderboard(&LeaderboardInput {
+                    tiebreak_mode,
+                    ..input(history.clone())
+                })
+                .unwrap()
+            });
+            assert_eq!((coal.score, stone.score), (4, 4));
+            assert_eq!((coal.tiebreak_mode, stone.tiebreak_mode), (tiebreak_mode, tiebreak_mode));
+            (coal.tiebreak, stone.tiebreak)
+        };
+        // The wider explorer wins
+        assert_eq!(tiebreaks(TiebreakMode::UniqueCells), (2, 4));
+        // Whoever finished first wins
+        assert_eq!(tiebreaks(TiebreakMode::EarliestLastNonce), (u64::MAX - 1, u64::MAX - 3));
+    }
+
+    #[test]
+    fn a_hidden_inventory_commits_only_to_a_salted_hash() {
+        let history: Vec<MiningRecord> = (0..3).map(|nonce| record(nonce as u8, 0, 2, nonce)).collect();
+        let hidden = |inventory_salt| {
+            compute_leaderboard(&LeaderboardInput {
+                hide_inventory: true,
+                inventory_salt,
+                ..input(history.clone())
+            })
+            .unwrap()
+        };
+        let plain = compute_leaderboard(&input(history.clone())).unwrap();
+        let output = hidden([5; 32]);
+        assert!(output.inventory_hidden);
+        assert_eq!(output.ore_inventory, vec![0; ORE_TYPES]);
+        assert_eq!(output.rare_inventory, vec![0; ORE_TYPES]);
+        assert_eq!(output.score, plain.score);
+
+        // Deterministic for one salt, and opened by it with the real counts
+        assert_eq!(hidden([5; 32]).inventory_commitment, output.inventory_commitment);
+        assert_eq!(
+            output.inventory_commitment,
+            hashing::salted_inventory_commitment(&plain.ore_inventory, &plain.rare_inventory, &[5; 32])
+        );
+        // Without the salt it is not the plain commitment to those counts
+        assert_ne!(output.inventory_commitment, plain.inventory_commitment);
+        assert_ne!(hidden([6; 32]).inventory_commitment, output.inventory_commitment);
+    }
+
+    #[test]
+    fn no_gaps_rejects_a_missing_nonce() {
+        let gapless = |nonces: &[u64]| LeaderboardInput {
+            no_gaps: true,
+            ..input(nonces.iter().map(|&nonce| record(nonce as u8, 0, 0, nonce)).collect())
+        };
+        let output = compute_leaderboard(&gapless(&[4, 5, 6])).unwrap();
+        assert_eq!(output.nonce_range, Some(NonceRange { start: 4, end: 6 }));
+        assert_eq!(compute_leaderboard(&gapless(&[4, 5, 7])), Err(ValidationError::NonceGap { after: 5 }));
+
+        // The same history is fine without the flag
+        let gap: Vec<MiningRecord> = gapless(&[4, 5, 7]).mining_history;
+        assert_eq!(compute_leaderboard(&input(gap)).unwrap().nonce_range, None);
+    }
+
+    #[test]
+    fn a_record_scores_only_in_its_region() {
+        // 2 × 2 regions of 16 × 16 cells; one gold (25 points) in the top left
+        let regions = |history| LeaderboardInput {
+            region_rows: 2,
+            region_cols: 2,
+            ..input(history)
+        };
+        let output = compute_leaderboard(&regions(vec![record(3, 5, 5, 1)])).unwrap();
+        assert_eq!(output.region_scores, vec![25, 0, 0, 0]);
+
+        // The last cell of the top-left region and the first of its neighbours
+        let output = compute_leaderboard(&regions(vec![record(15, 15, 0, 1), record(16, 15, 0, 2)])).unwrap();
+        assert_eq!(output.region_scores, vec![1, 1, 0, 0]);
+        assert_eq!(
+            compute_leaderboard(&LeaderboardInput {
+                region_rows: 3,
+                ..regions(Vec::new())
+            }),
+            Err(ValidationError::InvalidRegions { rows: 3, cols: 2 })
+        );
+    }
+
+    #[test]
+    fn re_mining_waits_out_the_cooldown() {
+        // Cell (4, 4) mined at second 10 and again at `second`, 60s cooldown
+        let remined = |second| LeaderboardInput {
+            allow_remine: true,
+            remine_cooldown: 60,
+            ..input(vec![record(4, 4, 1, 10), record(4, 4, 1, second)])
+        };
+        assert_eq!(compute_leaderboard(&remined(69)), Err(ValidationError::RemineTooSoon { x: 4, y: 4, z: 0 }));
+        let output = compute_leaderboard(&remined(70)).unwrap();
+        assert_eq!((output.total_mined, output.unique_cells, output.score), (2, 1, 4));
+
+        // Without allow_remine a second mine is a duplicate however late
+        let late: Vec<MiningRecord> = remined(1000).mining_history;
+        assert_eq!(compute_leaderboard(&input(late)), Err(ValidationError::DuplicateCell { x: 4, y: 4, z: 0 }));
+    }
+
+    #[test]
+    fn one_long_gap_earns_one_comeback() {
+        // Mined at seconds 0, 10, 20, then after a break at 500 and 510
+        let history = [0, 10, 20, 500, 510]
+            .iter()
+            .enumerate()
+            .map(|(i, &second)| MiningRecord {
+                timestamp: second,
+                ..record(i as u8, 0, 0, i as u64)
+            })
+            .collect();
+        let output = compute_leaderboard(&LeaderboardInput {
+            comeback_gap: 100,
+            comeback_bonus: 40,
+            ..input(history)
+        })
+        .unwrap();
+        assert_eq!(output.comeback_bonus_total, 40);
+        assert_eq!(output.score, 5 + 40);
+    }
+
+    #[test]
+    fn overlapping_histories_claim_related_leases() {
+        let lease = |nonces: std::ops::RangeInclusive<u64>, player_address| {
+            let output = compute_leaderboard(&LeaderboardInput {
+                player_address,
+                ..input(nonces.map(|nonce| record(nonce as u8, 0, 0, nonce)).collect())
+            })
+            .unwrap();
+            (output.lease_range.unwrap(), output.lease_token)
+        };
+        let (first, first_token) = lease(0..=4, [0x11; 20]);
+        let (second, second_token) = lease(3..=7, [0x11; 20]);
+        assert_eq!((first, second), (NonceRange { start: 0, end: 4 }, NonceRange { start: 3, end: 7 }));
+        // The ranges overlap, and each token opens to its range for the same
+        // player, which is how a contract detects the conflict
+        assert!(first.start <= second.end && second.start <= first.end);
+        assert_eq!(first_token, hashing::lease_token(&[0x11; 20], &first));
+        assert_eq!(second_token, hashing::lease_token(&[0x11; 20], &second));
+
+        // The same records claim the same lease; another player's do not
+        assert_eq!(lease(0..=4, [0x11; 20]).1, first_token);
+        assert_ne!(lease(0..=4, [0x22; 20]).1, first_token);
+        assert_eq!(lease_token(&[0x11; 20], None), [0; 32]);
+    }
+
+    #[test]
+    fn a_twelve_ore_economy_scores_every_ore() {
+        let economy = Economy {
+            base_scores: (1..=12).map(|points| points * 10).collect(),
+            rare_multipliers: vec![2; 12],
+            rarity_cutoffs: None,
+            score_caps: vec![u64::MAX; 12],
+        };
+        // One record of each ore, the last of ore 11 rare
+        let mut history: Vec<MiningRecord> = (0..12).map(|ore| record(ore, 0, ore, ore as u64)).collect();
+        history[11].is_rare = true;
+        let output = compute_leaderboard(&LeaderboardInput {
+            economy: economy.clone(),
+            ..input(history.clone())
+        })
+        .unwrap();
+        assert_eq!(output.ore_count, 12);
+        assert_eq!(output.ore_inventory, vec![1; 12]);
+        assert_eq!(output.rare_inventory[11], 1);
+        // 10 + 20 + … + 110 for ores 0 to 10, and 120 × 2 for the rare one
+        assert_eq!(output.score, 660 + 240);
+
+        // Ore 12 does not exist in it
+        history.push(record(12, 0, 12, 12));
+        assert_eq!(
+            compute_leaderboard(&LeaderboardInput {
+                economy,
+                ..input(history)
+            }),
+            Err(ValidationError::InvalidOreType { index: 12, value: 12 })
+        );
+    }
+
+    #[test]
+    fn a_boost_doubles_gold_only_inside_its_window() {
+        const GOLD: u8 = 5;
+        // Gold is 25 points; a 2× gold event from second 100 to 200
+        let gold_at = |second| {
+            let output = compute_leaderboard(&LeaderboardInput {
+                boost_windows: vec![BoostWindow {
+                    ore_type: GOLD,
+                    start: 100,
+                    end: 200,
+                    multiplier: 2,
+                }],
+                ..input(vec![MiningRecord {
+                    timestamp: second,
+                    ..record(0, 0, GOLD, 1)
+                }])
+            })
+            .unwrap();
+            output.score
+        };
+        assert_eq!(gold_at(150), 50);
+        assert_eq!((gold_at(100), gold_at(200)), (50, 50));
+        assert_eq!((gold_at(99), gold_at(201)), (25, 25));
+
+        // Other ores are not boosted inside the window
+        let silver = LeaderboardInput {
+            boost_windows: vec![BoostWindow {
+                ore_type: GOLD,
+                start: 0,
+                end: u64::MAX,
+                multiplier: 2,
+            }],
+            ..input(vec![record(0, 0, 4, 1)])
+        };
+        assert_eq!(compute_leaderboard(&silver).unwrap().score, 15);
+    }
+
+    #[test]
+    fn scores_at_a_threshold_reach_its_tier() {
+        let thresholds = [100, 1_000, 10_000];
+        for (tier, &threshold) in thresholds.iter().enumerate() {
+            assert_eq!(score_tier(threshold, &thresholds), tier as u8 + 1, "at {}", threshold);
+            assert_eq!(score_tier(threshold - 1, &thresholds), tier as u8, "below {}", threshold);
+        }
+        assert_eq!(score_tier(0, &thresholds), 0);
+        assert_eq!(score_tier(u64::MAX, &thresholds), 3);
+        assert_eq!(check_tier_thresholds(&[100, 100]), Err(ValidationError::InvalidTierThresholds));
+
+        // Hiding the score commits only the tier: 4 gold is 100 points
+        let gold = (0..4).map(|nonce| record(nonce as u8, 0, 5, nonce)).collect();
+        let output = compute_leaderboard(&LeaderboardInput {
+            tier_thresholds: thresholds.to_vec(),
+            hide_score: true,
+            ..input(gold)
+        })
+        .unwrap();
+        assert_eq!((output.tier, output.score, output.score_hidden), (1, 0, true));
+    }
+
+    #[test]
+    fn trusted_inputs_commit_the_same_aggregates_unvalidated() {
+        let history: Vec<MiningRecord> =
+            (0..20).map(|nonce| record(nonce as u8, 3, (nonce % 8) as u8, nonce)).collect();
+        let trusted = |history: Vec<MiningRecord>| {
+            compute_leaderboard(&LeaderboardInput {
+                trust_input: true,
+                ..input(history)
+            })
+        };
+        let validated = compute_leaderboard(&input(history.clone())).unwrap();
+        let output = trusted(history.clone()).unwrap();
+        assert!(validated.validated && !output.validated);
+        assert_eq!(
+            LeaderboardOutput {
+                validated: true,
+                ..output
+            },
+            validated
+        );
+
+        // Which is why it must never be ranked: a duplicated history proves too
+        let mut duplicated = history;
+        duplicated.push(record(0, 3, 7, 20));
+        assert_eq!(
+            compute_leaderboard(&input(duplicated.clone())),
+            Err(ValidationError::DuplicateCell { x: 0, y: 3, z: 0 })
+        );
+        assert_eq!(trusted(duplicated).unwrap().total_mined, 21);
+    }
+
+    #[test]
+    fn a_party_scores_each_member_and_the_total() {
+        // Two members taking turns: member 0 mines gold, member 1 coal
+        let history = (0..6)
+            .map(|nonce| MiningRecord {
+                miner_index: (nonce % 2) as u8,
+                ..record(nonce as u8, 0, if nonce % 2 == 0 { 5 } else { 1 }, nonce)
+            })
+            .collect();
+        let party = LeaderboardInput {
+            party_addresses: vec![[0xa1; 20], [0xb2; 20]],
+            ..input(history)
+        };
+        let output = compute_leaderboard(&party).unwrap();
+        assert_eq!(output.member_scores, vec![3 * 25, 3 * 2]);
+        assert_eq!(output.member_cells, vec![3, 3]);
+        assert_eq!(output.score, 3 * 25 + 3 * 2);
+        assert_eq!(output.party_addresses, party.party_addresses);
+
+        // A third member's record does not belong to the party
+        let mut outsider = party;
+        outsider.mining_history[4].miner_index = 2;
+        assert_eq!(compute_leaderboard(&outsider), Err(ValidationError::InvalidMiner { index: 4, value: 2 }));
+    }
+
+    /// Two ores worth 2^63 points a record, each capped at 1 point, so the
+    /// score fits but the raw points of both add past `u64::MAX`
+    fn cross_ore_overflow() -> LeaderboardInput {
+        let mut economy = LeaderboardInput::default().economy;
+        economy.base_scores[0] = 1 << 63;
+        economy.base_scores[1] = 1 << 63;
+        economy.score_caps[0] = 1;
+        economy.score_caps[1] = 1;
+        LeaderboardInput {
+            economy,
+            ..input(vec![record(0, 0, 0, 1), record(1, 0, 1, 2)])
+        }
+    }
+
+    #[test]
+    fn region_points_across_ores_cannot_overflow() {
+        assert_eq!(compute_leaderboard(&cross_ore_overflow()).unwrap().score, 2);
+        let one_region = LeaderboardInput {
+            region_rows: 1,
+            region_cols: 1,
+            ..cross_ore_overflow()
+        };
+        assert_eq!(compute_leaderboard(&one_region), Err(ValidationError::Overflow));
+    }
+
+    #[test]
+    fn member_points_across_ores_cannot_overflow() {
+        let party = LeaderboardInput {
+            party_addresses: vec![[0xa1; 20], [0xb2; 20]],
+            ..cross_ore_overflow()
+        };
+        assert_eq!(compute_leaderboard(&party), Err(ValidationError::Overflow));
+    }
+
+    #[test]
+    fn marked_scoring_marks_each_phase_in_order() {
+        let input = input(vec![record(0, 0, 0, 1), record(1, 0, 3, 2)]);
+        let mut phases = Vec::new();
+        let output = compute_leaderboard_marked(&input, &mut |phase| phases.push(phase)).unwrap();
+        assert_eq!(output, compute_leaderboard(&input).unwrap());
+        assert_eq!(phases, ["map", "records", "nonce-order", "roots", "output"]);
+    }
+
+    proptest! {
+        #[test]
+        fn valid_histories_satisfy_the_output_invariants(input in valid_input()) {
+            let output = compute_leaderboard(&input).unwrap();
+            let ores: u64 = output.ore_inventory.iter().sum();
+            let rares: u64 = output.rare_inventory.iter().sum();
+            prop_assert_eq!(output.total_mined, input.mining_history.len() as u64);
+            prop_assert!(output.unique_cells <= output.total_mined);
+            prop_assert_eq!(ores, output.total_mined);
+            prop_assert!(rares <= ores);
+            // Every record scores at least the smallest base score of 1
+            prop_assert!(output.score >= output.total_mined);
+            if input.scoring_mode == ScoringMode::PerRecord {
+                let most = BASE_SCORES.iter().max().unwrap() * RARE_MULTIPLIER;
+                prop_assert!(output.score <= output.total_mined * most);
+            }
+        }
+
+        #[test]
+        fn no_ore_type_has_more_rare_mines_than_mines(
+            input in valid_input(),
+            window_start in prop::option::of(0u64..64),
+            window_end in prop::option::of(0u64..64),
+        ) {
+            let input = LeaderboardInput { window_start, window_end, ..input };
+            let output = compute_leaderboard(&input).unwrap();
+            for (ore_type, (rare, ore)) in
+                output.rare_inventory.iter().zip(&output.ore_inventory).enumerate()
+            {
+                prop_assert!(rare <= ore, "ore type {}: {} rare of {}", ore_type, rare, ore);
+            }
+        }
+    }
+}
@@ -0,0 +1,591 @@
+//! Sharded proving
+//!
+//! A history too large to prove in one go is split into shards over
+//! consecutive nonce ranges. Each shard is proven on its own by the main
+//! guest (with `LeaderboardInput::shard` set), which commits a
+//! `ShardOutput`. The aggregator guest then verifies every shard receipt,
+//! checks that the ranges tile one contiguous nonce range with no gaps or
+//! overlaps, and folds the partial outputs into one `LeaderboardOutput`.
+//!
+//! Not every field composes exactly:
+//!
+//! - `total_mined`, `score`, `ore_inventory`, `rare_inventory`,
+//!   `unique_cells`, `prestige_level`, `tier`, `inventory_commitment`, the
+//!   economy and the map, bonus and boost parameters equal the
+//!   single-proof values. Score caps are applied to the summed per-ore
+//!   points, not per shard. Cells are tracked as bitsets so a cell mined
+//!   in two different shards is still rejected
+//!   as a duplicate. Each shard's `onboarding_count` is what is left of the
+//!   bonus after the shards before it. Every record is in exactly one
+//!   shard's cell set, window or not, so out-of-window cells still count
+//!   as duplicates across shards. The expected inventory snapshot
+//!   and `max_age` are checked by the aggregator rather than the shards.
+//! - `longest_session_len`/`_duration` are the longest session within
+//!   any one shard; a session that straddles a shard boundary is counted
+//!   as two, so this is a lower bound.
+//! - `provenance_root` and `history_root` are Merkle roots of the shard
+//!   roots in nonce order, not roots over every record.
+//! - `input_cid` is always zero, since no single input was proven.
+//! - The journal is always the full layout; shards ignore
+//!   `minimal_journal`.
+//! - A checkpoint, a client checksum, ordered achievements and the
+//!   comeback bonus need the whole history in one place, so an input
+//!   with any of them cannot be sharded (`UnshardableField`) rather than
+//!   aggregating to a different output. The aggregate commits none of
+//!   them (`score_delta` is the score).
+//! - Shards never hide their inventories, since the aggregator sums
+//!   them; `hide_inventory` is applied by the aggregator instead. Tiers
+//!   and `hide_score` likewise need the whole score, so shards drop the
+//!   thresholds and the aggregator applies both.
+//! - Shard cell sets must be disjoint, so a history with `allow_remine`
+//!   cannot be sharded at all (`RemineAcrossShards`).
+//!
+//! Shards proven with `trust_input` must all be, and the aggregate then
+//! commits `validated: false` too. The aggregator's cross-shard checks
+//! still run.
+//!
+//! The aggregate's `lease_range` runs from the lowest shard lease's start
+//! to the highest one's end, and equals the single-proof value.
+//!
+//! With `no_gaps` each shard checks its own records, and the aggregator
+//! checks that each shard's first nonce follows the previous shard's
+//! last, so `nonce_range` also equals the single-proof value.
+//!
+//! Nonces are unique across shards because the ranges are disjoint.
+//! Block heights are checked to be non-decreasing within each shard, and
+//! the aggregator checks each shard's first block height against the
+//! previous shard's last, so the whole history is held to the same rule.
+
+use crate::cells::CellSet;
+use crate::error::ValidationError;
+use crate::hashing;
+use crate::score::{self, compute_full, grid_layers, GRID_CELLS};
+use crate::types::*;
+
+/// Reject an input whose sharded proof would not equal its single
+/// proof: one that re-mines cells, or sets a field that needs the whole
+/// history in one place
+pub fn check_shardable(input: &LeaderboardInput) -> Result<(), ValidationError> {
+    if input.allow_remine {
+        return Err(ValidationError::RemineAcrossShards);
+    }
+    let field = if input.checkpoint.is_some() {
+        "checkpoint"
+    } else if input.client_checksum.is_some() {
+        "client_checksum"
+    } else if !input.ordered_achievements.is_empty() {
+        "ordered_achievements"
+    } else if input.comeback_bonus > 0 {
+        "comeback_bonus"
+    } else {
+        return Ok(());
+    };
+    Err(ValidationError::UnshardableField { field })
+}
+
+/// Split `input` into shards of at most `shard_size` records each, in
+/// nonce order. The ranges start at the lowest nonce, end at the highest
+/// and meet without gaps, so the shards aggregate cleanly.
+pub fn split_into_shards(input: &LeaderboardInput, shard_size: usize) -> Vec<LeaderboardInput> {
+    let mut by_nonce = input.mining_history.clone();
+    by_nonce.sort_by_key(|record| record.nonce);
+    let chunks: Vec<&[MiningRecord]> = by_nonce.chunks(shard_size.max(1)).collect();
+
+    let mut records_before: u64 = 0;
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let start = chunk[0].nonce;
+            let end = match chunks.get(i + 1) {
+                Some(next) => next[0].nonce.saturating_sub(1),
+                None => chunk[chunk.len() - 1].nonce,
+            };
+            // Onboarding counts from the start of the whole history
+            let onboarding_count = input.onboarding_count.saturating_sub(records_before);
+            records_before += chunk
+                .iter()
+                .filter(|record| score::in_window(record, input.window_start, input.window_end))
+                .count() as u64;
+            LeaderboardInput {
+                mining_history: chunk.to_vec(),
+                shard: Some(NonceRange { start, end }),
+                onboarding_count,
+                // Only the whole history can match the snapshot or be
+                // judged for freshness; the aggregator checks both
+                expected_inventory_commitment: None,
+                minimal_journal: false,
+                checkpoint: None,
+                client_checksum: None,
+                ordered_achievements: Vec::new(),
+                comeback_bonus: 0,
+                hide_inventory: false,
+                tier_thresholds: Vec::new(),
+                hide_score: false,
+                max_age: None,
+                ..input.clone()
+            }
+        })
+        .collect()
+}
+
+/// The aggregator's input for `input` split into `shards`, each proven
+/// by the guest `shard_image_id`: the whole-history settings
+/// `split_into_shards` left out of the shards
+pub fn aggregate_input(
+    input: &LeaderboardInput,
+    shard_image_id: [u32; 8],
+    shards: Vec<ShardOutput>,
+) -> AggregateInput {
+    AggregateInput {
+        shard_image_id,
+        player_address: input.player_address,
+        shards,
+        prestige_thresholds: input.prestige_thresholds.clone(),
+        milestones: input.milestones.clone(),
+        hide_inventory: input.hide_inventory,
+        inventory_salt: input.inventory_salt,
+        tier_thresholds: input.tier_thresholds.clone(),
+        hide_score: input.hide_score,
+        expected_inventory_commitment: input.expected_inventory_commitment,
+        now: input.now,
+        max_age: input.max_age,
+    }
+}
+
+/// Validate one shard of a history and compute its partial output
+pub fn compute_shard(input: &LeaderboardInput) -> Result<ShardOutput, ValidationError> {
+    let range = input.shard.unwrap_or(NonceRange {
+        start: 0,
+        end: u64::MAX,
+    });
+    let outside = input
+        .mining_history
+        .iter()
+        .position(|record| record.nonce < range.start || record.nonce > range.end);
+    if let Some(index) = outside {
+        return Err(ValidationError::NonceOutsideShard { index });
+    }
+
+    let computed = compute_full(input)?;
+    let first = input.mining_history.iter().min_by_key(|record| record.nonce);
+    let last = input.mining_history.iter().max_by_key(|record| record.nonce);
+    Ok(ShardOutput {
+        output: computed.output,
+        range,
+        cells: computed.cells.words().to_vec(),
+        ore_points: computed.ore_points,
+        mineable_cells: computed.mineable_cells,
+        max_unique_cells: computed.max_unique_cells,
+        newest_timestamp: input.mining_history.iter().map(|record| record.timestamp).max(),
+        first_block_height: first.map(|record| record.block_height),
+        last_block_height: last.map(|record| record.block_height),
+    })
+}
+
+/// Fold verified shard outputs into the output of the whole history
+pub fn aggregate_shards(input: &AggregateInput) -> Result<LeaderboardOutput, ValidationError> {
+    let mut shards: Vec<&ShardOutput> = input.shards.iter().collect();
+    shards.sort_by_key(|shard| shard.range.start);
+    let first = &shards.first().ok_or(ValidationError::NoShards)?.output;
+
+    for pair in shards.windows(2) {
+        let (prev, next) = (pair[0].range, pair[1].range);
+        if next.start <= prev.end {
+            return Err(ValidationError::ShardOverlap { at: next.start });
+        }
+        if next.start != prev.end + 1 {
+            return Err(ValidationError::ShardGap { after: prev.end });
+        }
+    }
+    // Empty shards have no block heights, so compare across them
+    let heights: Vec<(u64, u64, u64)> = shards
+        .iter()
+        .filter_map(|shard| {
+            Some((shard.range.start, shard.first_block_height?, shard.last_block_height?))
+        })
+        .collect();
+    for pair in heights.windows(2) {
+        let ((_, _, prev_last), (start, next_first, _)) = (pair[0], pair[1]);
+        if next_first < prev_last {
+            return Err(ValidationError::ShardBlockHeightDecreasing { start });
+        }
+    }
+
+    let hasher = hashing::select(first.hasher)
+        .ok_or(ValidationError::UnknownHasher { value: first.hasher })?;
+    let capacity = GRID_CELLS * grid_layers(first.grid_depth);
+    let cell_words = capacity.div_ceil(64);
+
+    let mut cells = CellSet::with_capacity(capacity);
+    let mut total_mined: u64 = 0;
+    let mut unique_cells: u64 = 0;
+    let ore_count = score::economy_ore_count(&first.economy)?;
+    if let Some(thresholds) = &input.prestige_thresholds {
+        score::check_ore_vec("prestige_thresholds", thresholds.len(), ore_count)?;
+    }
+    if let Some(milestones) = &input.milestones {
+        score::check_ore_vec("milestones", milestones.len(), ore_count)?;
+    }
+    score::check_tier_thresholds(&input.tier_thresholds)?;
+    let mut ore_points: OreVec<u64> = vec![0; ore_count];
+    let mut ore_inventory: OreVec<u64> = vec![0; ore_count];
+    let mut rare_inventory: OreVec<u64> = vec![0; ore_count];
+    let mut longest_session = (0, 0);
+    let mut shard_roots = Vec::with_capacity(shards.len());
+    let mut shard_history_roots = Vec::with_capacity(shards.len());
+    let mut onboarding_points: u64 = 0;
+    let mut region_scores = vec![0u64; first.region_scores.len()];
+    let mut member_scores = vec![0u64; first.party_addresses.len()];
+    let mut member_cells = vec![0u64; first.party_addresses.len()];
+
+    if shards.iter().any(|shard| shard.output.allow_remine) {
+        return Err(ValidationError::RemineAcrossShards);
+    }
+
+    for shard in &shards {
+        let output = &shard.output;
+        let shard_cells = CellSet::from_words(shard.cells.clone());
+        if output.player_address != input.player_address
+            || output.hasher != first.hasher
+            || output.scoring_mode != first.scoring_mode
+            || shard.range.start > shard.range.end
+            || shard.cells.len() != cell_words
+            || output.unique_cells > shard_cells.len() as u64
+            || output.grid_depth != first.grid_depth
+            || output.depth_bonus_per_layer != first.depth_bonus_per_layer
+            || output.onboarding_bonus != first.onboarding_bonus
+            || output.economy != first.economy
+            || output.boosts_hash != first.boosts_hash
+            || output.ore_inventory.len() != ore_count
+            || output.rare_inventory.len() != ore_count
+            || shard.ore_points.len() != ore_count
+            || output.tiebreak_mode != first.tiebreak_mode
+            || output.inventory_hidden
+            || output.score_hidden
+            || output.validated != first.validated
+            || output.rounding != first.rounding
+            || output.party_addresses != first.party_addresses
+            || output.member_scores.len() != first.party_addresses.len()
+            || output.member_cells.len() != first.party_addresses.len()
+            || output.no_gaps != first.no_gaps
+            || (output.region_rows, output.region_cols) != (first.region_rows, first.region_cols)
+            || output.region_scores.len() != first.region_scores.len()
+            || output.core_version != CORE_VERSION
+            || output.map != first.map
+            || (output.map_seed, output.beacon) != (first.map_seed, first.beacon)
+            || shard.mineable_cells != shards[0].mineable_cells
+            || shard.max_unique_cells != shards[0].max_unique_cells
+            || (output.window_start, output.window_end) != (first.window_start, first.window_end)
+            || output.onboarding_count != first.onboarding_count.saturating_sub(total_mined)
+        {
+            return Err(ValidationError::ShardMismatch { start: shard.range.start });
+        }
+
+        if let Some(index) = cells.first_overlap(&shard_cells) {
+            let (x, y, z) = score::cell_coords(index);
+            return Err(ValidationError::DuplicateCell { x, y, z });
+        }
+        cells.union_with(&shard_cells);
+
+        onboarding_points = onboarding_points
+            .checked_add(score::onboarding_total(
+                output.onboarding_count,
+                output.onboarding_bonus,
+                output.total_mined,
+            )?)
+            .ok_or(ValidationError::Overflow)?;
+        total_mined = total_mined
+            .checked_add(output.total_mined)
+            .ok_or(ValidationError::Overflow)?;
+        // Shard cell sets are disjoint, so their in-window counts add up
+        unique_cells += output.unique_cells;
+        for ore_type in 0..ore_count {
+            ore_points[ore_type] = ore_points[ore_type]
+                .checked_add(shard.ore_points[ore_type])
+                .ok_or(ValidationError::Overflow)?;
+            ore_inventory[ore_type] = ore_inventory[ore_type]
+                .checked_add(output.ore_inventory[ore_type])
+                .ok_or(ValidationError::Overflow)?;
+            rare_inventory[ore_type] = rare_inventory[ore_type]
+                .checked_add(output.rare_inventory[ore_type])
+                .ok_or(ValidationError::Overflow)?;
+        }
+        if output.longest_session_len > longest_session.0 {
+            longest_session = (output.longest_session_len, output.longest_session_duration);
+        }
+        shard_roots.push(output.provenance_root);
+        shard_history_roots.push(output.history_root);
+        for (total, points) in region_scores.iter_mut().zip(&output.region_scores) {
+            *total = total.checked_add(*points).ok_or(ValidationError::Overflow)?;
+        }
+        for (total, points) in member_scores.iter_mut().zip(&output.member_scores) {
+            *total = total.checked_add(*points).ok_or(ValidationError::Overflow)?;
+        }
+        // Disjoint across shards, like unique_cells
+        for (total, cells) in member_cells.iter_mut().zip(&output.member_cells) {
+            *total += cells;
+        }
+    }
+    score::check_cell_count(cells.len() as u64, shards[0].max_unique_cells)?;
+    let nonce_range =
+        joined_nonce_ranges(shards.iter().filter_map(|shard| shard.output.nonce_range))?;
+    let lease_range = shards
+        .iter()
+        .filter_map(|shard| shard.output.lease_range)
+        .reduce(|joined, lease| NonceRange {
+            start: joined.start.min(lease.start),
+            end: joined.end.max(lease.end),
+        });
+
+    // Shards cover consecutive nonce ranges, so the whole history's last
+    // nonce is the largest shard's, i.e. the smallest shard tiebreak
+    let tiebreak = match first.tiebreak_mode {
+        TiebreakMode::UniqueCells => unique_cells,
+        TiebreakMode::EarliestLastNonce => {
+            shards.iter().map(|shard| shard.output.tiebreak).min().unwrap_or(u64::MAX)
+        }
+    };
+
+    // Caps apply to the whole history, so both modes are recomputed from
+    // the summed per-ore totals rather than by adding shard scores
+    let score = match first.scoring_mode {
+        ScoringMode::PerRecord => score::capped_total(&ore_points, &first.economy)?,
+        ScoringMode::InventoryBonus => score::inventory_bonus_score(&ore_inventory, &first.economy)?,
+    }
+    .checked_add(onboarding_points)
+    .ok_or(ValidationError::Overflow)?;
+    let prestige_level = input
+        .prestige_thresholds
+        .as_ref()
+        .map_or(0, |thresholds| score::prestige_level(&ore_inventory, thresholds));
+
+    score::check_freshness(
+        shards.iter().filter_map(|shard| shard.newest_timestamp).max(),
+        input.now,
+        input.max_age,
+    )?;
+    let inventory_commitment = score::checked_inventory_commitment(
+        &ore_inventory,
+        &rare_inventory,
+        input.expected_inventory_commitment,
+    )?;
+
+    let milestones_reached =
+        score::milestones_reached(&ore_inventory, input.milestones.as_deref())?;
+
+    let mut output = LeaderboardOutput {
+        version: JOURNAL_VERSION,
+        player_address: input.player_address,
+        total_mined,
+        score,
+        ore_count: first.ore_count,
+        ore_inventory,
+        rare_inventory,
+        unique_cells,
+        provenance_root: hashing::merkle_root(hasher, &shard_roots),
+        history_root: hashing::merkle_root(hasher, &shard_history_roots),
+        longest_session_len: longest_session.0,
+        longest_session_duration: longest_session.1,
+        hasher: first.hasher,
+        input_cid: [0u8; 32],
+        scoring_mode: first.scoring_mode,
+        prestige_level,
+        onboarding_count: first.onboarding_count,
+        onboarding_bonus: first.onboarding_bonus,
+        grid_depth: first.grid_depth,
+        depth_bonus_per_layer: first.depth_bonus_per_layer,
+        inventory_commitment,
+        now: input.now,
+        max_age: input.max_age,
+        economy: first.economy.clone(),
+        economy_hash: first.economy_hash,
+        window_start: first.window_start,
+        window_end: first.window_end,
+        core_version: CORE_VERSION,
+        milestones_reached,
+        map: first.map,
+        coverage_bps: score::coverage_bps(unique_cells, shards[0].mineable_cells, first.rounding),
+        checkpoint: None,
+        score_delta: score,
+        client_checksum: None,
+        ordered_achievements_met: Vec::new(),
+        tiebreak_mode: first.tiebreak_mode,
+        tiebreak,
+        inventory_hidden: false,
+        no_gaps: first.no_gaps,
+        nonce_range,
+        region_rows: first.region_rows,
+        region_cols: first.region_cols,
+        region_scores,
+        allow_remine: false,
+        remine_cooldown: 0,
+        map_seed: first.map_seed,
+        beacon: first.beacon,
+        comeback_gap: 0,
+        comeback_bonus: 0,
+        comeback_bonus_total: 0,
+        lease_range,
+        lease_token: score::lease_token(&input.player_address, lease_range.as_ref()),
+        boosts_hash: first.boosts_hash,
+        tier: score::score_tier(score, &input.tier_thresholds),
+        tier_thresholds: input.tier_thresholds.clone(),
+        score_hidden: false,
+        validated: first.validated,
+        party_addresses: first.party_addresses.clone(),
+        member_scores,
+        member_cells,
+        rounding: first.rounding,
+    };
+    score::assert_output_invariants(&output);
+    if input.hide_inventory {
+        score::hide_inventory(&mut output, &input.inventory_salt);
+    }
+    if input.hide_score {
+        score::hide_score(&mut output);
+    }
+    Ok(output)
+}
+
+/// Join the gap-free nonce ranges of consecutive shards into one,
+/// failing if one does not pick up right after the last
+fn joined_nonce_ranges(
+    mut ranges: impl Iterator<Item = NonceRange>,
+) -> Result<Option<NonceRange>, ValidationError> {
+    let Some(mut joined) = ranges.next() else {
+        return Ok(None);
+    };
+    for next in ranges {
+        if next.start != joined.end.wrapping_add(1) {
+            return Err(ValidationError::NonceGap { after: joined.end });
+        }
+        joined.end = next.end;
+    }
+    Ok(Some(joined))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::record;
+
+    fn input() -> LeaderboardInput {
+        LeaderboardInput {
+            player_address: [0x11; 20],
+            ..LeaderboardInput::default()
+        }
+    }
+
+    #[test]
+    fn plain_inputs_are_shardable() {
+        assert_eq!(check_shardable(&input()), Ok(()));
+    }
+
+    #[test]
+    fn whole_history_fields_are_not_shardable() {
+        let checkpoint = LeaderboardInput {
+            checkpoint: Some(Checkpoint {
+                total_mined: 0,
+                ore_inventory: vec![0; ORE_TYPES],
+            }),
+            ..input()
+        };
+        let client_checksum = LeaderboardInput {
+            client_checksum: Some([0; 32]),
+            ..input()
+        };
+        let ordered_achievements = LeaderboardInput {
+            ordered_achievements: vec![OrderedAchievement { steps: vec![] }],
+            ..input()
+        };
+        let comeback_bonus = LeaderboardInput {
+            comeback_bonus: 1,
+            ..input()
+        };
+        for (input, field) in [
+            (checkpoint, "checkpoint"),
+            (client_checksum, "client_checksum"),
+            (ordered_achievements, "ordered_achievements"),
+            (comeback_bonus, "comeback_bonus"),
+        ] {
+            assert_eq!(check_shardable(&input), Err(ValidationError::UnshardableField { field }));
+        }
+    }
+
+    #[test]
+    fn remining_histories_are_not_shardable() {
+        let input = LeaderboardInput {
+            allow_remine: true,
+            ..input()
+        };
+        assert_eq!(check_shardable(&input), Err(ValidationError::RemineAcrossShards));
+    }
+
+    #[test]
+    fn two_shards_aggregate_to_the_single_proof_output() {
+        let mut whole = LeaderboardInput {
+            onboarding_count: 4,
+            onboarding_bonus: 7,
+            mining_history: (0..6)
+                .map(|nonce| record(nonce as u8, 1, nonce as u8 % 3, nonce))
+                .collect(),
+            ..input()
+        };
+        whole.mining_history[2].is_rare = true;
+        let shards = split_into_shards(&whole, 3);
+        assert_eq!(shards.len(), 2);
+        let outputs = shards.iter().map(|shard| compute_shard(shard).unwrap()).collect();
+        let aggregate = aggregate_shards(&aggregate_input(&whole, [0; 8], outputs)).unwrap();
+
+        // Roots and sessions are documented to compose differently
+        let single = LeaderboardOutput {
+            provenance_root: aggregate.provenance_root,
+            history_root: aggregate.history_root,
+            longest_session_len: aggregate.longest_session_len,
+            longest_session_duration: aggregate.longest_session_duration,
+            ..score::compute_leaderboard(&whole).unwrap()
+        };
+        assert_eq!(aggregate, single);
+    }
+
+    #[test]
+    fn block_heights_must_not_decrease_across_shards() {
+        let mut whole = LeaderboardInput {
+            mining_history: (0..6).map(|nonce| record(nonce as u8, 1, 0, nonce)).collect(),
+            ..input()
+        };
+        // Each shard is monotonic on its own, but the second starts lower
+        for (record, block_height) in whole.mining_history.iter_mut().zip([5, 6, 7, 1, 2, 3]) {
+            record.block_height = block_height;
+        }
+        assert_eq!(
+            score::compute_leaderboard(&whole),
+            Err(ValidationError::BlockHeightDecreasing { index: 3 })
+        );
+        let outputs = split_into_shards(&whole, 3)
+            .iter()
+            .map(|shard| compute_shard(shard).unwrap())
+            .collect();
+        assert_eq!(
+            aggregate_shards(&aggregate_input(&whole, [0; 8], outputs)),
+            Err(ValidationError::ShardBlockHeightDecreasing { start: 3 })
+        );
+    }
+
+    #[test]
+    fn summed_inventories_cannot_overflow() {
+        let whole = LeaderboardInput {
+            mining_history: (0..2).map(|nonce| record(nonce as u8, 1, 0, nonce)).collect(),
+            ..input()
+        };
+        let mut outputs: Vec<ShardOutput> = split_into_shards(&whole, 1)
+            .iter()
+            .map(|shard| compute_shard(shard).unwrap())
+            .collect();
+        outputs[1].output.ore_inventory[0] = u64::MAX;
+        assert_eq!(
+            aggregate_shards(&aggregate_input(&whole, [0; 8], outputs)),
+            Err(ValidationError::Overflow)
+        );
+    }
+}
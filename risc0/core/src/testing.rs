@@ -0,0 +1,64 @@
+//! Inputs for unit tests
+//!
+//! A record builder and a proptest strategy over histories that pass
+//! validation under the default economy: distinct in-bounds cells,
+//! unique nonces and block heights that never decrease in nonce order.
+
+use proptest::prelude::*;
+
+use crate::types::*;
+
+/// A surface record of `ore_type` at `(x, y)`, authorized at block
+/// `nonce` and mined at second `nonce`
+pub fn record(x: u8, y: u8, ore_type: u8, nonce: u64) -> MiningRecord {
+    MiningRecord {
+        grid_x: x,
+        grid_y: y,
+        ore_type,
+        is_rare: false,
+        random_output: [0; 32],
+        nonce,
+        block_height: nonce,
+        tx_hash: [0; 32],
+        timestamp: nonce,
+        grid_z: 0,
+        miner_index: 0,
+    }
+}
+
+/// A solo player's input over `records`, everything else defaulted
+pub fn input(records: Vec<MiningRecord>) -> LeaderboardInput {
+    LeaderboardInput {
+        player_address: [0x11; 20],
+        mining_history: records,
+        ..LeaderboardInput::default()
+    }
+}
+
+/// Up to 64 records on distinct cells with any ore type and rarity,
+/// nonces `0..n` in shuffled order
+pub fn valid_history() -> impl Strategy<Value = Vec<MiningRecord>> {
+    let cell = (0..GRID_SIZE, 0..GRID_SIZE);
+    let ore = (0..ORE_TYPES as u8, any::<bool>());
+    prop::collection::btree_map(cell, ore, 0..64)
+        .prop_map(|cells| {
+            cells
+                .into_iter()
+                .enumerate()
+                .map(|(nonce, ((x, y), (ore_type, is_rare)))| MiningRecord {
+                    is_rare,
+                    ..record(x, y, ore_type, nonce as u64)
+                })
+                .collect::<Vec<_>>()
+        })
+        .prop_shuffle()
+}
+
+/// `valid_history` under either scoring mode
+pub fn valid_input() -> impl Strategy<Value = LeaderboardInput> {
+    let scoring_mode = prop_oneof![Just(ScoringMode::PerRecord), Just(ScoringMode::InventoryBonus)];
+    (valid_history(), scoring_mode).prop_map(|(records, scoring_mode)| LeaderboardInput {
+        scoring_mode,
+        ..input(records)
+    })
+}
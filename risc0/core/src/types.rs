@@ -0,0 +1,510 @@
+//! Input and output types for the leaderboard guest
+
+use serde::{Deserialize, Serialize};
+
+/// Side length of the square mining grid
+pub const GRID_SIZE: u8 = 32;
+
+/// Number of ore types in the default economy (see `ORE_NAMES`)
+pub const ORE_TYPES: usize = 8;
+
+/// Most ore types an economy may define
+pub const MAX_ORE_TYPES: usize = 32;
+
+/// Most mining records one proof may cover; longer histories must be
+/// proven in shards
+pub const MAX_HISTORY_RECORDS: usize = 100_000;
+
+/// Most bytes of serialized input the guest reads, well inside the
+/// zkVM's guest memory
+pub const MAX_INPUT_BYTES: usize = 16 << 20;
+
+/// Version of the validation and scoring rules, committed in every full
+/// journal. Bump it with any change to what `gridzero-core` accepts or
+/// how it scores, a new input field or rejection included, so a guest
+/// image built from older rules is caught by the host instead of
+/// silently disagreeing with its native checks.
+pub const CORE_VERSION: u32 = 24;
+
+/// Score multiplier applied to rare finds
+pub const RARE_MULTIPLIER: u64 = 3;
+
+/// Per-ore-type values in canonical order: index `i` always refers to
+/// ore type `i` (see `ORE_NAMES`), with one entry per ore type of the
+/// economy (`Economy::ore_count`). Committed vectors are never sorted or
+/// compacted, so on-chain decoders can rely on positions.
+pub type OreVec<T> = Vec<T>;
+
+/// Names of the default economy's ore types, defining the canonical
+/// index order; an economy with more ores names the rest by index
+pub const ORE_NAMES: [&str; ORE_TYPES] = [
+    "Stone",
+    "Coal",
+    "Iron",
+    "Copper",
+    "Silver",
+    "Gold",
+    "Diamond",
+    "Mythril",
+];
+
+/// Score values per ore type of the default economy
+pub const BASE_SCORES: [u64; ORE_TYPES] = [
+    1,    // Stone
+    2,    // Coal
+    5,    // Iron
+    5,    // Copper
+    15,   // Silver
+    25,   // Gold
+    100,  // Diamond
+    500,  // Mythril
+];
+
+/// Scoring economy: every per-ore parameter of the score in one place,
+/// committed wholesale (values plus `economy_hash`) so a verifier can pin
+/// an approved economy by hash. Missing fields take the defaults below.
+///
+/// `base_scores` sets how many ore types there are (up to
+/// `MAX_ORE_TYPES`); every other per-ore vector, here and in the input,
+/// must have the same length.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct Economy {
+    pub base_scores: OreVec<u64>,        // Points per record of each ore
+    pub rare_multipliers: OreVec<u64>,   // Multiplier for rare records of each ore
+    pub rarity_cutoffs: Option<OreVec<u16>>, // Rare iff the VRF output's first two bytes (BE) are below this
+    pub score_caps: OreVec<u64>,         // Max points from each ore (u64::MAX for none)
+}
+
+impl Economy {
+    /// Number of ore types this economy scores
+    pub fn ore_count(&self) -> usize {
+        self.base_scores.len()
+    }
+}
+
+/// Display name of ore type `ore_type`
+pub fn ore_name(ore_type: usize) -> String {
+    match ORE_NAMES.get(ore_type) {
+        Some(name) => name.to_string(),
+        None => format!("Ore {}", ore_type),
+    }
+}
+
+impl Default for Economy {
+    /// The original economy: `BASE_SCORES`, a flat `RARE_MULTIPLIER`, no
+    /// rarity check against the VRF output and no caps
+    fn default() -> Self {
+        Economy {
+            base_scores: BASE_SCORES.to_vec(),
+            rare_multipliers: vec![RARE_MULTIPLIER; ORE_TYPES],
+            rarity_cutoffs: None,
+            score_caps: vec![u64::MAX; ORE_TYPES],
+        }
+    }
+}
+
+/// How the final score is derived from the history
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScoringMode {
+    /// Sum of each record's (rarity-adjusted) base score
+    #[default]
+    PerRecord,
+    /// Computed from the final `ore_inventory` alone, rewarding balanced
+    /// inventories (see `score::inventory_bonus_score`)
+    InventoryBonus,
+}
+
+/// How `tiebreak` is derived. In every mode a higher tiebreak ranks a
+/// player first among equal scores, so tied players sort by
+/// `(score, tiebreak)` descending.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TiebreakMode {
+    /// `unique_cells`: the wider explorer wins
+    #[default]
+    UniqueCells,
+    /// `u64::MAX` minus the nonce of the last in-window record (`u64::MAX`
+    /// with none): whoever finished mining earliest wins
+    EarliestLastNonce,
+}
+
+/// How a division feeding a committed value is rounded (see `fixed`)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Toward zero, as plain integer division
+    #[default]
+    Floor,
+    /// Up whenever there is a remainder
+    Ceil,
+    /// To the nearest integer, a remainder of exactly half up
+    Nearest,
+}
+
+/// A single mining result in the player's history
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MiningRecord {
+    pub grid_x: u8,
+    pub grid_y: u8,
+    pub ore_type: u8,
+    pub is_rare: bool,
+    pub random_output: [u8; 32],  // VRF output hash
+    pub nonce: u64,
+    #[serde(default)]
+    pub block_height: u64,        // Block of the authorizing chain event
+    #[serde(default)]
+    pub tx_hash: [u8; 32],        // Transaction of the authorizing chain event
+    #[serde(default)]
+    pub timestamp: u64,           // Unix time of the mine
+    #[serde(default)]
+    pub grid_z: u8,               // Layer of the cell (0 on flat maps)
+    #[serde(default)]
+    pub miner_index: u8,          // Party member who mined it (0 for a solo player)
+}
+
+/// Private input: full mining history
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LeaderboardInput {
+    pub player_address: [u8; 20],
+    pub mining_history: Vec<MiningRecord>,
+    #[serde(default)]
+    pub session_gap: u64,            // Max seconds between mines in one session
+    #[serde(default)]
+    pub hasher: u8,                  // Commitment hash (see hashing.rs)
+    #[serde(default)]
+    pub include_input_cid: bool,     // Commit input_cid (otherwise zero)
+    #[serde(default)]
+    pub scoring_mode: ScoringMode,
+    #[serde(default)]
+    pub prestige_thresholds: Option<OreVec<u64>>, // Count of each ore needed per prestige level
+    #[serde(default)]
+    pub shard: Option<NonceRange>,   // Prove only this slice of a larger history (see shard.rs)
+    #[serde(default)]
+    pub onboarding_count: u64,       // Records (in nonce order) that earn the onboarding bonus
+    #[serde(default)]
+    pub onboarding_bonus: u64,       // Bonus points per onboarding record
+    #[serde(default)]
+    pub grid_depth: u8,              // Layers on the map (0 and 1 both mean flat)
+    #[serde(default)]
+    pub depth_bonus_per_layer: u64,  // Extra points per layer below the surface
+    #[serde(default)]
+    pub expected_inventory_commitment: Option<[u8; 32]>, // Snapshot the inventories must match
+    #[serde(default)]
+    pub minimal_journal: bool,       // Commit a MinimalOutput instead of the full output
+    #[serde(default)]
+    pub now: u64,                    // Unix time the freshness check is relative to
+    #[serde(default)]
+    pub max_age: Option<u64>,        // Max seconds from the newest record to `now`
+    #[serde(default)]
+    pub economy: Economy,
+    #[serde(default)]
+    pub window_start: Option<u64>,   // Only score records timestamped at or after this
+    #[serde(default)]
+    pub window_end: Option<u64>,     // Only score records timestamped at or before this
+    #[serde(default)]
+    pub milestones: Option<OreVec<Vec<u64>>>, // Ascending count thresholds per ore type
+    #[serde(default)]
+    pub map_manifest: Option<MapManifest>, // Official map the records must agree with (see map.rs)
+    #[serde(default)]
+    pub map_layout: Vec<u8>,         // Ore type per linear cell index (ore_count or above: no ore), hashing to map_root
+    #[serde(default)]
+    pub allow_zero_address: bool,    // Accept the all-zero player address (normally a placeholder left in by mistake)
+    #[serde(default)]
+    pub checkpoint: Option<Checkpoint>, // Prior snapshot to commit the score earned since
+    #[serde(default)]
+    pub max_unique_cells: Option<u64>, // Tighter bound on cells mined than the grid or map allows
+    #[serde(default)]
+    pub client_checksum: Option<[u8; 32]>, // hashing::history_checksum the client expects of the records
+    #[serde(default)]
+    pub ordered_achievements: Vec<OrderedAchievement>, // Order-sensitive achievements to evaluate (see achievement.rs)
+    #[serde(default)]
+    pub tiebreak_mode: TiebreakMode,
+    #[serde(default)]
+    pub hide_inventory: bool,        // Commit a salted inventory commitment instead of the inventories
+    #[serde(default)]
+    pub inventory_salt: [u8; 32],    // Private salt for the hidden inventory commitment
+    #[serde(default)]
+    pub no_gaps: bool,               // Require the nonces to form one contiguous range
+    #[serde(default)]
+    pub region_rows: u8,             // Regions down and across the grid for region_scores (0: none)
+    #[serde(default)]
+    pub region_cols: u8,
+    #[serde(default)]
+    pub allow_remine: bool,          // A cell may be mined again once remine_cooldown has passed
+    #[serde(default)]
+    pub remine_cooldown: u64,        // Min seconds between two mines of one cell, in nonce order
+    #[serde(default)]
+    pub map_seed: Option<[u8; 32]>,  // Derive the map from this and beacon (see map.rs)
+    #[serde(default)]
+    pub beacon: [u8; 32],            // Public randomness for the round, mixed into a seeded map
+    #[serde(default)]
+    pub comeback_gap: u64,           // Seconds of inactivity after which the next mine is a comeback
+    #[serde(default)]
+    pub comeback_bonus: u64,         // Bonus points per comeback record (0 disables)
+    #[serde(default)]
+    pub boost_windows: Vec<BoostWindow>, // Limited-time score multipliers (see score.rs)
+    #[serde(default)]
+    pub tier_thresholds: Vec<u64>,   // Ascending scores at which each tier starts (see score.rs)
+    #[serde(default)]
+    pub hide_score: bool,            // Commit only the tier, with score and score_delta zeroed
+    #[serde(default)]
+    pub trust_input: bool,           // INSECURE: skip per-record validation (see score.rs)
+    #[serde(default)]
+    pub party_addresses: Vec<[u8; 20]>, // Members of a co-op party, indexed by miner_index (see score.rs)
+    #[serde(default)]
+    pub rounding: RoundingMode,      // Rounding of every division into a committed value
+}
+
+/// An official map version, signed by its map authority
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MapManifest {
+    pub map_id: u64,
+    pub map_root: [u8; 32],          // SHA-256 of the map layout
+    pub authority: [u8; 32],         // Ed25519 public key of the map authority
+    pub signature: Vec<u8>,          // 64-byte Ed25519 signature (see map::signed_message)
+}
+
+/// One step of an ordered achievement: mine `count` records of `ore_type`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrderedStep {
+    pub ore_type: u8,
+    pub count: u64,
+}
+
+/// A limited-time event: records of `ore_type` timestamped from `start`
+/// to `end` (inclusive) score `multiplier` times their points
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoostWindow {
+    pub ore_type: u8,
+    pub start: u64,
+    pub end: u64,
+    pub multiplier: u64,
+}
+
+/// Steps that must complete in nonce order (see achievement.rs)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OrderedAchievement {
+    pub steps: Vec<OrderedStep>,
+}
+
+/// A previously committed inventory, the baseline for `score_delta`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub total_mined: u64,            // In-window records (in nonce order) the snapshot covers
+    pub ore_inventory: OreVec<u64>,
+}
+
+/// The map a proof was checked against, as committed in the journal
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MapCommitment {
+    pub map_id: u64,
+    pub map_root: [u8; 32],
+    pub authority: [u8; 32],
+}
+
+/// `milestones_reached` entry for an ore with no milestone reached
+pub const NO_MILESTONE: u8 = u8::MAX;
+
+/// `version` of a full `LeaderboardOutput` journal. Bumped with every
+/// change to either journal's committed layout, so a version names
+/// exactly one; the host's layout snapshot test fails otherwise.
+pub const JOURNAL_VERSION: u32 = 10;
+
+/// Bit set in `version` when the journal is a `MinimalOutput`
+pub const MINIMAL_JOURNAL_FLAG: u32 = 0x8000_0000;
+
+/// Public output: verified score and stats
+///
+/// Per-ore vectors are `OreVec`s of `ore_count` entries in canonical
+/// ore-index order (Stone, Coal, Iron, Copper, Silver, Gold, Diamond,
+/// Mythril in the default economy).
+///
+/// Journals come in two layouts, told apart by their leading `version`
+/// word: this full `LeaderboardOutput` (`JOURNAL_VERSION`), or with
+/// `minimal_journal` set a `MinimalOutput` holding only `version`,
+/// `player_address`, `score` and `history_root` (`version` has
+/// `MINIMAL_JOURNAL_FLAG` set).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LeaderboardOutput {
+    pub version: u32,                // JOURNAL_VERSION
+    pub player_address: [u8; 20],
+    pub total_mined: u64,
+    pub score: u64,
+    pub ore_count: u8,               // Ore types in the economy, and entries per ore vector
+    pub ore_inventory: OreVec<u64>,  // Count per ore type
+    pub rare_inventory: OreVec<u64>, // Rare count per ore type
+    pub unique_cells: u64,           // Unique grid positions mined
+    pub provenance_root: [u8; 32],   // Merkle root of (block_height, tx_hash) in nonce order
+    pub history_root: [u8; 32],      // Merkle root of the full records in nonce order
+    pub longest_session_len: u64,    // Records in the longest mining session
+    pub longest_session_duration: u64, // Seconds from first to last mine of that session
+    pub hasher: u8,                  // Hash used for provenance_root
+    pub input_cid: [u8; 32],         // SHA-256 of the canonical input, if requested
+    pub scoring_mode: ScoringMode,   // How score was derived
+    pub prestige_level: u8,          // Highest prestige level satisfied (0 if none/unset)
+    pub onboarding_count: u64,       // Onboarding parameters the score was computed with
+    pub onboarding_bonus: u64,
+    pub grid_depth: u8,              // Map depth and depth bonus the score was computed with
+    pub depth_bonus_per_layer: u64,
+    pub inventory_commitment: [u8; 32], // SHA-256 of the inventories (see hashing.rs)
+    pub now: u64,                    // Freshness parameters the history was checked against
+    pub max_age: Option<u64>,
+    pub economy: Economy,            // Economy the score was computed with
+    pub economy_hash: [u8; 32],      // SHA-256 of the economy (see hashing.rs)
+    pub window_start: Option<u64>,   // Time window the counts and score cover
+    pub window_end: Option<u64>,
+    pub core_version: u32,           // CORE_VERSION of the rules that produced this
+    pub milestones_reached: OreVec<u8>, // Index of the highest milestone reached per ore, or NO_MILESTONE
+    pub map: Option<MapCommitment>,  // Signed map the records were checked against, if any
+    pub coverage_bps: u16,           // unique_cells over mineable cells, in basis points (rounded per rounding)
+    pub checkpoint: Option<Checkpoint>, // Snapshot score_delta is measured from, if any
+    pub score_delta: u64,            // score minus the score of the checkpoint's records (score if none)
+    pub client_checksum: Option<[u8; 32]>, // Client checksum the records were checked against, if any
+    pub ordered_achievements_met: Vec<bool>, // Per input ordered achievement, whether it was met
+    pub tiebreak_mode: TiebreakMode,
+    pub tiebreak: u64,               // Orders equal scores, higher first (see TiebreakMode)
+    pub inventory_hidden: bool,      // Inventories are zeroed and inventory_commitment is salted
+    pub no_gaps: bool,               // Nonces were checked to have no gaps
+    pub nonce_range: Option<NonceRange>, // With no_gaps, the first and last nonce (None if no records)
+    pub region_rows: u8,             // Region grid region_scores is laid out in
+    pub region_cols: u8,
+    pub region_scores: Vec<u64>,     // Uncapped record points per region, row-major from the top-left
+    pub allow_remine: bool,          // Re-mining rules the history was checked against
+    pub remine_cooldown: u64,
+    pub map_seed: Option<[u8; 32]>,  // Seeded map the records were checked against, if any
+    pub beacon: [u8; 32],            // Beacon the seeded map was derived with
+    pub comeback_gap: u64,           // Comeback parameters the score was computed with
+    pub comeback_bonus: u64,
+    pub comeback_bonus_total: u64,   // Comeback points included in score
+    pub lease_range: Option<NonceRange>, // Lowest to highest nonce proven (None if no records)
+    pub lease_token: [u8; 32],       // hashing::lease_token over lease_range (zero if none)
+    pub boosts_hash: [u8; 32],       // hashing::boosts_hash of the boost windows (zero if none)
+    pub tier: u8,                    // Tier thresholds the score reaches (0 below the lowest)
+    pub tier_thresholds: Vec<u64>,   // Thresholds the tier was computed against
+    pub score_hidden: bool,          // score and score_delta are zeroed; only the tier is revealed
+    pub validated: bool,             // Per-record checks ran (false with trust_input)
+    pub party_addresses: Vec<[u8; 20]>, // Party members, in miner_index order (empty if solo)
+    pub member_scores: Vec<u64>,     // Uncapped in-window record points per party member
+    pub member_cells: Vec<u64>,      // Distinct in-window cells per party member
+    pub rounding: RoundingMode,      // Rounding coverage_bps was computed with
+}
+
+/// Score-only public output, for on-chain ranking where the inventory
+/// arrays would only cost gas
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MinimalOutput {
+    pub version: u32,                // JOURNAL_VERSION | MINIMAL_JOURNAL_FLAG
+    pub player_address: [u8; 20],
+    pub score: u64,
+    pub history_root: [u8; 32],
+}
+
+/// A decoded journal in either layout
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum JournalOutput {
+    Full(Box<LeaderboardOutput>),
+    Minimal(MinimalOutput),
+}
+
+impl JournalOutput {
+    /// The journal the guest commits for `output` in the requested layout
+    pub fn new(output: LeaderboardOutput, minimal: bool) -> Self {
+        if minimal {
+            JournalOutput::Minimal(MinimalOutput {
+                version: JOURNAL_VERSION | MINIMAL_JOURNAL_FLAG,
+                player_address: output.player_address,
+                score: output.score,
+                history_root: output.history_root,
+            })
+        } else {
+            JournalOutput::Full(Box::new(output))
+        }
+    }
+
+    pub fn player_address(&self) -> [u8; 20] {
+        match self {
+            JournalOutput::Full(output) => output.player_address,
+            JournalOutput::Minimal(output) => output.player_address,
+        }
+    }
+
+    pub fn score(&self) -> u64 {
+        match self {
+            JournalOutput::Full(output) => output.score,
+            JournalOutput::Minimal(output) => output.score,
+        }
+    }
+}
+
+/// Inclusive range of nonces, e.g. covered by one shard
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonceRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Public output of a shard proof: the partial output over its records,
+/// plus what the aggregator needs to stitch shards back together
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ShardOutput {
+    pub output: LeaderboardOutput,
+    pub range: NonceRange,
+    pub cells: Vec<u64>,             // Bitset of mined cells, by linear cell index
+    pub newest_timestamp: Option<u64>, // For the aggregator's freshness check
+    pub first_block_height: Option<u64>, // Block heights at the shard's lowest and highest nonce,
+    pub last_block_height: Option<u64>,  // for the aggregator's monotonicity check
+    pub ore_points: OreVec<u64>,     // Uncapped record points per ore, for the aggregator's caps
+    pub mineable_cells: u64,         // Denominator of coverage_bps, for the aggregator
+    pub max_unique_cells: u64,       // Resolved bound on cells mined, for the aggregator
+}
+
+/// Private input to the aggregator guest
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AggregateInput {
+    pub shard_image_id: [u32; 8],    // Guest that proved every shard
+    pub player_address: [u8; 20],
+    pub shards: Vec<ShardOutput>,
+    #[serde(default)]
+    pub prestige_thresholds: Option<OreVec<u64>>,
+    #[serde(default)]
+    pub expected_inventory_commitment: Option<[u8; 32]>,
+    #[serde(default)]
+    pub now: u64,
+    #[serde(default)]
+    pub max_age: Option<u64>,
+    #[serde(default)]
+    pub milestones: Option<OreVec<Vec<u64>>>,
+    #[serde(default)]
+    pub hide_inventory: bool,
+    #[serde(default)]
+    pub inventory_salt: [u8; 32],
+    #[serde(default)]
+    pub tier_thresholds: Vec<u64>,
+    #[serde(default)]
+    pub hide_score: bool,
+}
+
+/// Public output of the aggregator guest
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AggregateOutput {
+    pub shard_image_id: [u32; 8],
+    pub shard_count: u64,
+    pub output: LeaderboardOutput,
+}
+
+/// Private input to the disjointness guest: two whole-history shard
+/// outputs, each backed by a receipt of `member_image_id`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DisjointInput {
+    pub member_image_id: [u32; 8],   // Guest that proved both members
+    pub members: [ShardOutput; 2],
+}
+
+/// Public output of the disjointness guest
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DisjointOutput {
+    pub member_image_id: [u32; 8],
+    pub player_addresses: [[u8; 20]; 2],
+    pub history_roots: [[u8; 32]; 2], // Ties the claim to the exact histories compared
+    pub disjoint: bool,              // No cell was mined by both players
+}
@@ -0,0 +1,42 @@
+//! Stamps the build provenance `--build-info` writes into meta.json
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("set by cargo");
+    let workspace = Path::new(&manifest_dir).join("..");
+
+    let git_commit = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(&workspace)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let build_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+
+    // The resolved version, from the lockfile's `[[package]]` entry
+    let lockfile = workspace.join("Cargo.lock");
+    let risc0_zkvm_version = std::fs::read_to_string(&lockfile)
+        .ok()
+        .and_then(|lock| {
+            let mut lines = lock.lines();
+            lines.find(|line| *line == "name = \"risc0-zkvm\"")?;
+            let version = lines.next()?.strip_prefix("version = \"")?;
+            Some(version.trim_end_matches('"').to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=GRIDZERO_GIT_COMMIT={}", git_commit);
+    println!("cargo:rustc-env=GRIDZERO_BUILD_TIMESTAMP={}", build_timestamp);
+    println!("cargo:rustc-env=GRIDZERO_RISC0_ZKVM_VERSION={}", risc0_zkvm_version);
+    println!("cargo:rerun-if-changed={}", lockfile.display());
+    println!("cargo:rerun-if-changed={}", workspace.join("../.git/HEAD").display());
+}
@@ -0,0 +1,194 @@
+//! Artifact files
+//!
+//! A single-input run writes `proof.bin`, `proof.hex`, `output.json` and
+//! `meta.json` into the output directory, as the original host did. In
+//! batch mode every file gets a `_0x<address>` suffix so players don't
+//! overwrite each other, e.g. `proof_0xab…cd.bin`.
+//!
+//! Writes go to a `.partial` sibling that is renamed into place once
+//! complete, so an interrupted run never leaves a truncated artifact.
+//!
+//! With `--receipt-format json` the receipt is written as serde JSON to
+//! `proof.json` instead of bincode in `proof.bin`, for risc0 tooling that
+//! expects it; `proof.hex` stays the bincode bytes zkVerify takes.
+//!
+//! JSON is pretty-printed unless the global `--compact` flag asked for
+//! minified output (see `set_compact_json`).
+
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::HostError;
+use crate::verify::decode_receipt;
+
+/// Whether JSON artifacts are written minified; set once from the CLI
+static COMPACT_JSON: AtomicBool = AtomicBool::new(false);
+
+/// Write every later JSON artifact (and JSON printed to stdout) minified
+/// rather than pretty-printed
+pub fn set_compact_json(compact: bool) {
+    COMPACT_JSON.store(compact, Ordering::Relaxed);
+}
+
+/// `value` as JSON in the selected style
+pub fn to_json<T: Serialize>(value: &T) -> String {
+    json_in_style(value, COMPACT_JSON.load(Ordering::Relaxed))
+}
+
+fn json_in_style<T: Serialize>(value: &T, compact: bool) -> String {
+    let json = if compact {
+        serde_json::to_string(value)
+    } else {
+        serde_json::to_string_pretty(value)
+    };
+    json.expect("artifact types always serialize")
+}
+
+/// Encoding of the written receipt
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReceiptFormat {
+    Bincode,
+    Json,
+}
+
+impl ReceiptFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReceiptFormat::Bincode => "bin",
+            ReceiptFormat::Json => "json",
+        }
+    }
+}
+
+/// Re-encode bincode receipt bytes in `format`
+pub fn encode_receipt(proof_bytes: &[u8], format: ReceiptFormat) -> Result<Vec<u8>, HostError> {
+    match format {
+        ReceiptFormat::Bincode => Ok(proof_bytes.to_vec()),
+        ReceiptFormat::Json => {
+            let receipt = decode_receipt(proof_bytes)?;
+            Ok(to_json(&receipt).into_bytes())
+        }
+    }
+}
+
+/// Where one proof's artifacts are written
+#[derive(Clone, Debug)]
+pub struct ArtifactPaths {
+    pub proof: PathBuf,
+    pub proof_hex: PathBuf,
+    pub output: PathBuf,
+    pub meta: PathBuf,
+}
+
+impl ArtifactPaths {
+    /// Unsuffixed names used when proving a single input
+    pub fn single(dir: &Path) -> Self {
+        ArtifactPaths {
+            proof: dir.join("proof.bin"),
+            proof_hex: dir.join("proof.hex"),
+            output: dir.join("output.json"),
+            meta: dir.join("meta.json"),
+        }
+    }
+
+    /// Per-player names used in batch mode
+    pub fn for_player(dir: &Path, player_address: &[u8; 20]) -> Self {
+        let suffix = format!("0x{}", hex::encode(player_address));
+        ArtifactPaths {
+            proof: dir.join(format!("proof_{}.bin", suffix)),
+            proof_hex: dir.join(format!("proof_{}.hex", suffix)),
+            output: dir.join(format!("output_{}.json", suffix)),
+            meta: dir.join(format!("meta_{}.json", suffix)),
+        }
+    }
+
+    /// Name the proof file for receipts written in `format`
+    pub fn with_receipt_format(mut self, format: ReceiptFormat) -> Self {
+        self.proof.set_extension(format.extension());
+        self
+    }
+}
+
+pub fn write_bytes(path: &Path, bytes: impl AsRef<[u8]>) -> Result<(), HostError> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+
+    if let Err(source) = fs::write(&partial, bytes) {
+        let _ = fs::remove_file(&partial);
+        return Err(HostError::Io {
+            path: partial,
+            source,
+        });
+    }
+    fs::rename(&partial, path).map_err(|source| {
+        let _ = fs::remove_file(&partial);
+        HostError::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    })
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), HostError> {
+    write_bytes(path, to_json(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::canonical_output;
+    use crate::testing::{fake_receipt, temp_dir};
+    use gridzero_core::{JournalOutput, LeaderboardOutput};
+    use gridzero_methods::GRIDZERO_GUEST_ID;
+    use risc0_zkvm::VerifierContext;
+
+    fn files(dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir).unwrap().map(|file| file.unwrap().path()).collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn writes_leave_no_partial_files() {
+        let dir = temp_dir("partial-writes");
+        let proof = dir.join("proof.bin");
+        write_bytes(&proof, b"first").unwrap();
+        write_bytes(&proof, b"second").unwrap();
+        assert_eq!(fs::read(&proof).unwrap(), b"second");
+
+        // A write that cannot be renamed into place leaves only what was there
+        let blocked = dir.join("output.json");
+        fs::create_dir(&blocked).unwrap();
+        assert!(write_bytes(&blocked, b"{}").is_err());
+        assert_eq!(files(&dir), vec![blocked, proof]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn receipts_round_trip_through_each_format() {
+        let proof_bytes = fake_receipt(GRIDZERO_GUEST_ID, &JournalOutput::Full(Box::new(canonical_output())));
+        let dev_mode = VerifierContext::default().with_dev_mode(true);
+        for format in [ReceiptFormat::Bincode, ReceiptFormat::Json] {
+            let encoded = encode_receipt(&proof_bytes, format).unwrap();
+            let receipt = decode_receipt(&encoded).unwrap();
+            assert_eq!(bincode::serialize(&receipt).unwrap(), proof_bytes, "{:?}", format);
+            receipt.verify_with_context(&dev_mode, GRIDZERO_GUEST_ID).unwrap();
+        }
+    }
+
+    #[test]
+    fn compact_and_pretty_json_hold_the_same_output() {
+        let output = canonical_output();
+        let compact = json_in_style(&output, true);
+        let pretty = json_in_style(&output, false);
+        assert!(!compact.contains('\n') && compact.len() < pretty.len());
+        let from_compact: LeaderboardOutput = serde_json::from_str(&compact).unwrap();
+        let from_pretty: LeaderboardOutput = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(from_compact, output);
+        assert_eq!(from_pretty, output);
+    }
+}
@@ -0,0 +1,508 @@
+//! Batch proving
+//!
+//! Given several input paths the host proves each player in turn, writes
+//! per-player artifacts, and finishes with `manifest.json`: an index of
+//! everything produced, so a submission script only has to iterate it.
+//! A player that fails is recorded in the manifest with the error's kind
+//! and message, and the batch moves on (`--continue`, the default); with
+//! `--fail-fast` the batch stops there instead and the manifest covers
+//! the players attempted so far.
+//!
+//! Ctrl-C is safe mid-batch: the player being proven is allowed to finish
+//! (artifact writes are atomic, see `artifacts`), no further players are
+//! started, and the manifest is written for everyone completed so far. A
+//! second Ctrl-C exits immediately.
+//!
+//! `--shard i/n` splits one batch across `n` processes. Each player
+//! belongs to exactly one shard, chosen by hashing its address, so `n`
+//! runs over the same inputs together prove every player once. Each run
+//! writes `manifest.<i>-of-<n>.json`, so they can share an output
+//! directory and their manifests can be merged later (see
+//! `merge_manifests`).
+//!
+//! A complete unsharded batch also writes a leaderboard root over every proven
+//! player's score, with per-player inclusion proofs (see `leaderboard`).
+//!
+//! `--ndjson` also prints one compact JSON line per proven player to
+//! stdout as soon as its proof is written: the address, the proof path
+//! and the committed output. Progress messages keep going to stdout too,
+//! but every NDJSON line, and no other line, starts with `{`.
+
+use risc0_zkvm::sha::Digest;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::artifacts::{self, ArtifactPaths};
+use crate::error::HostError;
+use crate::leaderboard;
+use gridzero_core::JournalOutput;
+use crate::prove::{self, ProveArgs, ProvenPlayer};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Shard `index` of `count` for `--shard i/n`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchShard {
+    pub index: u64,
+    pub count: u64,
+}
+
+impl BatchShard {
+    /// Whether `player_address` belongs to this shard: the first 8 bytes
+    /// of its SHA-256 (big-endian), modulo the shard count
+    pub fn contains(&self, player_address: &[u8; 20]) -> bool {
+        let digest = Sha256::digest(player_address);
+        let bucket = u64::from_be_bytes(digest[..8].try_into().expect("digest has 32 bytes"));
+        bucket % self.count == self.index
+    }
+
+    fn manifest_file(&self) -> String {
+        format!("manifest.{}-of-{}.json", self.index, self.count)
+    }
+}
+
+impl fmt::Display for BatchShard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// Parse `i/n` with `0 <= i < n`
+pub fn parse_batch_shard(arg: &str) -> Result<BatchShard, String> {
+    let (index, count) = arg
+        .split_once('/')
+        .ok_or_else(|| format!("expected i/n, got `{}`", arg))?;
+    let index: u64 = index.parse().map_err(|e| format!("shard index: {}", e))?;
+    let count: u64 = count.parse().map_err(|e| format!("shard count: {}", e))?;
+    if index >= count {
+        return Err(format!("shard index {} is not below the shard count {}", index, count));
+    }
+    Ok(BatchShard { index, count })
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Manifest {
+    pub summary: ManifestSummary,
+    pub players: Vec<ManifestEntry>,
+    pub failures: Vec<ManifestFailure>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ManifestSummary {
+    pub total_players: usize,
+    pub proven: usize,
+    pub failures: usize,
+    pub total_ms: u64,
+    /// The batch was stopped by Ctrl-C before every input was attempted
+    #[serde(default)]
+    pub interrupted: bool,
+    /// `--shard i/n` of this run, if any
+    #[serde(default)]
+    pub shard: Option<String>,
+    /// Inputs left to other shards
+    #[serde(default)]
+    pub skipped: usize,
+    /// `--fail-fast` stopped the batch at its first failure
+    #[serde(default)]
+    pub aborted: bool,
+}
+
+/// One proven player and where its artifacts live
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ManifestEntry {
+    pub player_address: String,
+    pub input: PathBuf,
+    pub proof: PathBuf,
+    pub proof_hex: Option<PathBuf>,
+    pub output: PathBuf,
+    pub meta: PathBuf,
+    pub proof_bytes: usize,
+    pub score: u64,
+    pub elapsed_ms: u64,
+}
+
+/// One `--ndjson` line, printed as a player's proof completes
+#[derive(Serialize)]
+struct NdjsonLine<'a> {
+    player_address: &'a str,
+    proof: &'a Path,
+    output: &'a JournalOutput,
+}
+
+/// An input that could not be proven
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ManifestFailure {
+    pub input: PathBuf,
+    /// `HostError::kind` of the failure, as in `--error-format json`
+    #[serde(default)]
+    pub kind: String,
+    pub error: String,
+}
+
+pub fn run(args: &ProveArgs, image_id: Digest) -> Result<(), HostError> {
+    let started = Instant::now();
+    let interrupted = install_interrupt_handler();
+    let mut stdout = std::io::stdout();
+    let ndjson = args.ndjson.then_some(&mut stdout as &mut dyn Write);
+    let attempts = attempt_all(args, &interrupted, ndjson, |input_path| {
+        prove_one(args, input_path, image_id)
+    });
+    prove::write_image_id(args, image_id)?;
+
+    let manifest = Manifest {
+        summary: ManifestSummary {
+            total_players: args.inputs.len(),
+            proven: attempts.players.len(),
+            failures: attempts.failures.len(),
+            total_ms: started.elapsed().as_millis() as u64,
+            interrupted: interrupted.load(Ordering::SeqCst),
+            shard: args.shard.map(|shard| shard.to_string()),
+            skipped: attempts.skipped,
+            aborted: attempts.aborted,
+        },
+        players: attempts.players,
+        failures: attempts.failures,
+    };
+    let manifest_file = match args.shard {
+        Some(shard) => shard.manifest_file(),
+        None => MANIFEST_FILE.to_string(),
+    };
+    let manifest_path = args.output_dir.join(manifest_file);
+    artifacts::write_json(&manifest_path, &manifest)?;
+
+    println!("\n📦 Batch complete");
+    println!(
+        "  Proven: {}/{} in {}ms",
+        manifest.summary.proven, manifest.summary.total_players, manifest.summary.total_ms
+    );
+    if let Some(shard) = args.shard {
+        println!("  Shard {}: {} inputs left to other shards", shard, manifest.summary.skipped);
+    }
+    println!("  Manifest: {}", manifest_path.display());
+
+    // A shard's or an interrupted or aborted batch's root would miss players
+    let complete = !manifest.summary.interrupted && !manifest.summary.aborted;
+    if args.shard.is_none() && complete && !manifest.players.is_empty() {
+        let results: Vec<([u8; 20], u64)> = manifest
+            .players
+            .iter()
+            .map(|entry| (parse_address(&entry.player_address), entry.score))
+            .collect();
+        let root = leaderboard::write(&args.output_dir, &results)?;
+        println!("  Leaderboard root: {}", hex::encode(root));
+    }
+
+    if manifest.summary.interrupted {
+        return Err(HostError::Interrupted {
+            completed: manifest.summary.proven,
+            total: manifest.summary.total_players,
+        });
+    }
+    if manifest.summary.failures > 0 {
+        return Err(HostError::BatchFailed {
+            failed: manifest.summary.failures,
+            total: manifest.summary.total_players,
+        });
+    }
+    Ok(())
+}
+
+/// What became of each input a batch attempted
+struct Attempts {
+    players: Vec<ManifestEntry>,
+    failures: Vec<ManifestFailure>,
+    skipped: usize,
+    aborted: bool,
+}
+
+/// Run `prove` over the batch's inputs in order until they run out, a
+/// Ctrl-C arrives or, with `--fail-fast`, one fails, printing each proven
+/// player's line to `ndjson` if given
+fn attempt_all(
+    args: &ProveArgs,
+    interrupted: &AtomicBool,
+    mut ndjson: Option<&mut dyn Write>,
+    mut prove: impl FnMut(&Path) -> Result<Option<(ManifestEntry, JournalOutput)>, HostError>,
+) -> Attempts {
+    let mut attempts = Attempts {
+        players: Vec::new(),
+        failures: Vec::new(),
+        skipped: 0,
+        aborted: false,
+    };
+    for (index, input_path) in args.inputs.iter().enumerate() {
+        if interrupted.load(Ordering::SeqCst) {
+            println!("\n⏹️  Interrupted; skipping {} remaining inputs", args.inputs.len() - index);
+            break;
+        }
+        println!("\n━━ [{}/{}] {}", index + 1, args.inputs.len(), input_path.display());
+        match prove(input_path) {
+            Ok(Some((entry, output))) => {
+                if let Some(out) = ndjson.as_deref_mut() {
+                    write_ndjson_line(out, &entry, &output);
+                }
+                attempts.players.push(entry);
+            }
+            Ok(None) => attempts.skipped += 1,
+            Err(err) => {
+                eprintln!("❌ {}: {}", input_path.display(), err);
+                attempts.failures.push(ManifestFailure {
+                    input: input_path.clone(),
+                    kind: err.kind().to_string(),
+                    error: err.to_string(),
+                });
+                if args.fail_fast {
+                    let remaining = args.inputs.len() - index - 1;
+                    println!("\n⏹️  --fail-fast: skipping {} remaining inputs", remaining);
+                    attempts.aborted = true;
+                    break;
+                }
+            }
+        }
+    }
+    attempts
+}
+
+/// Decode a manifest's `0x`-prefixed address
+pub(crate) fn parse_address(address: &str) -> [u8; 20] {
+    hex::decode(address.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .expect("manifest addresses are written from 20-byte arrays")
+}
+
+/// Flag set by the first Ctrl-C; the second one exits the process
+fn install_interrupt_handler() -> Arc<AtomicBool> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&interrupted);
+    let installed = ctrlc::set_handler(move || {
+        if flag.swap(true, Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+        eprintln!("\n⏹️  Ctrl-C: finishing the current player, then writing the manifest");
+    });
+    if let Err(err) = installed {
+        eprintln!("⚠️  Could not install Ctrl-C handler: {}", err);
+    }
+    interrupted
+}
+
+/// Prove one input, returning its manifest entry and committed output, or
+/// `None` if it belongs to another shard
+fn prove_one(
+    args: &ProveArgs,
+    input_path: &Path,
+    image_id: Digest,
+) -> Result<Option<(ManifestEntry, JournalOutput)>, HostError> {
+    let input = prove::load_input(args, input_path)?;
+    if let Some(shard) = args.shard {
+        if !shard.contains(&input.player_address) {
+            println!("  ⏭️  0x{} belongs to another shard", hex::encode(input.player_address));
+            return Ok(None);
+        }
+    }
+    let paths = ArtifactPaths::for_player(&args.output_dir, &input.player_address)
+        .with_receipt_format(args.receipt_format);
+    let proven = prove::prove_player(args, &input, &paths, image_id)?;
+    if !proven.proved {
+        // Nothing was written for the manifest to point at
+        return Err(HostError::ProverUnavailable {
+            message: "executed only under --allow-fallback; no proof written".to_string(),
+        });
+    }
+    let player_address = format!("0x{}", hex::encode(input.player_address));
+    let entry = manifest_entry(player_address, input_path, paths, &proven, !args.no_hex);
+    Ok(Some((entry, proven.output)))
+}
+
+/// Print `entry`'s `--ndjson` line to `out`
+fn write_ndjson_line(out: &mut dyn Write, entry: &ManifestEntry, output: &JournalOutput) {
+    let line = NdjsonLine {
+        player_address: &entry.player_address,
+        proof: &entry.proof,
+        output,
+    };
+    let line = serde_json::to_string(&line).expect("outputs always serialize");
+    // A consumer that went away should not fail the batch
+    let _ = writeln!(out, "{}", line).and_then(|()| out.flush());
+}
+
+/// The manifest's record of a player proven to `paths`
+fn manifest_entry(
+    player_address: String,
+    input_path: &Path,
+    paths: ArtifactPaths,
+    proven: &ProvenPlayer,
+    wrote_hex: bool,
+) -> ManifestEntry {
+    ManifestEntry {
+        player_address,
+        input: input_path.to_path_buf(),
+        proof: paths.proof,
+        proof_hex: wrote_hex.then_some(paths.proof_hex),
+        output: paths.output,
+        meta: paths.meta,
+        proof_bytes: proven.proof_bytes,
+        score: proven.output.score(),
+        elapsed_ms: proven.elapsed.as_millis() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifacts::ReceiptFormat;
+    use crate::layout::canonical_output;
+    use crate::meta::Meta;
+    use crate::testing::{prove_args, sample_input, temp_dir};
+    use gridzero_core::ValidationError;
+    use std::collections::BTreeSet;
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn manifest_entries_match_the_written_files() {
+        let dir = temp_dir("manifest-entries");
+        let input = sample_input();
+        let output = JournalOutput::Full(Box::new(canonical_output()));
+        let proof_bytes = vec![0x5a; 1234];
+        let meta = Meta {
+            image_id: "00".repeat(32),
+            proof_bytes: proof_bytes.len(),
+            cache_hit: false,
+            validation_report: crate::meta::validation_report(&input),
+            tags: Default::default(),
+            build: None,
+        };
+        let proven = ProvenPlayer {
+            output: output.clone(),
+            proof_bytes: proof_bytes.len(),
+            elapsed: Duration::from_millis(42),
+            proved: true,
+        };
+
+        for wrote_hex in [true, false] {
+            let paths = ArtifactPaths::for_player(&dir, &input.player_address);
+            prove::write_artifacts(&paths, &proof_bytes, ReceiptFormat::Bincode, wrote_hex, &output, &meta)
+                .unwrap();
+            let address = format!("0x{}", hex::encode(input.player_address));
+            let entry = manifest_entry(address, Path::new("input.json"), paths, &proven, wrote_hex);
+
+            assert_eq!(fs::read(&entry.proof).unwrap(), proof_bytes);
+            assert_eq!(entry.proof_bytes, fs::metadata(&entry.proof).unwrap().len() as usize);
+            let written: JournalOutput = serde_json::from_slice(&fs::read(&entry.output).unwrap()).unwrap();
+            assert_eq!(entry.score, written.score());
+            assert!(entry.meta.is_file());
+            if let Some(proof_hex) = &entry.proof_hex {
+                assert_eq!(fs::read_to_string(proof_hex).unwrap(), hex::encode(&proof_bytes));
+            }
+
+            // Every file in the directory is one the entry points at
+            let mut listed: BTreeSet<PathBuf> =
+                [&entry.proof, &entry.output, &entry.meta].into_iter().cloned().collect();
+            listed.extend(entry.proof_hex.clone());
+            let on_disk: BTreeSet<PathBuf> =
+                fs::read_dir(&dir).unwrap().map(|file| file.unwrap().path()).collect();
+            assert_eq!(on_disk, listed);
+            for path in on_disk {
+                fs::remove_file(path).unwrap();
+            }
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn shards_partition_the_players() {
+        for count in 1..=8 {
+            let shards: Vec<BatchShard> = (0..count)
+                .map(|index| parse_batch_shard(&format!("{}/{}", index, count)).unwrap())
+                .collect();
+            let mut sizes = vec![0; count as usize];
+            for player in 0..=255u8 {
+                let address = [player; 20];
+                let owners: Vec<usize> = (0..shards.len()).filter(|&i| shards[i].contains(&address)).collect();
+                assert_eq!(owners.len(), 1, "0x{} is in shards {:?} of {}", hex::encode(address), owners, count);
+                sizes[owners[0]] += 1;
+            }
+            // Addresses spread over every shard, not just one
+            assert!(sizes.iter().all(|&size| size > 0), "shard sizes {:?}", sizes);
+        }
+        assert!(parse_batch_shard("2/2").is_err());
+    }
+
+    /// What `prove_one` returns for a player proven from `input`, whose
+    /// file stem stands in for the address
+    fn proven(input: &Path) -> (ManifestEntry, JournalOutput) {
+        let entry = ManifestEntry {
+            player_address: input.file_stem().unwrap().to_string_lossy().into_owned(),
+            input: input.to_path_buf(),
+            proof: input.with_extension("bin"),
+            proof_hex: None,
+            output: PathBuf::from("output.json"),
+            meta: PathBuf::from("meta.json"),
+            proof_bytes: 0,
+            score: 0,
+            elapsed_ms: 0,
+        };
+        (entry, JournalOutput::Full(Box::new(canonical_output())))
+    }
+
+    #[test]
+    fn one_bad_input_under_each_policy() {
+        let inputs = ["good.json", "bad.json", "later.json"];
+        for fail_fast in [false, true] {
+            let mut flags = inputs.to_vec();
+            if fail_fast {
+                flags.push("--fail-fast");
+            }
+            let mut attempted = Vec::new();
+            let attempts = attempt_all(&prove_args(&flags), &AtomicBool::new(false), None, |input| {
+                attempted.push(input.to_path_buf());
+                if input == Path::new("bad.json") {
+                    Err(HostError::Validation(ValidationError::ZeroAddress))
+                } else {
+                    Ok(Some(proven(input)))
+                }
+            });
+
+            assert_eq!(attempts.aborted, fail_fast);
+            assert_eq!(attempts.failures.len(), 1);
+            assert_eq!(attempts.failures[0].input, Path::new("bad.json"));
+            assert_eq!(attempts.failures[0].kind, "ValidationError");
+            // Only --fail-fast leaves the input after the bad one unproven
+            let proven: Vec<&Path> = attempts.players.iter().map(|entry| entry.input.as_path()).collect();
+            let expected: &[&str] = if fail_fast { &["good.json"] } else { &["good.json", "later.json"] };
+            assert_eq!(proven, expected.iter().map(Path::new).collect::<Vec<_>>());
+            assert_eq!(attempted.len(), if fail_fast { 2 } else { 3 });
+        }
+    }
+
+    #[test]
+    fn each_proven_player_prints_one_ndjson_line() {
+        let args = prove_args(&["a.json", "bad.json", "elsewhere.json", "b.json", "--ndjson"]);
+        let mut out = Vec::new();
+        let attempts = attempt_all(&args, &AtomicBool::new(false), Some(&mut out), |input| {
+            match input.to_str().unwrap() {
+                "bad.json" => Err(HostError::Validation(ValidationError::ZeroAddress)),
+                "elsewhere.json" => Ok(None),
+                _ => Ok(Some(proven(input))),
+            }
+        });
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), attempts.players.len());
+        let players: Vec<&str> = lines.iter().map(|line| line["player_address"].as_str().unwrap()).collect();
+        assert_eq!(players, ["a", "b"]);
+        assert_eq!(lines[1]["proof"], "b.bin");
+    }
+}
@@ -0,0 +1,109 @@
+//! Proof cache
+//!
+//! Dev loops tend to re-prove identical inputs. With `--cache-dir` the
+//! host keys finished artifacts by SHA-256 over the canonical (bincode)
+//! encoding of the input plus the guest image ID, and reuses them on a
+//! hit instead of proving again. Rebuilding the guest changes the image
+//! ID and therefore every key, so stale entries are simply never hit.
+
+use risc0_zkvm::sha::Digest;
+use sha2::{Digest as _, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::HostError;
+use gridzero_core::{JournalOutput, LeaderboardInput};
+
+const PROOF_FILE: &str = "proof.bin";
+const OUTPUT_FILE: &str = "output.json";
+
+pub struct ProofCache {
+    dir: PathBuf,
+}
+
+impl ProofCache {
+    pub fn new(dir: &Path) -> Self {
+        ProofCache {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// Cache key for an input proved against a given guest image
+    pub fn key(input: &LeaderboardInput, image_id: &Digest) -> String {
+        let canonical = bincode::serialize(input).expect("input is always serializable");
+        let mut hasher = Sha256::new();
+        hasher.update(&canonical);
+        hasher.update(image_id.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    /// Cached proof bytes and output for `key`, if present
+    pub fn load(&self, key: &str) -> Result<Option<(Vec<u8>, JournalOutput)>, HostError> {
+        let entry = self.dir.join(key);
+        let proof_path = entry.join(PROOF_FILE);
+        let output_path = entry.join(OUTPUT_FILE);
+        if !proof_path.is_file() || !output_path.is_file() {
+            return Ok(None);
+        }
+
+        let proof_bytes = fs::read(&proof_path).map_err(HostError::io(&proof_path))?;
+        let output_json = fs::read(&output_path).map_err(HostError::io(&output_path))?;
+        match serde_json::from_slice(&output_json) {
+            Ok(output) => Ok(Some((proof_bytes, output))),
+            // A half-written or outdated entry is treated as a miss and
+            // overwritten by the next store.
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Store freshly generated artifacts under `key`
+    pub fn store(
+        &self,
+        key: &str,
+        proof_bytes: &[u8],
+        output: &JournalOutput,
+    ) -> Result<(), HostError> {
+        let entry = self.dir.join(key);
+        fs::create_dir_all(&entry).map_err(HostError::io(&entry))?;
+
+        let proof_path = entry.join(PROOF_FILE);
+        fs::write(&proof_path, proof_bytes).map_err(HostError::io(&proof_path))?;
+
+        let output_path = entry.join(OUTPUT_FILE);
+        let output_json = serde_json::to_string_pretty(output).unwrap();
+        fs::write(&output_path, output_json).map_err(HostError::io(&output_path))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{record, sample_input, temp_dir};
+    use gridzero_core::compute_leaderboard;
+
+    #[test]
+    fn identical_inputs_hit_and_a_changed_input_misses() {
+        let cache = ProofCache::new(&temp_dir("cache"));
+        let image_id = Digest::from([7u32; 8]);
+        let input = sample_input();
+        let output = JournalOutput::new(compute_leaderboard(&input).unwrap(), false);
+        cache.store(&ProofCache::key(&input, &image_id), b"proof", &output).unwrap();
+
+        let same = ProofCache::key(&sample_input(), &image_id);
+        assert_eq!(cache.load(&same).unwrap(), Some((b"proof".to_vec(), output)));
+
+        let mut changed = sample_input();
+        changed.mining_history.push(record(9, 9, 1, 4));
+        assert_eq!(cache.load(&ProofCache::key(&changed, &image_id)).unwrap(), None);
+    }
+
+    #[test]
+    fn a_rebuilt_guest_misses() {
+        let input = sample_input();
+        assert_ne!(
+            ProofCache::key(&input, &Digest::from([7u32; 8])),
+            ProofCache::key(&input, &Digest::from([8u32; 8]))
+        );
+    }
+}
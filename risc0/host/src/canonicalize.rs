@@ -0,0 +1,98 @@
+//! Input canonicalization
+//!
+//! `gridzero-host canonicalize <input.json>` rewrites an input in its
+//! canonical form: records sorted by nonce (the order `history_root` and
+//! `provenance_root` are built in), every defaulted field written out,
+//! and the whole encoded by serde as pretty JSON with a trailing
+//! newline. Proving the canonical file commits the `input_cid` printed
+//! on stderr, and two canonical files differ only where their inputs do.
+//! Canonicalizing a canonical file reproduces it byte for byte.
+
+use clap::Args;
+use gridzero_core::{hashing, LeaderboardInput};
+use std::path::PathBuf;
+
+use crate::artifacts;
+use crate::error::HostError;
+use crate::input::{self, InputFormat};
+
+#[derive(Args)]
+pub struct CanonicalizeArgs {
+    /// Input to canonicalize; `-` reads stdin
+    input: PathBuf,
+
+    /// Encoding of the input file
+    #[arg(long, value_enum, default_value_t = InputFormat::Json)]
+    input_format: InputFormat,
+
+    /// Write the canonical JSON here instead of to stdout
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+/// The canonical form of `input`: same content, records in nonce order
+pub fn canonical(mut input: LeaderboardInput) -> LeaderboardInput {
+    input.mining_history.sort_by_key(|record| record.nonce);
+    input
+}
+
+/// Records whose nonce is below the nonce of the record before them
+pub fn out_of_order_records(input: &LeaderboardInput) -> usize {
+    input
+        .mining_history
+        .windows(2)
+        .filter(|pair| pair[1].nonce < pair[0].nonce)
+        .count()
+}
+
+/// Canonical JSON encoding of an input
+pub fn to_canonical_json(input: &LeaderboardInput) -> String {
+    let mut json = serde_json::to_string_pretty(input).expect("inputs always serialize");
+    json.push('\n');
+    json
+}
+
+pub fn run(args: &CanonicalizeArgs) -> Result<(), HostError> {
+    let input = canonical(input::load_input(&args.input, args.input_format, false)?);
+    let json = to_canonical_json(&input);
+    match &args.output {
+        Some(path) => artifacts::write_bytes(path, &json)?,
+        None => print!("{}", json),
+    }
+    eprintln!("input_cid: {}", hex::encode(hashing::input_cid(&input)));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{sample_input, temp_dir};
+    use std::fs;
+
+    fn canonicalize(input: PathBuf, output: PathBuf) -> Vec<u8> {
+        let args = CanonicalizeArgs {
+            input,
+            input_format: InputFormat::Json,
+            output: Some(output.clone()),
+        };
+        run(&args).unwrap();
+        fs::read(output).unwrap()
+    }
+
+    #[test]
+    fn canonicalizing_is_idempotent() {
+        let dir = temp_dir("canonicalize");
+        let mut input = sample_input();
+        input.mining_history.reverse();
+        // Compact, out of order and with a defaulted field left out
+        let mut json = serde_json::to_value(&input).unwrap();
+        json.as_object_mut().unwrap().remove("session_gap");
+        fs::write(dir.join("input.json"), json.to_string()).unwrap();
+
+        let once = canonicalize(dir.join("input.json"), dir.join("once.json"));
+        let twice = canonicalize(dir.join("once.json"), dir.join("twice.json"));
+        assert_eq!(once, twice);
+        assert_eq!(String::from_utf8(once).unwrap(), to_canonical_json(&canonical(input)));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
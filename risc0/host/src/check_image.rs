@@ -0,0 +1,104 @@
+//! Image ID check
+//!
+//! `gridzero-host check-image <proof.bin>` verifies a receipt's integrity,
+//! reads the image ID out of its verified claim and compares it with the
+//! guests compiled into this host. A proof made by a forked or tampered
+//! guest verifies fine against its own image ID, so this is the check to
+//! run on prebuilt proofs from elsewhere; a mismatch exits with 4.
+//!
+//! The aggregator and disjointness guests verify inner receipts against
+//! an image ID the prover supplies and commit it, so their proofs also
+//! fail unless that inner image is the built leaderboard guest.
+
+use clap::Args;
+use risc0_zkvm::{sha::Digest, sha::Digestible, Journal, VerifierContext};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::HostError;
+use crate::verify::{check_aggregate, check_member_image, decode_receipt};
+use gridzero_core::{AggregateOutput, DisjointOutput};
+use gridzero_methods::{GRIDZERO_AGGREGATOR_ID, GRIDZERO_DISJOINT_ID, GRIDZERO_GUEST_ID};
+
+#[derive(Args)]
+pub struct CheckImageArgs {
+    /// Serialized receipt (proof.bin)
+    proof: PathBuf,
+}
+
+pub fn run(args: &CheckImageArgs) -> Result<(), HostError> {
+    let proof_bytes = fs::read(&args.proof).map_err(HostError::io(&args.proof))?;
+    let receipt = decode_receipt(&proof_bytes)?;
+    receipt
+        .verify_integrity_with_context(&VerifierContext::default())
+        .map_err(|e| HostError::InvalidProof {
+            message: e.to_string(),
+        })?;
+
+    let claim = receipt
+        .claim()
+        .map_err(|e| HostError::InvalidProof {
+            message: e.to_string(),
+        })?
+        .value()
+        .map_err(|e| HostError::InvalidProof {
+            message: format!("receipt claim is pruned: {}", e),
+        })?;
+    let claimed = claim.pre.digest();
+
+    let name = built_guest(claimed, &receipt.journal)?;
+
+    println!("✅ Image ID matches the built {}", name);
+    println!("  Image ID: {}", hex::encode(claimed.as_bytes()));
+    Ok(())
+}
+
+/// Name of the built guest `claimed` is the image ID of, after checking
+/// the inner image a composing guest committed to `journal`
+fn built_guest(claimed: Digest, journal: &Journal) -> Result<&'static str, HostError> {
+    let guest = Digest::from(GRIDZERO_GUEST_ID);
+    if claimed == guest {
+        Ok("leaderboard guest")
+    } else if claimed == Digest::from(GRIDZERO_AGGREGATOR_ID) {
+        let aggregate: AggregateOutput = journal.decode().map_err(|e| HostError::InvalidProof {
+            message: format!("journal does not decode as AggregateOutput: {}", e),
+        })?;
+        check_aggregate(&aggregate)?;
+        Ok("shard aggregator")
+    } else if claimed == Digest::from(GRIDZERO_DISJOINT_ID) {
+        let output: DisjointOutput = journal.decode().map_err(|e| HostError::InvalidProof {
+            message: format!("journal does not decode as DisjointOutput: {}", e),
+        })?;
+        check_member_image(output.member_image_id)?;
+        Ok("disjointness guest")
+    } else {
+        Err(HostError::ImageMismatch {
+            expected: hex::encode(guest.as_bytes()),
+            actual: hex::encode(claimed.as_bytes()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_built_guest_matches() {
+        let journal = Journal::new(Vec::new());
+        assert_eq!(built_guest(Digest::from(GRIDZERO_GUEST_ID), &journal).unwrap(), "leaderboard guest");
+    }
+
+    #[test]
+    fn a_wrong_image_id_is_a_mismatch() {
+        let mut forked = GRIDZERO_GUEST_ID;
+        forked[7] ^= 1;
+        let err = built_guest(Digest::from(forked), &Journal::new(Vec::new())).unwrap_err();
+        let HostError::ImageMismatch { expected, actual } = &err else {
+            panic!("expected an image mismatch, got {:?}", err);
+        };
+        assert_eq!(expected, &hex::encode(Digest::from(GRIDZERO_GUEST_ID).as_bytes()));
+        assert_eq!(actual, &hex::encode(Digest::from(forked).as_bytes()));
+        assert_eq!(err.exit_code(), crate::error::EXIT_IMAGE_MISMATCH);
+    }
+}
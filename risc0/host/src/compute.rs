@@ -0,0 +1,86 @@
+//! Native output preview
+//!
+//! `gridzero-host compute <input.json>` runs the core scoring rules
+//! natively, without the zkVM, and prints the output the guest would
+//! commit for that input: a `ShardOutput` when the input names a shard,
+//! otherwise the full or minimal journal. The guest runs the same
+//! `gridzero-core` code, so this doubles as a quick cross-check against a
+//! proof's `output.json`.
+
+use clap::Args;
+use gridzero_core::{compute_leaderboard, compute_shard, JournalOutput, LeaderboardInput};
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::artifacts;
+use crate::error::HostError;
+use crate::input::{self, InputFormat};
+
+#[derive(Args)]
+pub struct ComputeArgs {
+    /// Mining history to score; `-` reads stdin
+    input: PathBuf,
+
+    /// Encoding of the input file
+    #[arg(long, value_enum, default_value_t = InputFormat::Json)]
+    input_format: InputFormat,
+
+    /// Reject inputs that omit score-affecting fields
+    #[arg(long)]
+    strict: bool,
+
+    /// Write the output here instead of printing it
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: &ComputeArgs) -> Result<(), HostError> {
+    let input = input::load_input(&args.input, args.input_format, args.strict)?;
+    let output = compute(&input)?;
+
+    match &args.output {
+        Some(path) => artifacts::write_json(path, &output),
+        None => {
+            println!("{}", artifacts::to_json(&output));
+            Ok(())
+        }
+    }
+}
+
+/// What the guest would commit for `input`, as JSON
+fn compute(input: &LeaderboardInput) -> Result<Value, HostError> {
+    let output = match input.shard {
+        Some(_) => serde_json::to_value(compute_shard(input)?),
+        None => serde_json::to_value(JournalOutput::new(
+            compute_leaderboard(input)?,
+            input.minimal_journal,
+        )),
+    };
+    Ok(output.expect("output types always serialize"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal;
+    use crate::prove::execute;
+    use crate::testing::sample_input;
+    use gridzero_methods::GRIDZERO_GUEST_ELF;
+    use risc0_zkvm::ExecutorEnv;
+
+    /// Runs the built guest in the executor: `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn compute_matches_the_executed_journal() {
+        for minimal_journal in [false, true] {
+            let input = LeaderboardInput {
+                minimal_journal,
+                ..sample_input()
+            };
+            let env = ExecutorEnv::builder().write(&input).unwrap().build().unwrap();
+            let session = execute(env, GRIDZERO_GUEST_ELF).unwrap();
+            let executed = journal::decode(session.journal.as_ref().unwrap()).unwrap();
+            assert_eq!(compute(&input).unwrap(), serde_json::to_value(executed).unwrap());
+        }
+    }
+}
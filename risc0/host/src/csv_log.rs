@@ -0,0 +1,93 @@
+//! CSV proof log
+//!
+//! With `--csv <path>` every proof generated (or served from the cache)
+//! appends one row to a CSV file, for ops teams that track proofs in
+//! spreadsheets. A new file starts with a header row; an existing one is
+//! appended to, so one file can accumulate many runs and batches.
+//!
+//! Minimal journals do not commit `total_mined` or `unique_cells`, so
+//! those columns are left empty for them.
+
+use gridzero_core::JournalOutput;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::HostError;
+
+pub const HEADER: &str = "timestamp,player_address,score,total_mined,unique_cells,proof_bytes,proving_ms";
+
+/// Append one row for a proof, writing the header first if the file is new
+pub fn append_row(
+    path: &Path,
+    output: &JournalOutput,
+    proof_bytes: usize,
+    elapsed: Duration,
+) -> Result<(), HostError> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(HostError::io(path))?;
+    let is_new = file.metadata().map_err(HostError::io(path))?.len() == 0;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (total_mined, unique_cells) = match output {
+        JournalOutput::Full(output) => (output.total_mined.to_string(), output.unique_cells.to_string()),
+        JournalOutput::Minimal(_) => (String::new(), String::new()),
+    };
+    let mut rows = String::new();
+    if is_new {
+        rows.push_str(HEADER);
+        rows.push('\n');
+    }
+    rows.push_str(&format!(
+        "{},0x{},{},{},{},{},{}\n",
+        timestamp,
+        hex::encode(output.player_address()),
+        output.score(),
+        total_mined,
+        unique_cells,
+        proof_bytes,
+        elapsed.as_millis()
+    ));
+    file.write_all(rows.as_bytes()).map_err(HostError::io(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::canonical_output;
+    use crate::testing::temp_dir;
+    use std::fs;
+
+    #[test]
+    fn a_batch_appends_one_row_per_proof() {
+        let dir = temp_dir("csv-log");
+        let path = dir.join("proofs.csv");
+        let full = JournalOutput::Full(Box::new(canonical_output()));
+        let minimal = JournalOutput::new(canonical_output(), true);
+
+        // Two batches of three players each share one header
+        for _ in 0..2 {
+            for output in [&full, &minimal, &full] {
+                append_row(&path, output, 1234, Duration::from_millis(56)).unwrap();
+            }
+        }
+        let csv = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 1 + 6);
+        assert_eq!(lines[0], HEADER);
+        let address = format!("0x{}", hex::encode(full.player_address()));
+        let full_row = format!("{},1,1,1,1234,56", address);
+        let minimal_row = format!("{},1,,,1234,56", address);
+        for (line, expected) in lines[1..].iter().zip([&full_row, &minimal_row, &full_row].repeat(2)) {
+            let (_, row) = line.split_once(',').unwrap();
+            assert_eq!(row, expected.as_str());
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,103 @@
+//! `--dump-cycles-by-segment`: where a proof's cycles go, without proving
+//!
+//! Executes the guest once with cycle markers on (see
+//! `gridzero_core::markers`) and prints each segment's size and cycles,
+//! then the cycles spent between consecutive markers: reading the input,
+//! verifying the map, scoring records, ordering by nonce, hashing the
+//! roots, building the output and committing it. Cycles the markers do
+//! not cover (paging, padding to the segment size) are shown as `other`.
+
+use gridzero_core::markers::{parse_marker, CYCLE_MARKERS_ENV};
+use gridzero_core::{compute_leaderboard, LeaderboardInput};
+use risc0_zkvm::{default_executor, ExecutorEnv};
+
+use crate::error::HostError;
+use gridzero_methods::GRIDZERO_GUEST_ELF;
+
+pub fn print(input: &LeaderboardInput) -> Result<(), HostError> {
+    // A rejected history would only surface as a guest panic
+    compute_leaderboard(input)?;
+
+    let mut stderr = Vec::new();
+    let env = ExecutorEnv::builder()
+        .write(input)
+        .unwrap()
+        .env_var(CYCLE_MARKERS_ENV, "1")
+        .stderr(&mut stderr)
+        .build()
+        .unwrap();
+    let session = default_executor()
+        .execute(env, GRIDZERO_GUEST_ELF)
+        .expect("Guest execution failed");
+    let total = session.cycles();
+
+    println!("\n⏱️  Cycles for 0x{}", hex::encode(input.player_address));
+    println!("  {:>7}  {:>3}  {:>12}", "segment", "po2", "cycles");
+    for (index, segment) in session.segments.iter().enumerate() {
+        println!("  {:>7}  {:>3}  {:>12}", index, segment.po2, segment.cycles);
+    }
+    println!("  {:>7}  {:>3}  {:>12}", "total", "", total);
+
+    let stderr = String::from_utf8_lossy(&stderr);
+    let Some(phases) = phases(&stderr, total) else {
+        println!("\n  ⚠️  guest wrote no cycle markers; rebuild it to get a phase breakdown");
+        return Ok(());
+    };
+
+    println!("\n  {:<12}  {:>12}  {:>6}", "phase", "cycles", "share");
+    for (phase, cycles) in phases {
+        println!("  {:<12}  {:>12}  {:>5.1}%", phase, cycles, percent(cycles, total));
+    }
+    Ok(())
+}
+
+/// Cycles spent in each phase the guest's markers in `stderr` close,
+/// then the rest of `total` as `other`; `None` without any markers
+fn phases(stderr: &str, total: u64) -> Option<Vec<(&str, u64)>> {
+    let mut previous = 0;
+    let mut phases = Vec::new();
+    for (phase, cycles) in stderr.lines().filter_map(parse_marker) {
+        phases.push((phase, cycles.saturating_sub(previous)));
+        previous = cycles;
+    }
+    if phases.is_empty() {
+        return None;
+    }
+    phases.push(("other", total.saturating_sub(previous)));
+    Some(phases)
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    part as f64 * 100.0 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gridzero_core::markers::format_marker;
+
+    #[test]
+    fn phases_are_the_cycles_between_markers() {
+        let stderr = [
+            format_marker("read", 100),
+            "some guest log line".to_string(),
+            format_marker("records", 350),
+            format_marker("commit", 400),
+        ]
+        .join("\n");
+        assert_eq!(
+            phases(&stderr, 1000),
+            Some(vec![("read", 100), ("records", 250), ("commit", 50), ("other", 600)])
+        );
+        assert_eq!(phases("no markers here", 1000), None);
+    }
+
+    #[test]
+    fn shares_of_zero_cycles_are_zero() {
+        assert_eq!(percent(25, 200), 12.5);
+        assert_eq!(percent(0, 0), 0.0);
+    }
+}
@@ -0,0 +1,88 @@
+//! Disjointness proofs
+//!
+//! `gridzero-host prove-disjoint <a.json> <b.json>` proves whether two
+//! players mined disjoint cell sets. Each history is first proven by the
+//! main guest as one shard spanning every nonce, which commits its cell
+//! bitset; the disjointness guest then verifies both receipts as
+//! assumptions and commits a `DisjointOutput` (see
+//! `gridzero_core::disjoint` for how the bitsets are compared).
+//!
+//! Writes `disjoint_proof.bin` and `disjoint_output.json`, verified
+//! against the disjointness guest's image ID.
+
+use clap::Args;
+use gridzero_core::disjoint::FULL_HISTORY;
+use gridzero_core::{
+    check_disjoint, compute_shard, DisjointInput, DisjointOutput, LeaderboardInput, ShardOutput,
+};
+use risc0_zkvm::{sha::Digest, ExecutorEnv};
+use std::path::PathBuf;
+
+use crate::artifacts;
+use crate::error::HostError;
+use crate::input::{self, InputFormat};
+use crate::prove::prove_elf;
+use gridzero_methods::{
+    GRIDZERO_DISJOINT_ELF, GRIDZERO_DISJOINT_ID, GRIDZERO_GUEST_ELF, GRIDZERO_GUEST_ID,
+};
+
+#[derive(Args)]
+pub struct ProveDisjointArgs {
+    /// The two players' mining histories
+    #[arg(num_args = 2, required = true)]
+    inputs: Vec<PathBuf>,
+
+    /// Encoding of the input files
+    #[arg(long, value_enum, default_value_t = InputFormat::Json)]
+    input_format: InputFormat,
+
+    /// Directory artifacts are written to
+    #[arg(long, default_value = ".")]
+    output_dir: PathBuf,
+}
+
+pub fn run(args: &ProveDisjointArgs) -> Result<(), HostError> {
+    let mut members = Vec::with_capacity(2);
+    for path in &args.inputs {
+        let input = input::load_input(path, args.input_format, false)?;
+        members.push(LeaderboardInput {
+            shard: Some(FULL_HISTORY),
+            ..input
+        });
+    }
+
+    // Check natively first so a bad history fails before any proving
+    let outputs: Vec<ShardOutput> = members.iter().map(compute_shard).collect::<Result<_, _>>()?;
+    let input = DisjointInput {
+        member_image_id: GRIDZERO_GUEST_ID,
+        members: outputs.try_into().expect("clap requires exactly two inputs"),
+    };
+    check_disjoint(&input)?;
+
+    println!("🎮 GridZero Disjointness Proof Generator");
+    let mut env = ExecutorEnv::builder();
+    for member in &members {
+        println!("\n⚙️  Proving 0x{}...", hex::encode(member.player_address));
+        let member_env = ExecutorEnv::builder().write(member).unwrap().build().unwrap();
+        env.add_assumption(prove_elf(member_env, GRIDZERO_GUEST_ELF, None)?);
+    }
+    let env = env.write(&input).unwrap().build().unwrap();
+    println!("\n⚙️  Proving disjointness...");
+    let receipt = prove_elf(env, GRIDZERO_DISJOINT_ELF, None)?;
+    let output: DisjointOutput = receipt.journal.decode().unwrap();
+
+    std::fs::create_dir_all(&args.output_dir).map_err(HostError::io(&args.output_dir))?;
+    let proof_bytes = bincode::serialize(&receipt).unwrap();
+    artifacts::write_bytes(&args.output_dir.join("disjoint_proof.bin"), &proof_bytes)?;
+    artifacts::write_json(&args.output_dir.join("disjoint_output.json"), &output)?;
+
+    println!("\n✅ Proof generated!");
+    println!(
+        "  0x{} and 0x{}: {}",
+        hex::encode(output.player_addresses[0]),
+        hex::encode(output.player_addresses[1]),
+        if output.disjoint { "disjoint" } else { "overlapping" }
+    );
+    println!("  Image ID: {}", hex::encode(Digest::from(GRIDZERO_DISJOINT_ID).as_bytes()));
+    Ok(())
+}
@@ -0,0 +1,135 @@
+//! Economy presets and hashes
+//!
+//! `--economy <preset>` on the prove command starts every input from a
+//! built-in economy; fields an input's `economy` sets itself still win.
+//! The presets, per ore in canonical order (Stone … Mythril):
+//!
+//! | preset     | base_scores                       | rare_multipliers | score_caps                                          |
+//! |------------|-----------------------------------|------------------|-----------------------------------------------------|
+//! | `classic`  | 1, 2, 5, 5, 15, 25, 100, 500      | 3 each           | none                                                |
+//! | `hardcore` | 1, 1, 3, 3, 10, 20, 80, 400       | 5 each           | 500, 1000, 2000, 2000, 5000, 10000, 20000, 50000    |
+//! | `casual`   | 2, 3, 6, 6, 15, 25, 100, 500      | 2 each           | none                                                |
+//!
+//! No preset sets `rarity_cutoffs`. `classic` is the default economy.
+//!
+//! `gridzero-host economy-hash [economy.json]` prints the `economy_hash`
+//! a proof made under that economy would commit, so the expected value
+//! can be published before any proofs are made. Without a file it hashes
+//! `--preset`, or the default economy.
+
+use clap::{Args, ValueEnum};
+use gridzero_core::{hashing, Economy, ORE_TYPES};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::HostError;
+use crate::input::InputFormat;
+
+/// Built-in economies for common game modes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum EconomyPreset {
+    Classic,
+    Hardcore,
+    Casual,
+}
+
+impl EconomyPreset {
+    /// The preset's full parameter set (see the module docs)
+    pub fn economy(self) -> Economy {
+        match self {
+            EconomyPreset::Classic => Economy::default(),
+            EconomyPreset::Hardcore => Economy {
+                base_scores: vec![1, 1, 3, 3, 10, 20, 80, 400],
+                rare_multipliers: vec![5; ORE_TYPES],
+                rarity_cutoffs: None,
+                score_caps: vec![500, 1_000, 2_000, 2_000, 5_000, 10_000, 20_000, 50_000],
+            },
+            EconomyPreset::Casual => Economy {
+                base_scores: vec![2, 3, 6, 6, 15, 25, 100, 500],
+                rare_multipliers: vec![2; ORE_TYPES],
+                rarity_cutoffs: None,
+                score_caps: vec![u64::MAX; ORE_TYPES],
+            },
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct EconomyHashArgs {
+    /// Economy as JSON, in the shape of `LeaderboardInput::economy`
+    #[arg(conflicts_with = "preset")]
+    economy: Option<PathBuf>,
+
+    /// Hash a built-in preset instead of a file
+    #[arg(long, value_enum)]
+    preset: Option<EconomyPreset>,
+}
+
+/// Read an economy from a JSON file
+pub fn load_economy(path: &PathBuf) -> Result<Economy, HostError> {
+    let bytes = fs::read(path).map_err(HostError::io(path))?;
+    serde_json::from_slice(&bytes).map_err(|e| HostError::Parse {
+        format: InputFormat::Json,
+        message: e.to_string(),
+    })
+}
+
+/// The committed hash of `economy`, hex-encoded
+pub fn economy_hash_hex(economy: &Economy) -> String {
+    hex::encode(hashing::economy_hash(economy))
+}
+
+pub fn run(args: &EconomyHashArgs) -> Result<(), HostError> {
+    let economy = match (&args.economy, args.preset) {
+        (Some(path), _) => load_economy(path)?,
+        (None, Some(preset)) => preset.economy(),
+        (None, None) => Economy::default(),
+    };
+    println!("{}", economy_hash_hex(&economy));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::load_input_with_preset;
+    use crate::testing::{sample_input, temp_dir};
+
+    #[test]
+    fn presets_resolve_to_their_documented_parameters() {
+        // The module docs' table, row by row
+        let documented = [
+            (EconomyPreset::Classic, [1, 2, 5, 5, 15, 25, 100, 500], 3, [u64::MAX; ORE_TYPES]),
+            (
+                EconomyPreset::Hardcore,
+                [1, 1, 3, 3, 10, 20, 80, 400],
+                5,
+                [500, 1000, 2000, 2000, 5000, 10000, 20000, 50000],
+            ),
+            (EconomyPreset::Casual, [2, 3, 6, 6, 15, 25, 100, 500], 2, [u64::MAX; ORE_TYPES]),
+        ];
+        for (preset, base_scores, rare_multiplier, score_caps) in documented {
+            let economy = preset.economy();
+            assert_eq!(economy.base_scores, base_scores, "{:?}", preset);
+            assert_eq!(economy.rare_multipliers, vec![rare_multiplier; ORE_TYPES], "{:?}", preset);
+            assert_eq!(economy.score_caps, score_caps, "{:?}", preset);
+            assert_eq!(economy.rarity_cutoffs, None, "{:?}", preset);
+        }
+    }
+
+    #[test]
+    fn input_economy_fields_override_the_preset() {
+        let dir = temp_dir("economy-preset");
+        let path = dir.join("input.json");
+        let mut json = serde_json::to_value(sample_input()).unwrap();
+        json["economy"] = serde_json::json!({ "base_scores": [9, 9, 9, 9, 9, 9, 9, 9] });
+        fs::write(&path, json.to_string()).unwrap();
+
+        let hardcore = EconomyPreset::Hardcore.economy();
+        let input = load_input_with_preset(&path, InputFormat::Json, false, &hardcore).unwrap();
+        assert_eq!(input.economy.base_scores, vec![9; ORE_TYPES]);
+        assert_eq!(input.economy.rare_multipliers, hardcore.rare_multipliers);
+        assert_eq!(input.economy.score_caps, hardcore.score_caps);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,373 @@
+//! Host error type
+//!
+//! Everything that can go wrong before or after proving is surfaced
+//! through `HostError` so `main` can print one clear message and exit.
+//! With `--error-format json` that message is a JSON object instead, so
+//! scripts can branch on `kind` rather than parse prose.
+
+use clap::ValueEnum;
+use gridzero_core::ValidationError;
+use serde_json::{json, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::image_id::ImageIdError;
+use crate::input::InputFormat;
+
+#[derive(Debug)]
+pub enum HostError {
+    /// Reading or writing a file failed
+    Io { path: PathBuf, source: std::io::Error },
+    /// Input bytes did not decode in the declared format
+    Parse { format: InputFormat, message: String },
+    /// `--strict` and the input omits fields that would change the score
+    MissingFields { fields: Vec<String> },
+    /// An image ID argument was malformed
+    ImageId(ImageIdError),
+    /// A receipt failed to decode or verify
+    InvalidProof { message: String },
+    /// The mining history breaks a rule the guest enforces
+    Validation(ValidationError),
+    /// Some proofs in a `verify --batch` run did not verify
+    VerifyFailed { failed: usize, total: usize },
+    /// A proof verified but its journal differs from the expected output
+    OutputMismatch { fields: Vec<String> },
+    /// A receipt was made by a guest other than the one built into this host
+    ImageMismatch { expected: String, actual: String },
+    /// `tamper-test` found a mutated proof that still verifies
+    TamperUndetected { mutation: &'static str },
+    /// Some players in a batch could not be proven
+    BatchFailed { failed: usize, total: usize },
+    /// `merge-manifests` found no shard manifests in a directory
+    NoManifests { dir: PathBuf },
+    /// `merge-manifests` found a player proven by two shard manifests
+    DuplicatePlayer { address: String, first: PathBuf, second: PathBuf },
+    /// A batch was stopped by Ctrl-C
+    Interrupted { completed: usize, total: usize },
+    /// A proof or journal is larger than its `--max-*-bytes` budget
+    OverBudget { what: &'static str, actual: usize, budget: usize },
+    /// A journal's version is outside the range this host can decode
+    UnsupportedJournalVersion { version: u32, min: u32, max: u32 },
+    /// Executing the guest took more cycles than `--max-cycles`
+    OverCycleBudget { cycles: u64, budget: u64 },
+    /// The succinct prover could not be set up on this machine
+    ProverUnavailable { message: String },
+    /// The guest failed to load or panicked while executing
+    GuestFailed { message: String },
+    /// The prover was set up but failed to prove an executed session
+    ProvingFailed { message: String },
+    /// `serve` could not listen on its address
+    Listen { addr: String, message: String },
+}
+
+/// Process exit codes for failures scripts need to tell apart
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_INVALID_PROOF: i32 = 2;
+pub const EXIT_OUTPUT_MISMATCH: i32 = 3;
+pub const EXIT_IMAGE_MISMATCH: i32 = 4;
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// How `main` reports a failure on stderr
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    Text,
+    Json,
+}
+
+impl HostError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            HostError::InvalidProof { .. } | HostError::VerifyFailed { .. } => EXIT_INVALID_PROOF,
+            HostError::OutputMismatch { .. } => EXIT_OUTPUT_MISMATCH,
+            HostError::ImageMismatch { .. } => EXIT_IMAGE_MISMATCH,
+            HostError::Interrupted { .. } => EXIT_INTERRUPTED,
+            _ => EXIT_FAILURE,
+        }
+    }
+
+    /// Stable name of the error variant, for machine-readable output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            HostError::Io { .. } => "IoError",
+            HostError::Parse { .. } => "ParseError",
+            HostError::MissingFields { .. } => "MissingFields",
+            HostError::ImageId(_) => "ImageIdError",
+            HostError::InvalidProof { .. } => "InvalidProof",
+            HostError::VerifyFailed { .. } => "VerifyFailed",
+            HostError::Validation(_) => "ValidationError",
+            HostError::OutputMismatch { .. } => "OutputMismatch",
+            HostError::ImageMismatch { .. } => "ImageMismatch",
+            HostError::TamperUndetected { .. } => "TamperUndetected",
+            HostError::BatchFailed { .. } => "BatchFailed",
+            HostError::NoManifests { .. } => "NoManifests",
+            HostError::DuplicatePlayer { .. } => "DuplicatePlayer",
+            HostError::Interrupted { .. } => "Interrupted",
+            HostError::OverBudget { .. } => "OverBudget",
+            HostError::OverCycleBudget { .. } => "OverCycleBudget",
+            HostError::UnsupportedJournalVersion { .. } => "UnsupportedJournalVersion",
+            HostError::ProverUnavailable { .. } => "ProverUnavailable",
+            HostError::GuestFailed { .. } => "GuestFailed",
+            HostError::ProvingFailed { .. } => "ProvingFailed",
+            HostError::Listen { .. } => "ListenError",
+        }
+    }
+
+    /// Variant-specific fields for machine-readable output
+    pub fn detail(&self) -> Value {
+        match self {
+            HostError::Io { path, source } => json!({
+                "path": path.display().to_string(),
+                "io_error": source.kind().to_string(),
+            }),
+            HostError::Parse { format, message } => json!({
+                "format": format.name(),
+                "message": message,
+            }),
+            HostError::MissingFields { fields } => json!({ "fields": fields }),
+            HostError::ImageId(err) => json!({ "message": err.to_string() }),
+            HostError::InvalidProof { message } => json!({ "message": message }),
+            HostError::VerifyFailed { failed, total } => json!({
+                "failed": failed,
+                "total": total,
+            }),
+            HostError::Validation(err) => serde_json::to_value(err).unwrap_or(Value::Null),
+            HostError::OutputMismatch { fields } => json!({ "fields": fields }),
+            HostError::ImageMismatch { expected, actual } => json!({
+                "expected": expected,
+                "actual": actual,
+            }),
+            HostError::TamperUndetected { mutation } => json!({ "mutation": mutation }),
+            HostError::BatchFailed { failed, total } => json!({
+                "failed": failed,
+                "total": total,
+            }),
+            HostError::NoManifests { dir } => json!({ "dir": dir.display().to_string() }),
+            HostError::DuplicatePlayer { address, first, second } => json!({
+                "address": address,
+                "first": first.display().to_string(),
+                "second": second.display().to_string(),
+            }),
+            HostError::Interrupted { completed, total } => json!({
+                "completed": completed,
+                "total": total,
+            }),
+            HostError::OverBudget { what, actual, budget } => json!({
+                "what": what,
+                "actual": actual,
+                "budget": budget,
+            }),
+            HostError::UnsupportedJournalVersion { version, min, max } => json!({
+                "version": version,
+                "min": min,
+                "max": max,
+            }),
+            HostError::OverCycleBudget { cycles, budget } => json!({
+                "cycles": cycles,
+                "budget": budget,
+            }),
+            HostError::ProverUnavailable { message }
+            | HostError::GuestFailed { message }
+            | HostError::ProvingFailed { message } => json!({ "message": message }),
+            HostError::Listen { addr, message } => json!({
+                "addr": addr,
+                "message": message,
+            }),
+        }
+    }
+
+    /// The error as a JSON object: message, `kind()` and `detail()`
+    pub fn to_json(&self) -> Value {
+        json!({
+            "error": self.to_string(),
+            "kind": self.kind(),
+            "detail": self.detail(),
+        })
+    }
+
+    /// Print this error to stderr in `format`
+    pub fn report(&self, format: ErrorFormat) {
+        match format {
+            ErrorFormat::Text => eprintln!("❌ {}", self),
+            ErrorFormat::Json => eprintln!("{}", self.to_json()),
+        }
+    }
+
+    /// Adapter for `map_err` on filesystem calls
+    pub fn io(path: &Path) -> impl FnOnce(std::io::Error) -> HostError + '_ {
+        move |source| HostError::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostError::Io { path, source } => {
+                write!(f, "{}: {}", path.display(), source)
+            }
+            HostError::Parse { format, message } => write!(
+                f,
+                "input is not valid {} ({}); check --input-format",
+                format.name(),
+                message
+            ),
+            HostError::MissingFields { fields } => write!(
+                f,
+                "input omits score-affecting fields (--strict): {}",
+                fields.join(", ")
+            ),
+            HostError::ImageId(err) => write!(f, "{}", err),
+            HostError::InvalidProof { message } => write!(f, "invalid proof: {}", message),
+            HostError::VerifyFailed { failed, total } => {
+                write!(f, "{} of {} proofs failed verification", failed, total)
+            }
+            HostError::Validation(err) => write!(f, "invalid mining history: {}", err),
+            HostError::OutputMismatch { fields } => write!(
+                f,
+                "proof is valid but its output differs from the expected output in: {}",
+                fields.join(", ")
+            ),
+            HostError::ImageMismatch { expected, actual } => write!(
+                f,
+                "proof was made by image {}, not the built guest {}",
+                actual, expected
+            ),
+            HostError::TamperUndetected { mutation } => {
+                write!(f, "tampered proof ({}) still verifies", mutation)
+            }
+            HostError::BatchFailed { failed, total } => {
+                write!(f, "{} of {} players failed; see the manifest", failed, total)
+            }
+            HostError::NoManifests { dir } => {
+                write!(f, "no manifest.<i>-of-<n>.json files in {}", dir.display())
+            }
+            HostError::DuplicatePlayer { address, first, second } => write!(
+                f,
+                "player {} is in both {} and {}; shards must not overlap",
+                address,
+                first.display(),
+                second.display()
+            ),
+            HostError::Interrupted { completed, total } => write!(
+                f,
+                "interrupted after proving {} of {} players; manifest written",
+                completed, total
+            ),
+            HostError::OverBudget { what, actual, budget } => {
+                write!(f, "{} is {} bytes, over the budget of {} bytes", what, actual, budget)?;
+                match *what {
+                    "journal" => write!(f, " (a minimal_journal input commits far less)"),
+                    _ => write!(f, " (a groth16 receipt is far smaller on chain)"),
+                }
+            }
+            HostError::UnsupportedJournalVersion { version, min, max } => {
+                write!(
+                    f,
+                    "journal version {} is not supported by this host, which decodes versions \
+                     {} to {}",
+                    version, min, max
+                )?;
+                if version > max {
+                    write!(f, " (upgrade the host to read newer journals)")?;
+                }
+                Ok(())
+            }
+            HostError::OverCycleBudget { cycles, budget } => write!(
+                f,
+                "guest ran {} cycles, over the budget of {}; split the history with \
+                 --shard-size to prove it in smaller pieces",
+                cycles, budget
+            ),
+            HostError::ProverUnavailable { message } => write!(
+                f,
+                "succinct proving is unavailable ({}); it needs the RISC Zero proving \
+                 toolchain (rzup install). For development, set RISC0_DEV_MODE=1 for fake \
+                 receipts, pass --allow-fallback to execute the guest without proving, or \
+                 run `gridzero-host compute`",
+                message
+            ),
+            HostError::GuestFailed { message } => write!(f, "guest failed to execute: {}", message),
+            HostError::ProvingFailed { message } => write!(f, "proving failed: {}", message),
+            HostError::Listen { addr, message } => {
+                write!(f, "could not listen on {}: {}", addr, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HostError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HostError::Io { source, .. } => Some(source),
+            HostError::ImageId(err) => Some(err),
+            HostError::Validation(err) => Some(err),
+            HostError::Parse { .. }
+            | HostError::MissingFields { .. }
+            | HostError::InvalidProof { .. }
+            | HostError::VerifyFailed { .. }
+            | HostError::OutputMismatch { .. }
+            | HostError::ImageMismatch { .. }
+            | HostError::TamperUndetected { .. }
+            | HostError::BatchFailed { .. }
+            | HostError::NoManifests { .. }
+            | HostError::DuplicatePlayer { .. }
+            | HostError::Interrupted { .. }
+            | HostError::OverBudget { .. }
+            | HostError::OverCycleBudget { .. }
+            | HostError::UnsupportedJournalVersion { .. }
+            | HostError::ProverUnavailable { .. }
+            | HostError::GuestFailed { .. }
+            | HostError::ProvingFailed { .. }
+            | HostError::Listen { .. } => None,
+        }
+    }
+}
+
+impl From<ValidationError> for HostError {
+    fn from(err: ValidationError) -> Self {
+        HostError::Validation(err)
+    }
+}
+
+impl From<ImageIdError> for HostError {
+    fn from(err: ImageIdError) -> Self {
+        HostError::ImageId(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_errors_carry_kind_and_detail() {
+        let err = HostError::Parse {
+            format: InputFormat::Cbor,
+            message: "unexpected end".to_string(),
+        };
+        assert_eq!(
+            err.to_json(),
+            json!({
+                "error": err.to_string(),
+                "kind": "ParseError",
+                "detail": { "format": "CBOR", "message": "unexpected end" },
+            })
+        );
+
+        let err = HostError::from(ValidationError::DuplicateCell { x: 1, y: 2, z: 0 });
+        let value = err.to_json();
+        assert_eq!(value["kind"], "ValidationError");
+        assert_eq!(value["detail"], json!({ "DuplicateCell": { "x": 1, "y": 2, "z": 0 } }));
+    }
+
+    #[test]
+    fn exit_codes_follow_the_kind() {
+        let mismatch = HostError::OutputMismatch { fields: vec!["score".to_string()] };
+        assert_eq!(mismatch.exit_code(), EXIT_OUTPUT_MISMATCH);
+        let invalid = HostError::InvalidProof { message: String::new() };
+        assert_eq!(invalid.exit_code(), EXIT_INVALID_PROOF);
+        assert_eq!(HostError::from(ValidationError::Overflow).exit_code(), EXIT_FAILURE);
+    }
+}
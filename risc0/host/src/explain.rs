@@ -0,0 +1,54 @@
+//! `--explain`: print how an input scores without proving
+
+use clap::ValueEnum;
+use gridzero_core::{explain_score, LeaderboardInput, ScoreExplanation, ScoringMode};
+
+use crate::artifacts;
+use crate::error::HostError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExplainFormat {
+    Text,
+    Json,
+}
+
+pub fn print(input: &LeaderboardInput, format: ExplainFormat) -> Result<(), HostError> {
+    let explanation = explain_score(input)?;
+    match format {
+        ExplainFormat::Json => {
+            println!("{}", artifacts::to_json(&explanation));
+        }
+        ExplainFormat::Text => print_table(input, &explanation),
+    }
+    Ok(())
+}
+
+fn print_table(input: &LeaderboardInput, explanation: &ScoreExplanation) {
+    println!("\n🧾 Score breakdown for 0x{}", hex::encode(input.player_address));
+    println!(
+        "  {:>5}  {:>8}  {:<12}  {:<8}  {:>5}  {:>4}  {:>5}  {:>5}  {:>5}  {:>5}  {:>6}  {:>8}",
+        "#", "nonce", "cell", "ore", "base", "rare", "depth", "boost", "bonus", "back", "points", "total"
+    );
+    for row in &explanation.records {
+        println!(
+            "  {:>5}  {:>8}  {:<12}  {:<8}  {:>5}  {:>4}  {:>5}  {:>5}  {:>5}  {:>5}  {:>6}  {:>8}",
+            row.index,
+            row.nonce,
+            format!("({},{},{})", row.grid_x, row.grid_y, row.grid_z),
+            row.ore,
+            row.base,
+            format!("×{}", row.rare_multiplier),
+            row.depth_bonus,
+            format!("×{}", row.boost),
+            row.onboarding_bonus,
+            row.comeback_bonus,
+            row.points,
+            row.running_total
+        );
+    }
+    println!("\n  Per-record total: {}", explanation.per_record_total);
+    if explanation.scoring_mode != ScoringMode::PerRecord {
+        println!("  Scoring mode: {:?}", explanation.scoring_mode);
+    }
+    println!("  Final score: {}", explanation.score);
+}
@@ -0,0 +1,148 @@
+//! Image ID parsing
+//!
+//! An image ID is the 32-byte digest of the guest program (8 u32 words,
+//! hex-encoded in `image_id.hex`). Commands that take one accept either
+//! the hex string itself or a path to a file containing it.
+//!
+//! An allowlist file holds several image IDs, one hex ID per line; blank
+//! lines and `#` comments are ignored.
+
+use risc0_zkvm::sha::Digest;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Decoded length of an image ID in bytes
+pub const IMAGE_ID_LEN: usize = 32;
+
+#[derive(Debug)]
+pub enum ImageIdError {
+    /// The argument named a file that could not be read
+    Read { path: PathBuf, source: std::io::Error },
+    /// The string is not valid hex
+    InvalidHex { message: String },
+    /// The hex decoded cleanly but to the wrong number of bytes
+    WrongLength { len: usize },
+    /// A line of an allowlist file is not a valid image ID
+    AllowlistLine { line: usize, source: Box<ImageIdError> },
+    /// An allowlist file names no image IDs at all
+    EmptyAllowlist { path: PathBuf },
+}
+
+impl fmt::Display for ImageIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageIdError::Read { path, source } => {
+                write!(f, "cannot read image ID from {}: {}", path.display(), source)
+            }
+            ImageIdError::InvalidHex { message } => {
+                write!(f, "image ID is not valid hex: {}", message)
+            }
+            ImageIdError::WrongLength { len } => write!(
+                f,
+                "image ID must be {} bytes (8 u32 words), got {}",
+                IMAGE_ID_LEN, len
+            ),
+            ImageIdError::AllowlistLine { line, source } => {
+                write!(f, "allowlist line {}: {}", line, source)
+            }
+            ImageIdError::EmptyAllowlist { path } => {
+                write!(f, "image ID allowlist {} is empty", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImageIdError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageIdError::Read { source, .. } => Some(source),
+            ImageIdError::AllowlistLine { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Parse an image ID given as hex or as a path to an `image_id.hex` file
+pub fn parse_image_id(arg: &str) -> Result<Digest, ImageIdError> {
+    let path = Path::new(arg);
+    if path.is_file() {
+        let contents = fs::read_to_string(path).map_err(|source| ImageIdError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        return parse_image_id_hex(&contents);
+    }
+    parse_image_id_hex(arg)
+}
+
+/// Parse a hex-encoded image ID, with or without a `0x` prefix
+pub fn parse_image_id_hex(hex_str: &str) -> Result<Digest, ImageIdError> {
+    let trimmed = hex_str.trim();
+    let digits = trimmed.strip_prefix("0x").unwrap_or(trimmed);
+    let bytes = hex::decode(digits).map_err(|e| ImageIdError::InvalidHex {
+        message: e.to_string(),
+    })?;
+    if bytes.len() != IMAGE_ID_LEN {
+        return Err(ImageIdError::WrongLength { len: bytes.len() });
+    }
+    Ok(Digest::try_from(bytes.as_slice()).expect("length checked above"))
+}
+
+/// Parse an allowlist file of image IDs, one per line
+pub fn parse_allowlist(path: &Path) -> Result<Vec<Digest>, ImageIdError> {
+    let contents = fs::read_to_string(path).map_err(|source| ImageIdError::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut image_ids = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let entry = line.split('#').next().unwrap_or("").trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let image_id = parse_image_id_hex(entry).map_err(|source| ImageIdError::AllowlistLine {
+            line: index + 1,
+            source: Box::new(source),
+        })?;
+        if !image_ids.contains(&image_id) {
+            image_ids.push(image_id);
+        }
+    }
+    if image_ids.is_empty() {
+        return Err(ImageIdError::EmptyAllowlist {
+            path: path.to_path_buf(),
+        });
+    }
+    Ok(image_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    const HEX: &str = "0101010102020202030303030404040405050505060606060707070708080808";
+
+    #[test]
+    fn malformed_hex_is_rejected() {
+        let err = parse_image_id("not an image id").unwrap_err();
+        assert!(matches!(err, ImageIdError::InvalidHex { .. }));
+    }
+
+    #[test]
+    fn wrong_length_is_rejected() {
+        let err = parse_image_id(&HEX[..62]).unwrap_err();
+        assert!(matches!(err, ImageIdError::WrongLength { len: 31 }));
+    }
+
+    #[test]
+    fn file_and_string_parse_the_same() {
+        let path = temp_dir("image-id").join("image_id.hex");
+        fs::write(&path, format!("{}\n", HEX)).unwrap();
+        let from_file = parse_image_id(path.to_str().unwrap()).unwrap();
+        assert_eq!(from_file, parse_image_id(HEX).unwrap());
+        assert_eq!(from_file, parse_image_id(&format!("0x{}", HEX)).unwrap());
+        assert_eq!(hex::encode(from_file.as_bytes()), HEX);
+    }
+}
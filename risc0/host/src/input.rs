@@ -0,0 +1,401 @@
+//! Input loading
+//!
+//! `LeaderboardInput` can arrive as JSON (the default), CBOR or
+//! MessagePack. All three go through the same serde derives, so the
+//! only thing that changes is which deserializer reads the bytes.
+//!
+//! The path `-` reads from stdin, and gzip-compressed input (a `.gz`
+//! path or bytes starting with the gzip magic) is decompressed first.
+//!
+//! Fields added to the schema over time default when absent, which keeps
+//! old inputs loading but can silently change a score. Absent fields
+//! that affect the score are warned about, or rejected with `--strict`.
+//!
+//! An input can also be split into a header file holding every field but
+//! `mining_history` and a records file holding just the history, so a
+//! large record list can be stored apart from the player's settings.
+//!
+//! An economy preset (`--economy`) fills in the `economy` fields an input
+//! leaves out; fields the input does set win over the preset's.
+//!
+//! `--input-format guest` reads the exact bytes the host writes to the
+//! zkVM (see `guest_input_bytes`), as `--dump-guest-input` saves them.
+//! They are risc0 serde words with every field present, so nothing is
+//! defaulted; an economy preset or split input cannot apply to them.
+//!
+//! `--dedupe` drops records that repeat an earlier record field for field
+//! before the input is built, so an upstream double-send does not fail the
+//! duplicate-cell check. Two different records on one cell still do.
+
+use clap::ValueEnum;
+use flate2::read::GzDecoder;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::error::HostError;
+use crate::journal;
+use gridzero_core::{Economy, LeaderboardInput, MiningRecord};
+use serde_json::{Map, Value};
+
+/// Encoding of the input file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum InputFormat {
+    Json,
+    Cbor,
+    Msgpack,
+    /// Guest input words, as written by `--dump-guest-input`
+    Guest,
+}
+
+impl InputFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            InputFormat::Json => "JSON",
+            InputFormat::Cbor => "CBOR",
+            InputFormat::Msgpack => "MessagePack",
+            InputFormat::Guest => "guest input",
+        }
+    }
+}
+
+/// First two bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// `LeaderboardInput` fields that change the score, or how far the
+/// journal vouches for it, and default when absent
+pub const SCORE_AFFECTING_FIELDS: &[&str] = &[
+    "scoring_mode",
+    "onboarding_count",
+    "onboarding_bonus",
+    "grid_depth",
+    "depth_bonus_per_layer",
+    "economy",
+    "comeback_gap",
+    "comeback_bonus",
+    "window_start",
+    "window_end",
+    "boost_windows",
+    "trust_input",
+    "party_addresses",
+    "rounding",
+];
+
+/// `MiningRecord` fields that change the score and default when absent
+pub const SCORE_AFFECTING_RECORD_FIELDS: &[&str] = &["grid_z"];
+
+/// Read and decode an input file (or `-` for stdin) in the given format,
+/// warning about defaulted score-affecting fields (an error if `strict`)
+pub fn load_input(path: &Path, format: InputFormat, strict: bool) -> Result<LeaderboardInput, HostError> {
+    let bytes = read_input_bytes(path)?;
+    let input = parse_input(&bytes, format)?;
+    warn_defaulted(path, defaulted_score_fields(&bytes, format), strict)?;
+    Ok(input)
+}
+
+/// `load_input`, with `preset` supplying every economy field the input
+/// does not set itself
+pub fn load_input_with_preset(
+    path: &Path,
+    format: InputFormat,
+    strict: bool,
+    preset: &Economy,
+) -> Result<LeaderboardInput, HostError> {
+    let value = decode_value(&read_input_bytes(path)?, format)
+        .map_err(|message| HostError::Parse { format, message })?;
+    let Value::Object(input) = value else {
+        return Err(HostError::Parse {
+            format,
+            message: format!("{}: input is not a map", path.display()),
+        });
+    };
+    from_merged(path, apply_preset(input, Some(preset)), strict)
+}
+
+/// Read a header file and a records file (both in `format`) and merge
+/// them into one input, as if `mining_history` had been in the header
+pub fn load_split_input(
+    header_path: &Path,
+    records_path: &Path,
+    format: InputFormat,
+    strict: bool,
+    preset: Option<&Economy>,
+) -> Result<LeaderboardInput, HostError> {
+    let parse_error = |message: String| HostError::Parse { format, message };
+    let header = decode_value(&read_input_bytes(header_path)?, format).map_err(parse_error)?;
+    let records = decode_value(&read_input_bytes(records_path)?, format).map_err(parse_error)?;
+
+    let Value::Object(mut merged) = header else {
+        return Err(parse_error(format!("{}: header is not a map", header_path.display())));
+    };
+    if !records.is_array() {
+        return Err(parse_error(format!("{}: records are not a list", records_path.display())));
+    }
+    if merged.insert("mining_history".to_string(), records).is_some() {
+        return Err(parse_error(format!(
+            "{}: header already holds a mining_history",
+            header_path.display()
+        )));
+    }
+
+    from_merged(header_path, apply_preset(merged, preset), strict)
+}
+
+/// Overlay an input's own `economy` fields on `preset`'s
+fn apply_preset(mut input: Map<String, Value>, preset: Option<&Economy>) -> Map<String, Value> {
+    let Some(preset) = preset else {
+        return input;
+    };
+    let Ok(Value::Object(mut economy)) = serde_json::to_value(preset) else {
+        unreachable!("an economy serializes to a map");
+    };
+    if let Some(Value::Object(custom)) = input.get("economy") {
+        economy.extend(custom.clone());
+    }
+    input.insert("economy".to_string(), Value::Object(economy));
+    input
+}
+
+/// Decode an input assembled as a JSON map, re-encoding it so it goes
+/// through the usual parse and defaulted-field checks
+fn from_merged(path: &Path, merged: Map<String, Value>, strict: bool) -> Result<LeaderboardInput, HostError> {
+    let bytes = serde_json::to_vec(&merged).expect("JSON values always serialize");
+    let input = parse_input(&bytes, InputFormat::Json)?;
+    warn_defaulted(path, defaulted_score_fields(&bytes, InputFormat::Json), strict)?;
+    Ok(input)
+}
+
+/// Decode input bytes into a format-neutral value
+fn decode_value(bytes: &[u8], format: InputFormat) -> Result<Value, String> {
+    match format {
+        InputFormat::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+        InputFormat::Cbor => ciborium::from_reader(bytes).map_err(|e| e.to_string()),
+        InputFormat::Msgpack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+        InputFormat::Guest => Err("guest input words carry no field names".to_string()),
+    }
+}
+
+/// Warn about defaulted fields, or fail on them if `strict`
+fn warn_defaulted(path: &Path, defaulted: Vec<String>, strict: bool) -> Result<(), HostError> {
+    if defaulted.is_empty() {
+        return Ok(());
+    }
+    if strict {
+        return Err(HostError::MissingFields { fields: defaulted });
+    }
+    eprintln!(
+        "⚠️  {}: score-affecting fields absent and defaulted: {}",
+        path.display(),
+        defaulted.join(", ")
+    );
+    Ok(())
+}
+
+/// Score-affecting fields absent from encoded input, which serde filled
+/// with defaults. Record fields are reported once, as
+/// `mining_history[].field`. Inputs that do not decode to a map (such as
+/// MessagePack written in array form) have no field names to check.
+pub fn defaulted_score_fields(bytes: &[u8], format: InputFormat) -> Vec<String> {
+    let value = decode_value(bytes, format).ok();
+    let Some(object) = value.as_ref().and_then(|value| value.as_object()) else {
+        return Vec::new();
+    };
+
+    let mut missing: Vec<String> = SCORE_AFFECTING_FIELDS
+        .iter()
+        .filter(|field| !object.contains_key(**field))
+        .map(|field| field.to_string())
+        .collect();
+    let records = object
+        .get("mining_history")
+        .and_then(|history| history.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for field in SCORE_AFFECTING_RECORD_FIELDS {
+        let absent = records
+            .iter()
+            .any(|record| record.as_object().is_some_and(|record| !record.contains_key(*field)));
+        if absent {
+            missing.push(format!("mining_history[].{}", field));
+        }
+    }
+    missing
+}
+
+/// Raw input bytes, decompressed if they are gzipped
+pub fn read_input_bytes(path: &Path) -> Result<Vec<u8>, HostError> {
+    let bytes = if path == Path::new("-") {
+        let mut bytes = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(HostError::io(path))?;
+        bytes
+    } else {
+        fs::read(path).map_err(HostError::io(path))?
+    };
+
+    let gzipped = path.extension().is_some_and(|ext| ext == "gz") || bytes.starts_with(&GZIP_MAGIC);
+    if !gzipped {
+        return Ok(bytes);
+    }
+    let mut decompressed = Vec::new();
+    GzDecoder::new(bytes.as_slice())
+        .read_to_end(&mut decompressed)
+        .map_err(HostError::io(path))?;
+    Ok(decompressed)
+}
+
+/// Decode input bytes in the given format
+pub fn parse_input(bytes: &[u8], format: InputFormat) -> Result<LeaderboardInput, HostError> {
+    let parsed = match format {
+        InputFormat::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+        InputFormat::Cbor => ciborium::from_reader(bytes).map_err(|e| e.to_string()),
+        InputFormat::Msgpack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+        InputFormat::Guest => parse_guest_input(bytes),
+    };
+    parsed.map_err(|message| HostError::Parse { format, message })
+}
+
+/// The bytes `ExecutorEnv::write` sends the guest for `input`: its risc0
+/// serde words, little-endian
+pub fn guest_input_bytes(input: &LeaderboardInput) -> Vec<u8> {
+    journal::words(input).iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+/// Decode `guest_input_bytes` back into the input
+fn parse_guest_input(bytes: &[u8]) -> Result<LeaderboardInput, String> {
+    if !bytes.len().is_multiple_of(4) {
+        return Err(format!("{} bytes is not a whole number of words", bytes.len()));
+    }
+    let words: Vec<u32> = bytes
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+        .collect();
+    risc0_zkvm::serde::from_slice(&words).map_err(|e| e.to_string())
+}
+
+/// Drop records identical to an earlier one, keeping the first; returns
+/// how many were dropped
+pub fn dedupe_records(records: &mut Vec<MiningRecord>) -> usize {
+    let before = records.len();
+    let mut seen = HashSet::with_capacity(before);
+    records.retain(|record| seen.insert(record.clone()));
+    before - records.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{record, sample_input, temp_dir};
+    use gridzero_core::{compute_leaderboard, ValidationError};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn json_round_trips() {
+        let input = sample_input();
+        let bytes = serde_json::to_vec(&input).unwrap();
+        assert_eq!(parse_input(&bytes, InputFormat::Json).unwrap(), input);
+    }
+
+    #[test]
+    fn cbor_round_trips() {
+        let input = sample_input();
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&input, &mut bytes).unwrap();
+        assert_eq!(parse_input(&bytes, InputFormat::Cbor).unwrap(), input);
+    }
+
+    #[test]
+    fn msgpack_round_trips() {
+        let input = sample_input();
+        for bytes in [rmp_serde::to_vec_named(&input).unwrap(), rmp_serde::to_vec(&input).unwrap()] {
+            assert_eq!(parse_input(&bytes, InputFormat::Msgpack).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn gzipped_input_parses_like_its_plaintext() {
+        let dir = temp_dir("gzip-input");
+        let json = serde_json::to_vec(&sample_input()).unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&json).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        let plain = dir.join("input.json");
+        fs::write(&plain, &json).unwrap();
+        let expected = load_input(&plain, InputFormat::Json, false).unwrap();
+        // By extension, and by magic bytes alone
+        for name in ["input.json.gz", "input.bin"] {
+            let path = dir.join(name);
+            fs::write(&path, &gzipped).unwrap();
+            assert_eq!(load_input(&path, InputFormat::Json, false).unwrap(), expected);
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn strict_mode_rejects_a_missing_field() {
+        let dir = temp_dir("strict-input");
+        let mut json = serde_json::to_value(sample_input()).unwrap();
+        let path = dir.join("input.json");
+        fs::write(&path, json.to_string()).unwrap();
+        assert_eq!(load_input(&path, InputFormat::Json, true).unwrap(), sample_input());
+
+        json.as_object_mut().unwrap().remove("rounding");
+        json["mining_history"][1].as_object_mut().unwrap().remove("grid_z");
+        fs::write(&path, json.to_string()).unwrap();
+        let err = load_input(&path, InputFormat::Json, true).unwrap_err();
+        let HostError::MissingFields { fields } = err else {
+            panic!("expected missing fields, got {:?}", err);
+        };
+        assert_eq!(fields, vec!["rounding", "mining_history[].grid_z"]);
+        // Without --strict the defaults stand in, with a warning
+        assert_eq!(load_input(&path, InputFormat::Json, false).unwrap(), sample_input());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn split_input_matches_the_combined_form() {
+        let dir = temp_dir("split-input");
+        let mut header = serde_json::to_value(sample_input()).unwrap();
+        let records = header.as_object_mut().unwrap().remove("mining_history").unwrap();
+        let combined = dir.join("input.json");
+        let header_path = dir.join("header.json");
+        let records_path = dir.join("records.json");
+        fs::write(&combined, serde_json::to_vec(&sample_input()).unwrap()).unwrap();
+        fs::write(&header_path, header.to_string()).unwrap();
+        fs::write(&records_path, records.to_string()).unwrap();
+
+        let split = load_split_input(&header_path, &records_path, InputFormat::Json, true, None).unwrap();
+        assert_eq!(split, load_input(&combined, InputFormat::Json, true).unwrap());
+
+        // The records belong in one file only
+        let err = load_split_input(&combined, &records_path, InputFormat::Json, true, None).unwrap_err();
+        assert!(matches!(err, HostError::Parse { .. }), "{:?}", err);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn dedupe_drops_exact_repeats_only() {
+        let mut input = sample_input();
+        let first = input.mining_history[0].clone();
+        input.mining_history.push(first.clone());
+        input.mining_history.insert(1, first.clone());
+        let kept = input.mining_history.len() - 2;
+        assert_eq!(dedupe_records(&mut input.mining_history), 2);
+        assert_eq!(input.mining_history.len(), kept);
+        assert_eq!(input.mining_history[0], first);
+        assert!(compute_leaderboard(&input).is_ok());
+
+        // A different record on the same cell is a conflict, not a repeat
+        input.mining_history.push(record(first.grid_x, first.grid_y, (first.ore_type + 1) % 8, 99));
+        assert_eq!(dedupe_records(&mut input.mining_history), 0);
+        assert_eq!(
+            compute_leaderboard(&input),
+            Err(ValidationError::DuplicateCell { x: first.grid_x, y: first.grid_y, z: 0 })
+        );
+    }
+}
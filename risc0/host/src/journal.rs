@@ -0,0 +1,273 @@
+//! Journal decoding
+//!
+//! A journal is either a full `LeaderboardOutput` or a score-only
+//! `MinimalOutput`. Both start with a `version` word, and
+//! `MINIMAL_JOURNAL_FLAG` in it says which layout follows.
+//!
+//! The rest of the version word must lie in
+//! `MIN_SUPPORTED_JOURNAL_VERSION..=MAX_SUPPORTED_JOURNAL_VERSION`, the
+//! layouts this host knows how to decode; anything else is rejected
+//! before decoding rather than misread as the current layout.
+//!
+//! `dump` prints the raw layout annotated field by field, for chasing
+//! decode mismatches against on-chain structs; `layout` is the same
+//! breakdown as offsets alone, for pinning it (see `layout`'s module).
+
+use gridzero_core::{
+    AggregateOutput, JournalOutput, CORE_VERSION, JOURNAL_VERSION, MINIMAL_JOURNAL_FLAG,
+};
+use risc0_zkvm::Journal;
+use serde::Serialize;
+
+use crate::error::HostError;
+
+/// Oldest journal version this host decodes
+pub const MIN_SUPPORTED_JOURNAL_VERSION: u32 = JOURNAL_VERSION;
+
+/// Newest journal version this host decodes
+pub const MAX_SUPPORTED_JOURNAL_VERSION: u32 = JOURNAL_VERSION;
+
+/// Reject a version word (minimal flag and all) outside the supported range
+pub fn check_version(version: u32) -> Result<(), HostError> {
+    let layout_version = version & !MINIMAL_JOURNAL_FLAG;
+    if !(MIN_SUPPORTED_JOURNAL_VERSION..=MAX_SUPPORTED_JOURNAL_VERSION).contains(&layout_version) {
+        return Err(HostError::UnsupportedJournalVersion {
+            version: layout_version,
+            min: MIN_SUPPORTED_JOURNAL_VERSION,
+            max: MAX_SUPPORTED_JOURNAL_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// Decode a journal in whichever layout its version word names
+pub fn decode(journal: &Journal) -> Result<JournalOutput, HostError> {
+    let version = journal
+        .bytes
+        .get(..4)
+        .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+        .ok_or_else(|| HostError::InvalidProof {
+            message: "journal is empty".to_string(),
+        })?;
+    check_version(version)?;
+
+    let decoded = if version & MINIMAL_JOURNAL_FLAG != 0 {
+        journal.decode().map(JournalOutput::Minimal)
+    } else {
+        journal.decode().map(|output| JournalOutput::Full(Box::new(output)))
+    };
+    decoded.map_err(|e| HostError::InvalidProof {
+        message: format!("journal (version {:#x}) does not decode: {}", version, e),
+    })
+}
+
+/// Warn if a journal was produced under different core rules than this
+/// host was built with: the guest image is stale (or newer), so the
+/// host's native checks no longer predict what it enforces. Minimal
+/// journals do not carry a core version.
+pub fn warn_on_core_version(output: &JournalOutput) {
+    if let Some(core_version) = stale_core_version(output) {
+        eprintln!(
+            "⚠️  STALE GUEST: journal was produced by core rules v{}, but this host has v{}; \
+             rebuild the guest so native checks match what it proves",
+            core_version, CORE_VERSION
+        );
+    }
+}
+
+/// The journal's core version, if it is not this host's `CORE_VERSION`
+fn stale_core_version(output: &JournalOutput) -> Option<u32> {
+    match output {
+        JournalOutput::Full(output) if output.core_version != CORE_VERSION => Some(output.core_version),
+        _ => None,
+    }
+}
+
+/// One committed field: its name and its words in the journal
+type Field = (&'static str, Vec<u32>);
+
+/// Bytes shown per line of a dump
+const DUMP_WIDTH: usize = 16;
+
+/// The journal's risc0 serde words for one value
+pub(crate) fn words<T: Serialize>(value: &T) -> Vec<u32> {
+    risc0_zkvm::serde::to_vec(value).expect("journal types always serialize")
+}
+
+/// `(name, words)` for the listed fields of a struct, in order
+macro_rules! fields {
+    ($value:expr; $($field:ident),* $(,)?) => {
+        vec![$((stringify!($field), words(&$value.$field))),*]
+    };
+}
+
+/// Fields of a full or minimal journal, in commit order
+fn output_fields(output: &JournalOutput) -> Vec<Field> {
+    match output {
+        JournalOutput::Full(output) => fields![output;
+            version, player_address, total_mined, score, ore_count, ore_inventory,
+            rare_inventory, unique_cells, provenance_root, history_root, longest_session_len,
+            longest_session_duration, hasher, input_cid, scoring_mode, prestige_level,
+            onboarding_count, onboarding_bonus, grid_depth, depth_bonus_per_layer,
+            inventory_commitment, now, max_age, economy, economy_hash, window_start, window_end,
+            core_version, milestones_reached, map, coverage_bps, checkpoint, score_delta,
+            client_checksum, ordered_achievements_met, tiebreak_mode, tiebreak, inventory_hidden,
+            no_gaps, nonce_range, region_rows, region_cols, region_scores, allow_remine,
+            remine_cooldown, map_seed, beacon, comeback_gap, comeback_bonus, comeback_bonus_total,
+            lease_range, lease_token, boosts_hash, tier, tier_thresholds, score_hidden, validated,
+            party_addresses, member_scores, member_cells, rounding,
+        ],
+        JournalOutput::Minimal(output) => fields![output; version, player_address, score, history_root],
+    }
+}
+
+/// `(field, word offset, word count)` for each committed field of `output`
+pub fn layout(output: &JournalOutput) -> Vec<(&'static str, usize, usize)> {
+    let mut offset = 0;
+    output_fields(output)
+        .into_iter()
+        .map(|(name, field_words)| {
+            let field = (name, offset, field_words.len());
+            offset += field_words.len();
+            field
+        })
+        .collect()
+}
+
+/// Print a journal's bytes with offsets, each byte range labelled with
+/// the field it encodes. risc0 serde widens every `u8` and `bool` to a
+/// little-endian word and writes `u64`s as two words (low word first),
+/// which is where on-chain decoders usually disagree. `aggregate` says
+/// the journal is an aggregator guest's `AggregateOutput`.
+pub fn dump(journal: &Journal, aggregate: bool) -> Result<(), HostError> {
+    let fields = if aggregate {
+        let output: AggregateOutput = journal.decode().map_err(|e| HostError::InvalidProof {
+            message: format!("aggregate journal does not decode: {}", e),
+        })?;
+        check_version(output.output.version)?;
+        let mut fields = fields![output; shard_image_id, shard_count];
+        fields.extend(output_fields(&JournalOutput::Full(Box::new(output.output))));
+        fields
+    } else {
+        output_fields(&decode(journal)?)
+    };
+
+    let bytes = &journal.bytes;
+    println!("\n🔬 Journal layout ({} bytes)", bytes.len());
+    println!("  {:<6}  {:<width$}  field", "offset", "bytes", width = DUMP_WIDTH * 3 - 1);
+    let mut offset = 0;
+    for (name, field_words) in &fields {
+        let end = offset + field_words.len() * 4;
+        let Some(field_bytes) = bytes.get(offset..end) else {
+            println!("  ⚠️  journal ends before {}", name);
+            return Ok(());
+        };
+        for (line, chunk) in field_bytes.chunks(DUMP_WIDTH).enumerate() {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+            let label = if line == 0 { *name } else { "" };
+            println!(
+                "  {:#06x}  {:<width$}  {}",
+                offset + line * DUMP_WIDTH,
+                hex.join(" "),
+                label,
+                width = DUMP_WIDTH * 3 - 1
+            );
+        }
+        offset = end;
+    }
+    if offset != bytes.len() {
+        println!("  ⚠️  {} trailing bytes not covered by the layout", bytes.len() - offset);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_input;
+    use gridzero_core::compute_leaderboard;
+
+    /// The journal the guest commits for `output`
+    fn committed(output: &JournalOutput) -> Journal {
+        Journal::new(words(output).iter().flat_map(|word| word.to_le_bytes()).collect())
+    }
+
+    #[test]
+    fn both_journal_modes_decode() {
+        let input = sample_input();
+        let output = compute_leaderboard(&input).unwrap();
+
+        let full = JournalOutput::new(output.clone(), false);
+        assert_eq!(decode(&committed(&full)).unwrap(), full);
+
+        let minimal = decode(&committed(&JournalOutput::new(output.clone(), true))).unwrap();
+        let JournalOutput::Minimal(minimal) = minimal else {
+            panic!("a minimal journal decoded as the full layout");
+        };
+        assert_eq!(minimal.version, JOURNAL_VERSION | MINIMAL_JOURNAL_FLAG);
+        assert_eq!(minimal.player_address, input.player_address);
+        assert_eq!(minimal.score, output.score);
+        assert_eq!(minimal.history_root, output.history_root);
+    }
+
+    #[test]
+    fn a_journal_from_other_core_rules_is_stale() {
+        let mut output = compute_leaderboard(&sample_input()).unwrap();
+        assert_eq!(stale_core_version(&JournalOutput::new(output.clone(), false)), None);
+        output.core_version = CORE_VERSION - 1;
+        assert_eq!(
+            stale_core_version(&JournalOutput::new(output.clone(), false)),
+            Some(CORE_VERSION - 1)
+        );
+        // Minimal journals carry no core version to compare
+        assert_eq!(stale_core_version(&JournalOutput::new(output, true)), None);
+    }
+
+    #[test]
+    fn the_dump_layout_covers_every_journal_byte() {
+        let output = compute_leaderboard(&sample_input()).unwrap();
+        for minimal in [false, true] {
+            let journal = JournalOutput::new(output.clone(), minimal);
+            let fields = layout(&journal);
+            let mut offset = 0;
+            for &(_, field_offset, field_words) in &fields {
+                assert_eq!(field_offset, offset);
+                offset += field_words;
+            }
+            assert_eq!(offset * 4, committed(&journal).bytes.len());
+            assert_eq!(fields[0].0, "version");
+        }
+    }
+
+    #[test]
+    fn only_decodable_journals_dump() {
+        let output = compute_leaderboard(&sample_input()).unwrap();
+        for minimal in [false, true] {
+            let journal = committed(&JournalOutput::new(output.clone(), minimal));
+            assert!(dump(&journal, false).is_ok());
+            let truncated = Journal::new(journal.bytes[..journal.bytes.len() - 4].to_vec());
+            assert!(matches!(dump(&truncated, false), Err(HostError::InvalidProof { .. })));
+        }
+    }
+
+    #[test]
+    fn an_out_of_range_version_is_rejected() {
+        assert!(check_version(JOURNAL_VERSION).is_ok());
+        assert!(check_version(JOURNAL_VERSION | MINIMAL_JOURNAL_FLAG).is_ok());
+
+        let newer = check_version(MAX_SUPPORTED_JOURNAL_VERSION + 1).unwrap_err();
+        assert!(matches!(
+            newer,
+            HostError::UnsupportedJournalVersion { version, .. } if version == JOURNAL_VERSION + 1
+        ));
+        assert!(newer.to_string().contains("upgrade the host"), "{}", newer);
+
+        let mut output = compute_leaderboard(&sample_input()).unwrap();
+        output.version = MIN_SUPPORTED_JOURNAL_VERSION - 1;
+        let older = decode(&committed(&JournalOutput::new(output, false))).unwrap_err();
+        let message = older.to_string();
+        let expected = format!("journal version {} is not supported", JOURNAL_VERSION - 1);
+        assert!(message.contains(&expected), "{}", message);
+        assert!(!message.contains("upgrade the host"), "{}", message);
+    }
+}
@@ -0,0 +1,174 @@
+//! Journal layout snapshot
+//!
+//! `snapshot` renders the risc0 serde layout of a canonical full and
+//! minimal journal: every field's word offset and length. Off-chain and
+//! on-chain decoders are written against exactly this layout, so a test
+//! compares it with `journal_layout.txt`, the snapshot committed next to
+//! this crate, and fails on any change. An intentional layout change
+//! bumps `JOURNAL_VERSION` (the snapshot's first line) and regenerates
+//! the snapshot in the same commit with
+//! `cargo test -p gridzero-host write_the_snapshot -- --ignored`.
+//!
+//! The canonical output sets every `Option` and fills every `Vec` (with
+//! fixed lengths), so both shapes of each field are pinned and the
+//! layout does not depend on any scoring rule. Per-ore vectors hold the
+//! default economy's `ORE_TYPES` entries; an economy with more ore types
+//! lengthens each of them, and `ore_count` says by how much.
+
+use gridzero_core::hashing::HASHER_SHA256;
+use gridzero_core::{
+    Checkpoint, Economy, JournalOutput, LeaderboardOutput, MapCommitment, NonceRange, RoundingMode,
+    ScoringMode, TiebreakMode, CORE_VERSION, JOURNAL_VERSION, NO_MILESTONE, ORE_TYPES,
+};
+
+use crate::journal;
+
+/// The snapshot text: the journal version, then `<layout> <field>
+/// <offset> <words>` per field, offsets and lengths in 32-bit words
+pub fn snapshot() -> String {
+    let mut text = format!("JOURNAL_VERSION {}\n", JOURNAL_VERSION);
+    let outputs = [
+        ("full", JournalOutput::new(canonical_output(), false)),
+        ("minimal", JournalOutput::new(canonical_output(), true)),
+    ];
+    for (kind, output) in &outputs {
+        for (name, offset, words) in journal::layout(output) {
+            text.push_str(&format!("{} {} {} {}\n", kind, name, offset, words));
+        }
+    }
+    text
+}
+
+/// Lines (by content) present in only one of the two snapshots
+#[cfg(test)]
+fn changed_lines(expected: &str, actual: &str) -> Vec<String> {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let removed = expected
+        .iter()
+        .filter(|line| !actual.contains(line))
+        .map(|line| format!("-{}", line));
+    let added = actual
+        .iter()
+        .filter(|line| !expected.contains(line))
+        .map(|line| format!("+{}", line));
+    removed.chain(added).collect()
+}
+
+/// An output with every field in its largest shape
+pub(crate) fn canonical_output() -> LeaderboardOutput {
+    LeaderboardOutput {
+        version: JOURNAL_VERSION,
+        player_address: [0x11; 20],
+        total_mined: 1,
+        score: 1,
+        ore_count: ORE_TYPES as u8,
+        ore_inventory: vec![1; ORE_TYPES],
+        rare_inventory: vec![0; ORE_TYPES],
+        unique_cells: 1,
+        provenance_root: [0x22; 32],
+        history_root: [0x33; 32],
+        longest_session_len: 1,
+        longest_session_duration: 0,
+        hasher: HASHER_SHA256,
+        input_cid: [0x44; 32],
+        scoring_mode: ScoringMode::PerRecord,
+        prestige_level: 0,
+        onboarding_count: 0,
+        onboarding_bonus: 0,
+        grid_depth: 0,
+        depth_bonus_per_layer: 0,
+        inventory_commitment: [0x55; 32],
+        now: 0,
+        max_age: Some(0),
+        economy: Economy {
+            rarity_cutoffs: Some(vec![0; ORE_TYPES]),
+            ..Economy::default()
+        },
+        economy_hash: [0x66; 32],
+        window_start: Some(0),
+        window_end: Some(0),
+        core_version: CORE_VERSION,
+        milestones_reached: vec![NO_MILESTONE; ORE_TYPES],
+        map: Some(MapCommitment {
+            map_id: 0,
+            map_root: [0x77; 32],
+            authority: [0x88; 32],
+        }),
+        coverage_bps: 0,
+        checkpoint: Some(Checkpoint {
+            total_mined: 0,
+            ore_inventory: vec![0; ORE_TYPES],
+        }),
+        score_delta: 1,
+        client_checksum: Some([0x99; 32]),
+        ordered_achievements_met: vec![true, false],
+        tiebreak_mode: TiebreakMode::UniqueCells,
+        tiebreak: 1,
+        inventory_hidden: false,
+        no_gaps: true,
+        nonce_range: Some(NonceRange { start: 0, end: 0 }),
+        region_rows: 2,
+        region_cols: 2,
+        region_scores: vec![1, 0, 0, 0],
+        allow_remine: false,
+        remine_cooldown: 0,
+        map_seed: Some([0xaa; 32]),
+        beacon: [0xbb; 32],
+        comeback_gap: 0,
+        comeback_bonus: 0,
+        comeback_bonus_total: 0,
+        lease_range: Some(NonceRange { start: 0, end: 0 }),
+        lease_token: [0xcc; 32],
+        boosts_hash: [0xdd; 32],
+        tier: 1,
+        tier_thresholds: vec![1, 10],
+        score_hidden: false,
+        validated: true,
+        party_addresses: vec![[0x01; 20], [0x02; 20]],
+        member_scores: vec![1, 0],
+        member_cells: vec![1, 0],
+        rounding: RoundingMode::Floor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    /// `JOURNAL_VERSION` and the SHA-256 of the snapshot it ships with. A
+    /// layout change bumps the version and replaces both, so a version
+    /// never names two layouts.
+    const RELEASED_LAYOUT: (u32, &str) =
+        (10, "33455c2d2a858fd9c4db1fec0d9d571422143815765393da1c765bc98f9f29d0");
+
+    #[test]
+    fn layout_matches_the_snapshot() {
+        let changed = changed_lines(include_str!("../journal_layout.txt"), &snapshot());
+        assert!(
+            changed.is_empty(),
+            "journal layout differs from journal_layout.txt; bump JOURNAL_VERSION and \
+             regenerate it if intended:\n{}",
+            changed.join("\n")
+        );
+    }
+
+    #[test]
+    fn the_layout_is_pinned_to_its_version() {
+        let digest = hex::encode(Sha256::digest(snapshot()));
+        assert_eq!(
+            (JOURNAL_VERSION, digest.as_str()),
+            RELEASED_LAYOUT,
+            "the journal layout changed without a new JOURNAL_VERSION in RELEASED_LAYOUT"
+        );
+    }
+
+    /// Regenerates `journal_layout.txt` after an intentional layout change
+    #[test]
+    #[ignore]
+    fn write_the_snapshot() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/journal_layout.txt");
+        std::fs::write(path, snapshot()).unwrap();
+    }
+}
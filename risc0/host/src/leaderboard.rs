@@ -0,0 +1,120 @@
+//! Leaderboard root
+//!
+//! After an unsharded batch the host commits to the whole leaderboard
+//! with one Merkle root, for anchoring on chain, and writes every player
+//! an inclusion proof against it.
+//!
+//! Each leaf is the SHA-256 of a player's 20-byte address followed by
+//! their score as a big-endian `u64` (28 bytes). Leaves are sorted by
+//! address, then score, so the root depends only on the set of results,
+//! not on input order. The tree is `hashing::merkle_root` with SHA-256:
+//! parents are `sha256(left || right)` and an unpaired node is carried
+//! up a level unchanged.
+//!
+//! Writes `leaderboard_root.hex` and `inclusion_0x<address>.json`.
+
+use gridzero_core::hashing::{self, MerkleStep, Sha256Hasher};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use crate::artifacts;
+use crate::error::HostError;
+
+pub const ROOT_FILE: &str = "leaderboard_root.hex";
+
+/// One step of an inclusion proof, hex-encoded
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InclusionStep {
+    pub sibling: String,
+    pub sibling_on_left: bool,
+}
+
+/// A player's proof of inclusion in the leaderboard root
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InclusionProof {
+    pub player_address: String,
+    pub score: u64,
+    pub leaf_index: usize,
+    pub leaf: String,
+    pub proof: Vec<InclusionStep>,
+    pub root: String,
+}
+
+/// Leaf committing to one player's score
+pub fn leaf(player_address: &[u8; 20], score: u64) -> [u8; 32] {
+    let mut data = [0u8; 28];
+    data[..20].copy_from_slice(player_address);
+    data[20..].copy_from_slice(&score.to_be_bytes());
+    Sha256::digest(data).into()
+}
+
+/// Compute the root over `(address, score)` results and write it with
+/// every player's inclusion proof into `dir`; returns the root
+pub fn write(dir: &Path, results: &[([u8; 20], u64)]) -> Result<[u8; 32], HostError> {
+    let mut sorted = results.to_vec();
+    sorted.sort();
+    let leaves: Vec<[u8; 32]> = sorted.iter().map(|(address, score)| leaf(address, *score)).collect();
+    let root = hashing::merkle_root(&Sha256Hasher, &leaves);
+    artifacts::write_bytes(&dir.join(ROOT_FILE), hex::encode(root))?;
+
+    for (leaf_index, (address, score)) in sorted.iter().enumerate() {
+        let steps = hashing::merkle_proof(&Sha256Hasher, &leaves, leaf_index);
+        debug_assert!(hashing::verify_merkle_proof(&Sha256Hasher, leaves[leaf_index], &steps, root));
+        let proof = InclusionProof {
+            player_address: format!("0x{}", hex::encode(address)),
+            score: *score,
+            leaf_index,
+            leaf: hex::encode(leaves[leaf_index]),
+            proof: steps.iter().map(hex_step).collect(),
+            root: hex::encode(root),
+        };
+        let path = dir.join(format!("inclusion_0x{}.json", hex::encode(address)));
+        artifacts::write_json(&path, &proof)?;
+    }
+    Ok(root)
+}
+
+fn hex_step(step: &MerkleStep) -> InclusionStep {
+    InclusionStep {
+        sibling: hex::encode(step.sibling),
+        sibling_on_left: step.sibling_on_left,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+    use std::fs;
+
+    fn unhex(hex: &str) -> [u8; 32] {
+        hex::decode(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn every_inclusion_proof_verifies_against_the_root() {
+        // Unpaired nodes show up at every odd size
+        for players in 1..=7u8 {
+            let dir = temp_dir(&format!("leaderboard-{}", players));
+            let results: Vec<([u8; 20], u64)> =
+                (0..players).rev().map(|i| ([i; 20], 100 * i as u64)).collect();
+            let root = write(&dir, &results).unwrap();
+            assert_eq!(fs::read_to_string(dir.join(ROOT_FILE)).unwrap(), hex::encode(root));
+
+            for (address, score) in &results {
+                let path = dir.join(format!("inclusion_0x{}.json", hex::encode(address)));
+                let proof: InclusionProof = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+                assert_eq!((proof.score, unhex(&proof.leaf)), (*score, leaf(address, *score)));
+                let computed = proof.proof.iter().fold(unhex(&proof.leaf), |node, step| {
+                    let sibling = unhex(&step.sibling);
+                    let (left, right) = if step.sibling_on_left { (sibling, node) } else { (node, sibling) };
+                    Sha256::new().chain_update(left).chain_update(right).finalize().into()
+                });
+                assert_eq!(computed, root);
+                assert_eq!(unhex(&proof.root), root);
+            }
+            fs::remove_dir_all(dir).unwrap();
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! GridZero host library
+//!
+//! Everything behind the `gridzero-host` binary, plus an in-memory API
+//! for integrators: `prove_leaderboard` and `verify_proof` work on
+//! inputs and proof bytes directly, with no files involved. With the
+//! `tokio` feature, `prove_leaderboard_async` runs the same proof on
+//! tokio's blocking pool so async services are not stalled.
+//! `estimate_journal_size` predicts the journal's length without proving.
+
+pub mod artifacts;
+pub mod batch;
+pub mod cache;
+pub mod canonicalize;
+pub mod check_image;
+pub mod compute;
+pub mod csv_log;
+pub mod cycles;
+pub mod disjoint;
+pub mod economy;
+pub mod error;
+pub mod explain;
+pub mod image_id;
+pub mod input;
+pub mod journal;
+pub mod layout;
+pub mod leaderboard;
+pub mod list_ores;
+pub mod merge_manifests;
+pub mod meta;
+pub mod prove;
+pub mod replay;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod tamper;
+#[cfg(test)]
+mod testing;
+pub mod verify;
+
+use gridzero_core::{compute_leaderboard, compute_shard, JournalOutput, LeaderboardInput};
+use risc0_zkvm::sha::Digest;
+
+pub use error::HostError;
+
+/// Validate and prove `input` with the built guest, returning the
+/// serialized receipt (as written to `proof.bin`) and its journal
+pub fn prove_leaderboard(input: &LeaderboardInput) -> Result<(Vec<u8>, JournalOutput), HostError> {
+    compute_leaderboard(input)?;
+    prove::prove(input, None)
+}
+
+/// `prove_leaderboard` on tokio's blocking thread pool, for calling from
+/// async code. A panic while proving is resumed in the caller, as the
+/// sync API would panic.
+#[cfg(feature = "tokio")]
+pub async fn prove_leaderboard_async(
+    input: LeaderboardInput,
+) -> Result<(Vec<u8>, JournalOutput), HostError> {
+    joined(tokio::task::spawn_blocking(move || prove_leaderboard(&input)).await)
+}
+
+/// The result of a joined proving task. A cancelled task is a
+/// `ProvingFailed`, not a `ProverUnavailable`: `--allow-fallback` retries
+/// the latter elsewhere, and nothing says the prover is down.
+#[cfg(feature = "tokio")]
+fn joined<T>(
+    joined: Result<Result<T, HostError>, tokio::task::JoinError>,
+) -> Result<T, HostError> {
+    match joined {
+        Ok(proved) => proved,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(err) => Err(HostError::ProvingFailed {
+            message: format!("proving task was cancelled: {}", err),
+        }),
+    }
+}
+
+/// Byte length of the journal proving `input` would commit, for gas
+/// estimates. Computed natively from the same rules and commit layout
+/// the guest uses, so it is exact but fails wherever proving would.
+pub fn estimate_journal_size(input: &LeaderboardInput) -> Result<usize, HostError> {
+    let words = match input.shard {
+        Some(_) => journal::words(&compute_shard(input)?),
+        None => journal::words(&JournalOutput::new(
+            compute_leaderboard(input)?,
+            input.minimal_journal,
+        )),
+    };
+    Ok(words.len() * 4)
+}
+
+/// Verify serialized receipt bytes against `image_id` and decode the
+/// journal; the programmatic counterpart of `gridzero-host verify`
+pub fn verify_proof(proof_bytes: &[u8], image_id: &[u32; 8]) -> Result<JournalOutput, HostError> {
+    verify::verify_receipt(proof_bytes, Digest::from(*image_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_input;
+    use gridzero_methods::GRIDZERO_GUEST_ID;
+
+    /// Proves for real, so it needs the built guest and a prover (or
+    /// `RISC0_DEV_MODE=1`): run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn proofs_verify_from_bytes() {
+        let (proof_bytes, output) = prove_leaderboard(&sample_input()).unwrap();
+        assert_eq!(verify_proof(&proof_bytes, &GRIDZERO_GUEST_ID).unwrap(), output);
+
+        let mut forked = GRIDZERO_GUEST_ID;
+        forked[0] ^= 1;
+        assert!(matches!(
+            verify_proof(&proof_bytes, &forked),
+            Err(HostError::InvalidProof { .. })
+        ));
+    }
+
+    #[test]
+    fn a_minimal_journal_is_estimated_smaller() {
+        let full = estimate_journal_size(&sample_input()).unwrap();
+        let minimal = LeaderboardInput {
+            minimal_journal: true,
+            ..sample_input()
+        };
+        assert!(estimate_journal_size(&minimal).unwrap() < full);
+        let zero = LeaderboardInput {
+            player_address: [0; 20],
+            ..sample_input()
+        };
+        assert!(matches!(estimate_journal_size(&zero), Err(HostError::Validation(_))));
+    }
+
+    /// Executes the built guest: run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn the_estimate_is_the_executed_journal_length() {
+        for minimal_journal in [false, true] {
+            let input = LeaderboardInput {
+                minimal_journal,
+                ..sample_input()
+            };
+            let env = risc0_zkvm::ExecutorEnv::builder().write(&input).unwrap().build().unwrap();
+            let session = prove::execute(env, gridzero_methods::GRIDZERO_GUEST_ELF).unwrap();
+            let journal = session.journal.expect("the guest commits a journal");
+            assert_eq!(estimate_journal_size(&input).unwrap(), journal.bytes.len());
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(future)
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn async_proving_reports_invalid_inputs() {
+        let zero = LeaderboardInput {
+            player_address: [0; 20],
+            ..sample_input()
+        };
+        assert!(matches!(
+            block_on(prove_leaderboard_async(zero)),
+            Err(HostError::Validation(gridzero_core::ValidationError::ZeroAddress))
+        ));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn a_panicking_proving_task_panics_in_the_caller() {
+        let task = block_on(async {
+            tokio::task::spawn_blocking(|| -> Result<(), HostError> { panic!("prover bug") }).await
+        });
+        let resumed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| joined(task))).unwrap_err();
+        assert_eq!(resumed.downcast_ref::<&str>(), Some(&"prover bug"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn a_cancelled_proving_task_is_not_retried_elsewhere() {
+        let cancelled = block_on(async {
+            let task = tokio::spawn(std::future::pending::<Result<(), HostError>>());
+            task.abort();
+            task.await
+        });
+        assert!(matches!(joined(cancelled), Err(HostError::ProvingFailed { .. })));
+    }
+
+    /// Proves for real; run with `cargo test --features tokio -- --ignored`
+    #[cfg(feature = "tokio")]
+    #[test]
+    #[ignore]
+    fn async_proofs_verify_from_bytes() {
+        let (proof_bytes, output) = block_on(prove_leaderboard_async(sample_input())).unwrap();
+        assert_eq!(verify_proof(&proof_bytes, &GRIDZERO_GUEST_ID).unwrap(), output);
+    }
+}
@@ -0,0 +1,102 @@
+//! `gridzero-host list-ores`: print the ore index table
+//!
+//! Records name their ore by index (`ore_type`); this lists each index
+//! with its name, base score and rare multiplier under the default
+//! economy, or under `--preset`. `--json` prints the same table as a
+//! JSON array for tooling. Ores past the named eight are listed by index.
+
+use clap::Args;
+use gridzero_core::{ore_name, Economy};
+use serde::Serialize;
+
+use crate::artifacts;
+use crate::economy::EconomyPreset;
+use crate::error::HostError;
+
+#[derive(Args)]
+pub struct ListOresArgs {
+    /// Show scores under a built-in preset instead of the default economy
+    #[arg(long, value_enum)]
+    preset: Option<EconomyPreset>,
+
+    /// Print the table as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+/// One row of the table
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct OreInfo {
+    index: usize,
+    name: String,
+    base_score: u64,
+    rare_multiplier: u64,
+}
+
+pub fn run(args: &ListOresArgs) -> Result<(), HostError> {
+    let ores = ores(&args.preset.map_or_else(Economy::default, EconomyPreset::economy));
+    if args.json {
+        println!("{}", artifacts::to_json(&ores));
+        return Ok(());
+    }
+    println!("  {:>5}  {:<10}  {:>10}  {:>15}", "index", "ore", "base score", "rare multiplier");
+    for ore in &ores {
+        println!(
+            "  {:>5}  {:<10}  {:>10}  {:>15}",
+            ore.index, ore.name, ore.base_score, ore.rare_multiplier
+        );
+    }
+    Ok(())
+}
+
+/// The table's rows for `economy`, one per ore type
+fn ores(economy: &Economy) -> Vec<OreInfo> {
+    (0..economy.ore_count())
+        .map(|index| OreInfo {
+            index,
+            name: ore_name(index),
+            base_score: economy.base_scores[index],
+            rare_multiplier: economy.rare_multipliers[index],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gridzero_core::{BASE_SCORES, ORE_NAMES, ORE_TYPES};
+
+    #[test]
+    fn the_default_table_lists_the_named_ores() {
+        let ores = ores(&Economy::default());
+        assert_eq!(ores.len(), ORE_TYPES);
+        for (index, ore) in ores.iter().enumerate() {
+            assert_eq!((ore.index, ore.name.as_str()), (index, ORE_NAMES[index]));
+            assert_eq!(ore.base_score, BASE_SCORES[index]);
+        }
+    }
+
+    #[test]
+    fn unnamed_ores_are_listed_by_index() {
+        let mut economy = Economy::default();
+        economy.base_scores.push(7);
+        economy.rare_multipliers.push(2);
+        let extra = ores(&economy).pop().unwrap();
+        assert_eq!(
+            extra,
+            OreInfo {
+                index: ORE_TYPES,
+                name: format!("Ore {}", ORE_TYPES),
+                base_score: 7,
+                rare_multiplier: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn the_json_table_has_one_object_per_ore() {
+        let json = serde_json::to_value(ores(&Economy::default())).unwrap();
+        assert_eq!(json[0]["name"], ORE_NAMES[0]);
+        assert_eq!(json.as_array().unwrap().len(), ORE_TYPES);
+    }
+}
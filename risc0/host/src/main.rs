@@ -1,98 +1,89 @@
-/// GridZero Leaderboard Proof Host
-/// 
-/// Generates RISC Zero proofs for leaderboard score verification
-/// and submits them to zkVerify for on-chain attestation.
+//! GridZero Leaderboard Proof Host
+//!
+//! Generates RISC Zero proofs for leaderboard score verification
+//! and submits them to zkVerify for on-chain attestation.
 
-use risc0_zkvm::{default_prover, ExecutorEnv, ProverOpts};
-use serde::{Deserialize, Serialize};
-use std::fs;
+use clap::{Parser, Subcommand};
+use gridzero_host::{
+    artifacts, canonicalize, check_image, compute, disjoint, economy, error, list_ores,
+    merge_manifests, prove, replay, tamper, verify,
+};
 
-// Import the guest program's image ID
-use gridzero_methods::GRIDZERO_GUEST_ID;
+/// Command-line arguments
+///
+/// Without a subcommand the host proves an input file, which keeps the
+/// original `gridzero-host input.json` invocation working.
+#[derive(Parser)]
+#[command(
+    name = "gridzero-host",
+    about = "GridZero leaderboard proof generator",
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-/// A single mining result
-#[derive(Serialize, Deserialize, Clone)]
-pub struct MiningRecord {
-    pub grid_x: u8,
-    pub grid_y: u8,
-    pub ore_type: u8,
-    pub is_rare: bool,
-    pub random_output: [u8; 32],
-    pub nonce: u64,
-}
+    /// How failures are reported on stderr
+    #[arg(long, global = true, value_enum, default_value_t = error::ErrorFormat::Text)]
+    error_format: error::ErrorFormat,
+
+    /// Write JSON outputs (output.json, meta.json, manifests) minified
+    /// instead of pretty-printed
+    #[arg(long, global = true)]
+    compact: bool,
 
-/// Input for the guest program
-#[derive(Serialize, Deserialize)]
-pub struct LeaderboardInput {
-    pub player_address: [u8; 20],
-    pub mining_history: Vec<MiningRecord>,
+    #[command(flatten)]
+    prove: prove::ProveArgs,
 }
 
-/// Output from the guest program
-#[derive(Serialize, Deserialize, Debug)]
-pub struct LeaderboardOutput {
-    pub player_address: [u8; 20],
-    pub total_mined: u64,
-    pub score: u64,
-    pub ore_inventory: [u64; 8],
-    pub rare_inventory: [u64; 8],
-    pub unique_cells: u64,
+#[derive(Subcommand)]
+enum Command {
+    /// Verify a proof against a guest image ID
+    Verify(verify::VerifyArgs),
+    /// Check that a proof was made by the guest built into this host
+    CheckImage(check_image::CheckImageArgs),
+    /// Rewrite an input in canonical form (records in nonce order, defaults filled)
+    Canonicalize(canonicalize::CanonicalizeArgs),
+    /// Compute the output a proof would commit, natively and without proving
+    Compute(compute::ComputeArgs),
+    /// Check saved output.json and proof artifacts against their input
+    Replay(replay::ReplayArgs),
+    /// Merge the manifests of sharded batch runs into one ranked leaderboard
+    MergeManifests(merge_manifests::MergeManifestsArgs),
+    /// Prove whether two players mined disjoint sets of cells
+    ProveDisjoint(disjoint::ProveDisjointArgs),
+    /// Serve proofs over HTTP
+    #[cfg(feature = "server")]
+    Serve(gridzero_host::server::ServeArgs),
+    /// Print the hash a proof commits for an economy
+    EconomyHash(economy::EconomyHashArgs),
+    /// List ore indices with their names and scores
+    ListOres(list_ores::ListOresArgs),
+    /// Diagnostic: confirm tampered copies of a proof fail verification
+    #[command(hide = true)]
+    TamperTest(tamper::TamperTestArgs),
 }
 
 fn main() {
-    // Load mining history from file (in production, from database)
-    let input_path = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| "input.json".to_string());
-    
-    let input_data = fs::read_to_string(&input_path)
-        .expect("Failed to read input file");
-    let input: LeaderboardInput = serde_json::from_str(&input_data)
-        .expect("Failed to parse input");
-    
-    println!("🎮 GridZero Leaderboard Proof Generator");
-    println!("  Player: 0x{}", hex::encode(&input.player_address));
-    println!("  Mining records: {}", input.mining_history.len());
-    
-    // Build executor environment with input
-    let env = ExecutorEnv::builder()
-        .write(&input)
-        .unwrap()
-        .build()
-        .unwrap();
-    
-    // Generate proof
-    println!("\n⚙️  Generating RISC Zero proof...");
-    let prover = default_prover();
-    let receipt = prover
-        .prove_with_opts(env, GRIDZERO_GUEST_ID, &ProverOpts::succinct())
-        .expect("Proof generation failed");
-    
-    // Extract public output
-    let output: LeaderboardOutput = receipt.journal.decode().unwrap();
-    
-    println!("\n📊 Verified Leaderboard Stats:");
-    println!("  Total mined: {}", output.total_mined);
-    println!("  Score: {}", output.score);
-    println!("  Unique cells: {}", output.unique_cells);
-    println!("  Ore inventory: {:?}", output.ore_inventory);
-    println!("  Rare inventory: {:?}", output.rare_inventory);
-    
-    // Serialize proof for zkVerify submission
-    let proof_bytes = bincode::serialize(&receipt).unwrap();
-    let proof_hex = hex::encode(&proof_bytes);
-    
-    // Save proof artifacts
-    fs::write("proof.bin", &proof_bytes).unwrap();
-    fs::write("proof.hex", &proof_hex).unwrap();
-    fs::write("output.json", serde_json::to_string_pretty(&output).unwrap()).unwrap();
-    
-    // Save image ID (verification key for zkVerify)
-    let image_id_hex = hex::encode(GRIDZERO_GUEST_ID.as_bytes());
-    fs::write("image_id.hex", &image_id_hex).unwrap();
-    
-    println!("\n✅ Proof generated!");
-    println!("  Proof: proof.bin ({} bytes)", proof_bytes.len());
-    println!("  Image ID: {}", image_id_hex);
-    println!("\nNext: Submit to zkVerify using zkverifyjs");
+    let cli = Cli::parse();
+    artifacts::set_compact_json(cli.compact);
+    let result = match &cli.command {
+        Some(Command::Verify(args)) => verify::run(args),
+        Some(Command::CheckImage(args)) => check_image::run(args),
+        Some(Command::Canonicalize(args)) => canonicalize::run(args),
+        Some(Command::Compute(args)) => compute::run(args),
+        Some(Command::Replay(args)) => replay::run(args),
+        Some(Command::MergeManifests(args)) => merge_manifests::run(args),
+        Some(Command::ProveDisjoint(args)) => disjoint::run(args),
+        #[cfg(feature = "server")]
+        Some(Command::Serve(args)) => gridzero_host::server::run(args),
+        Some(Command::EconomyHash(args)) => economy::run(args),
+        Some(Command::ListOres(args)) => list_ores::run(args),
+        Some(Command::TamperTest(args)) => tamper::run(args),
+        None => prove::run(&cli.prove),
+    };
+    if let Err(err) = result {
+        err.report(cli.error_format);
+        std::process::exit(err.exit_code());
+    }
 }
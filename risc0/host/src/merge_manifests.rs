@@ -0,0 +1,261 @@
+//! `gridzero-host merge-manifests <dir>`: combine sharded batch runs
+//!
+//! Reads every `manifest.<i>-of-<n>.json` in a directory, as `--shard i/n`
+//! batch runs write them, and writes `leaderboard.json`: the proven
+//! players of all of them, ranked by score (highest first). Ties share a
+//! rank and are listed by address, so the result does not depend on
+//! which shard proved whom. Shards partition the players, so one proven
+//! by two manifests is an error rather than a duplicate leaderboard row.
+//!
+//! When the manifests are every shard of one `/n` split and none was
+//! interrupted or aborted, the leaderboard root an unsharded batch would
+//! have written is written too, with its inclusion proofs (see
+//! `leaderboard`).
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::artifacts;
+use crate::batch::{self, Manifest, ManifestEntry};
+use crate::error::HostError;
+use crate::input::InputFormat;
+use crate::leaderboard;
+
+pub const LEADERBOARD_FILE: &str = "leaderboard.json";
+
+#[derive(Args)]
+pub struct MergeManifestsArgs {
+    /// Directory holding the shard runs' manifest.<i>-of-<n>.json files
+    dir: PathBuf,
+
+    /// Where to write the merged leaderboard (defaults to leaderboard.json in the directory)
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+/// The merged leaderboard written to `leaderboard.json`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MergedLeaderboard {
+    /// Manifests merged, in file name order
+    pub manifests: Vec<PathBuf>,
+    /// Leaderboard root over every player, if the shards were complete
+    pub root: Option<String>,
+    pub players: Vec<RankedPlayer>,
+}
+
+/// One proven player with its rank across all shards
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RankedPlayer {
+    /// 1-based; players with equal scores share the rank of the first
+    pub rank: usize,
+    /// `--shard i/n` of the run that proved the player
+    pub shard: Option<String>,
+    #[serde(flatten)]
+    pub entry: ManifestEntry,
+}
+
+pub fn run(args: &MergeManifestsArgs) -> Result<(), HostError> {
+    let paths = manifest_paths(&args.dir)?;
+    let mut manifests = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let json = fs::read(path).map_err(HostError::io(path))?;
+        let manifest: Manifest = serde_json::from_slice(&json).map_err(|e| HostError::Parse {
+            format: InputFormat::Json,
+            message: format!("{}: {}", path.display(), e),
+        })?;
+        manifests.push(manifest);
+    }
+
+    println!("🧩 Merging {} manifests from {}", manifests.len(), args.dir.display());
+    let players = merge(&paths, &manifests)?;
+    let failures: usize = manifests.iter().map(|manifest| manifest.summary.failures).sum();
+
+    let root = if complete_split(&manifests) {
+        let results: Vec<([u8; 20], u64)> = players
+            .iter()
+            .map(|player| (batch::parse_address(&player.entry.player_address), player.entry.score))
+            .collect();
+        Some(hex::encode(leaderboard::write(&args.dir, &results)?))
+    } else {
+        println!("  ⚠️  Manifests are not one complete split; no leaderboard root is written");
+        None
+    };
+
+    let output_path = args.output.clone().unwrap_or_else(|| args.dir.join(LEADERBOARD_FILE));
+    let merged = MergedLeaderboard {
+        manifests: paths,
+        root,
+        players,
+    };
+    artifacts::write_json(&output_path, &merged)?;
+
+    println!("  Players: {}", merged.players.len());
+    if failures > 0 {
+        println!("  ⚠️  {} inputs failed across the shards and are not ranked", failures);
+    }
+    if let Some(root) = &merged.root {
+        println!("  Leaderboard root: {}", root);
+    }
+    println!("  Leaderboard: {}", output_path.display());
+    Ok(())
+}
+
+/// Shard manifests in `dir`, sorted by file name
+fn manifest_paths(dir: &Path) -> Result<Vec<PathBuf>, HostError> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(HostError::io(dir))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with("manifest.") && name.contains("-of-") && name.ends_with(".json")
+                })
+        })
+        .collect();
+    paths.sort();
+    if paths.is_empty() {
+        return Err(HostError::NoManifests {
+            dir: dir.to_path_buf(),
+        });
+    }
+    Ok(paths)
+}
+
+/// Every manifest's players, ranked; fails on a player in two manifests
+fn merge(paths: &[PathBuf], manifests: &[Manifest]) -> Result<Vec<RankedPlayer>, HostError> {
+    let mut seen: BTreeMap<&str, &Path> = BTreeMap::new();
+    let mut players = Vec::new();
+    for (path, manifest) in paths.iter().zip(manifests) {
+        for entry in &manifest.players {
+            if let Some(first) = seen.insert(&entry.player_address, path) {
+                return Err(HostError::DuplicatePlayer {
+                    address: entry.player_address.clone(),
+                    first: first.to_path_buf(),
+                    second: path.clone(),
+                });
+            }
+            players.push(RankedPlayer {
+                rank: 0,
+                shard: manifest.summary.shard.clone(),
+                entry: entry.clone(),
+            });
+        }
+    }
+
+    players.sort_by(|a, b| {
+        b.entry
+            .score
+            .cmp(&a.entry.score)
+            .then_with(|| a.entry.player_address.cmp(&b.entry.player_address))
+    });
+    let mut previous_score = None;
+    for index in 0..players.len() {
+        let score = players[index].entry.score;
+        players[index].rank = match previous_score {
+            Some(previous) if previous == score => players[index - 1].rank,
+            _ => index + 1,
+        };
+        previous_score = Some(score);
+    }
+    Ok(players)
+}
+
+/// Whether the manifests are shards `0..n` of one `/n` split, each once,
+/// and every run attempted all of its inputs
+fn complete_split(manifests: &[Manifest]) -> bool {
+    let mut count = None;
+    let mut indices = BTreeSet::new();
+    for manifest in manifests {
+        let summary = &manifest.summary;
+        if summary.interrupted || summary.aborted {
+            return false;
+        }
+        let shard = summary.shard.as_deref().map(batch::parse_batch_shard);
+        let Some(Ok(shard)) = shard else {
+            return false;
+        };
+        if *count.get_or_insert(shard.count) != shard.count || !indices.insert(shard.index) {
+            return false;
+        }
+    }
+    count.is_some_and(|count| indices.len() as u64 == count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch::ManifestSummary;
+    use crate::testing::temp_dir;
+
+    fn entry(player: u8, score: u64) -> ManifestEntry {
+        ManifestEntry {
+            player_address: format!("0x{}", hex::encode([player; 20])),
+            input: PathBuf::from(format!("input_{}.json", player)),
+            proof: PathBuf::from(format!("proof_{}.bin", player)),
+            proof_hex: None,
+            output: PathBuf::from(format!("output_{}.json", player)),
+            meta: PathBuf::from(format!("meta_{}.json", player)),
+            proof_bytes: 0,
+            score,
+            elapsed_ms: 0,
+        }
+    }
+
+    fn write_shard(dir: &Path, index: u64, players: Vec<ManifestEntry>) {
+        let manifest = Manifest {
+            summary: ManifestSummary {
+                total_players: players.len(),
+                proven: players.len(),
+                failures: 0,
+                total_ms: 0,
+                interrupted: false,
+                shard: Some(format!("{}/2", index)),
+                skipped: 0,
+                aborted: false,
+            },
+            players,
+            failures: Vec::new(),
+        };
+        artifacts::write_json(&dir.join(format!("manifest.{}-of-2.json", index)), &manifest).unwrap();
+    }
+
+    #[test]
+    fn two_shards_merge_into_one_ranking() {
+        let dir = temp_dir("merge-manifests");
+        write_shard(&dir, 0, vec![entry(0xaa, 10), entry(0xcc, 30)]);
+        write_shard(&dir, 1, vec![entry(0xbb, 30), entry(0xdd, 5)]);
+        let args = MergeManifestsArgs {
+            dir: dir.clone(),
+            output: None,
+        };
+        run(&args).unwrap();
+
+        let json = fs::read(dir.join(LEADERBOARD_FILE)).unwrap();
+        let merged: MergedLeaderboard = serde_json::from_slice(&json).unwrap();
+        let ranking: Vec<(usize, u8, &str)> = merged
+            .players
+            .iter()
+            .map(|player| {
+                let address = batch::parse_address(&player.entry.player_address);
+                (player.rank, address[0], player.shard.as_deref().unwrap())
+            })
+            .collect();
+        // The tie at 30 shares first place and is listed by address
+        assert_eq!(ranking, [(1, 0xbb, "1/2"), (1, 0xcc, "0/2"), (3, 0xaa, "0/2"), (4, 0xdd, "1/2")]);
+        assert!(merged.root.is_some());
+
+        // A player proven by both shards is an error, not two rows
+        write_shard(&dir, 1, vec![entry(0xbb, 30), entry(0xaa, 10)]);
+        let err = run(&args).unwrap_err();
+        let HostError::DuplicatePlayer { address, first, second } = &err else {
+            panic!("expected a duplicate player, got {:?}", err);
+        };
+        assert_eq!(address, &format!("0x{}", hex::encode([0xaa; 20])));
+        assert!(first.ends_with("manifest.0-of-2.json") && second.ends_with("manifest.1-of-2.json"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,264 @@
+//! Proof metadata
+//!
+//! `meta.json` sits next to the proof artifacts and records facts about
+//! how a proof was produced that are not part of the journal itself.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+use gridzero_core::score::{check_freshness, grid_layers};
+use gridzero_core::{LeaderboardInput, MiningRecord, GRID_SIZE};
+
+#[derive(Serialize)]
+pub struct Meta {
+    pub image_id: String,
+    pub proof_bytes: usize,
+    pub cache_hit: bool,
+    pub validation_report: ValidationReport,
+    /// Caller-supplied `--tag` values; never part of the journal
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+    /// Provenance of the host binary, with `--build-info`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<BuildInfo>,
+}
+
+/// What built the host that made a proof, captured at compile time by
+/// `build.rs` ("unknown" where it could not be determined)
+#[derive(Serialize)]
+pub struct BuildInfo {
+    pub git_commit: &'static str,
+    /// Unix time the host was compiled
+    pub build_timestamp: u64,
+    pub risc0_zkvm_version: &'static str,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        BuildInfo {
+            git_commit: env!("GRIDZERO_GIT_COMMIT"),
+            build_timestamp: env!("GRIDZERO_BUILD_TIMESTAMP")
+                .parse()
+                .expect("build.rs writes a number"),
+            risc0_zkvm_version: env!("GRIDZERO_RISC0_ZKVM_VERSION"),
+        }
+    }
+}
+
+/// Parse a `--tag key=value` argument. Keys are non-empty and limited to
+/// ASCII letters, digits, `_`, `-` and `.` so they work as JSON keys and
+/// shell variables alike; the value is everything after the first `=`.
+pub fn parse_tag(arg: &str) -> Result<(String, String), String> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected key=value, got `{}`", arg))?;
+    if key.is_empty() {
+        return Err("tag key is empty".to_string());
+    }
+    if let Some(bad) = key
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+    {
+        return Err(format!("tag key `{}` contains `{}`", key, bad));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Collect parsed tags, the last value winning when a key repeats
+pub fn tags(args: &[(String, String)]) -> BTreeMap<String, String> {
+    args.iter().cloned().collect()
+}
+
+/// Which anti-cheat checks a proof enforced
+#[derive(Serialize)]
+pub struct ValidationReport {
+    pub checks: Vec<CheckEntry>,
+}
+
+#[derive(Serialize)]
+pub struct CheckEntry {
+    pub name: &'static str,
+    /// Whether the guest enforces this check for the given input
+    pub enabled: bool,
+    /// Outcome of the host-side pre-check, where one exists
+    pub host_precheck: HostCheck,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HostCheck {
+    Passed,
+    Failed,
+    NotRun,
+}
+
+impl HostCheck {
+    fn from_pass(passed: bool) -> Self {
+        if passed {
+            HostCheck::Passed
+        } else {
+            HostCheck::Failed
+        }
+    }
+}
+
+impl ValidationReport {
+    /// Names of enabled checks whose host pre-check failed
+    pub fn failures(&self) -> Vec<&'static str> {
+        self.checks
+            .iter()
+            .filter(|check| check.host_precheck == HostCheck::Failed)
+            .map(|check| check.name)
+            .collect()
+    }
+}
+
+/// Build the report for `input`, running the host pre-checks that mirror
+/// the guest's assertions
+pub fn validation_report(input: &LeaderboardInput) -> ValidationReport {
+    let records = &input.mining_history;
+    let layers = grid_layers(input.grid_depth);
+    let checks = vec![
+        CheckEntry {
+            name: "ore_type",
+            enabled: true,
+            host_precheck: HostCheck::from_pass(
+                records.iter().all(|r| (r.ore_type as usize) < input.economy.ore_count()),
+            ),
+        },
+        CheckEntry {
+            name: "bounds",
+            enabled: true,
+            host_precheck: HostCheck::from_pass(
+                records.iter().all(|r| {
+                    r.grid_x < GRID_SIZE && r.grid_y < GRID_SIZE && (r.grid_z as usize) < layers
+                }),
+            ),
+        },
+        CheckEntry {
+            name: "duplicates",
+            enabled: true,
+            host_precheck: HostCheck::from_pass(no_duplicate_cells(records)),
+        },
+        CheckEntry {
+            name: "provenance_order",
+            enabled: true,
+            host_precheck: HostCheck::from_pass(provenance_in_order(records)),
+        },
+        CheckEntry {
+            name: "nonce_monotonicity",
+            enabled: true,
+            host_precheck: HostCheck::from_pass(nonces_unique(records)),
+        },
+        CheckEntry {
+            name: "max_age",
+            enabled: input.max_age.is_some(),
+            host_precheck: match input.max_age {
+                Some(_) => HostCheck::from_pass(
+                    check_freshness(
+                        records.iter().map(|r| r.timestamp).max(),
+                        input.now,
+                        input.max_age,
+                    )
+                    .is_ok(),
+                ),
+                None => HostCheck::NotRun,
+            },
+        },
+        CheckEntry {
+            name: "vrf",
+            enabled: false,
+            host_precheck: HostCheck::NotRun,
+        },
+        CheckEntry {
+            name: "rate_limit",
+            enabled: false,
+            host_precheck: HostCheck::NotRun,
+        },
+        CheckEntry {
+            name: "rarity_ratio",
+            enabled: false,
+            host_precheck: HostCheck::NotRun,
+        },
+    ];
+    ValidationReport { checks }
+}
+
+fn no_duplicate_cells(records: &[MiningRecord]) -> bool {
+    let mut seen = HashSet::new();
+    records.iter().all(|r| seen.insert((r.grid_x, r.grid_y, r.grid_z)))
+}
+
+fn nonces_unique(records: &[MiningRecord]) -> bool {
+    let mut seen = HashSet::new();
+    records.iter().all(|r| seen.insert(r.nonce))
+}
+
+fn provenance_in_order(records: &[MiningRecord]) -> bool {
+    let mut by_nonce: Vec<&MiningRecord> = records.iter().collect();
+    by_nonce.sort_by_key(|r| r.nonce);
+    by_nonce
+        .windows(2)
+        .all(|pair| pair[0].block_height <= pair[1].block_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{prove_args, sample_input, try_prove_args};
+
+    #[test]
+    fn build_info_is_populated() {
+        let build = BuildInfo::current();
+        assert!(!build.git_commit.is_empty());
+        assert!(!build.risc0_zkvm_version.is_empty());
+        assert!(build.build_timestamp > 0);
+
+        let json = serde_json::to_value(&build).unwrap();
+        for field in ["git_commit", "build_timestamp", "risc0_zkvm_version"] {
+            assert!(json.get(field).is_some(), "meta.json is missing `{}`", field);
+        }
+    }
+
+    #[test]
+    fn tags_parse_and_the_last_value_wins() {
+        assert_eq!(parse_tag("run.id=42=x"), Ok(("run.id".to_string(), "42=x".to_string())));
+        assert!(parse_tag("no-equals").is_err());
+        assert!(parse_tag("=value").is_err());
+        assert!(parse_tag("bad key=value").is_err());
+
+        let args = vec![parse_tag("env=staging").unwrap(), parse_tag("env=prod").unwrap()];
+        assert_eq!(tags(&args), BTreeMap::from([("env".to_string(), "prod".to_string())]));
+        assert!(try_prove_args(&["--tag", "bad key=value"]).is_err());
+        assert_eq!(prove_args(&["--tag", "env=staging", "--tag", "env=prod"]).tags, args);
+    }
+
+    fn precheck(report: &ValidationReport, name: &str) -> HostCheck {
+        report.checks.iter().find(|check| check.name == name).unwrap().host_precheck
+    }
+
+    #[test]
+    fn the_report_flags_failed_prechecks() {
+        let input = sample_input();
+        let report = validation_report(&input);
+        assert!(report.failures().is_empty());
+        assert_eq!(precheck(&report, "max_age"), HostCheck::NotRun);
+
+        let mut duplicated = input.clone();
+        let first = duplicated.mining_history[0].clone();
+        duplicated.mining_history.push(MiningRecord { nonce: 99, block_height: 99, ..first });
+        assert_eq!(validation_report(&duplicated).failures(), ["duplicates"]);
+
+        let mut out_of_order = input;
+        out_of_order.mining_history[0].block_height = u64::MAX;
+        assert_eq!(validation_report(&out_of_order).failures(), ["provenance_order"]);
+    }
+
+    #[test]
+    fn the_report_serializes_snake_case_outcomes() {
+        let json = serde_json::to_value(validation_report(&sample_input())).unwrap();
+        let checks = json["checks"].as_array().unwrap();
+        let duplicates = checks.iter().find(|check| check["name"] == "duplicates").unwrap();
+        assert_eq!(duplicates["host_precheck"], "passed");
+    }
+}
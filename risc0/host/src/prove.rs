@@ -0,0 +1,1026 @@
+//! Proof generation
+//!
+//! The default command. One input path proves a single player into the
+//! unsuffixed artifact names; several paths run a batch (see `batch`).
+//! With `--shard-size` each history is proven in shards that the
+//! aggregator guest composes into the final proof.
+
+use clap::Args;
+use gridzero_core::{
+    aggregate_input, check_shardable, compute_leaderboard, score, split_into_shards,
+    AggregateOutput, JournalOutput, LeaderboardInput, ShardOutput,
+};
+use risc0_zkvm::{
+    get_prover_server, sha::Digest, ExecutorEnv, ExecutorImpl, ProverOpts, Receipt, Session,
+    VerifierContext,
+};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::artifacts::{self, ArtifactPaths, ReceiptFormat};
+use crate::batch::{self, BatchShard};
+use crate::cache::ProofCache;
+use crate::canonicalize;
+use crate::csv_log;
+use crate::cycles;
+use crate::economy::{self, EconomyPreset};
+use crate::error::HostError;
+use crate::explain::{self, ExplainFormat};
+use crate::input::{self, InputFormat};
+use crate::journal;
+use crate::meta::{self, BuildInfo, Meta};
+use crate::verify::decode_receipt;
+use gridzero_methods::{
+    GRIDZERO_AGGREGATOR_ELF, GRIDZERO_AGGREGATOR_ID, GRIDZERO_GUEST_ELF, GRIDZERO_GUEST_ID,
+};
+
+/// Arguments for the default prove command
+#[derive(Args)]
+pub struct ProveArgs {
+    /// Mining histories to prove (in production, exported from the database);
+    /// `-` reads stdin and gzipped files are decompressed transparently.
+    /// More than one path runs a batch.
+    #[arg(default_value = "input.json")]
+    pub inputs: Vec<PathBuf>,
+
+    /// Encoding of the input files
+    #[arg(long, value_enum, default_value_t = InputFormat::Json)]
+    pub input_format: InputFormat,
+
+    /// Directory artifacts are written to
+    #[arg(long, default_value = ".")]
+    pub output_dir: PathBuf,
+
+    /// Reuse artifacts from previous runs over identical input
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// How the receipt is written: bincode to proof.bin or serde JSON to
+    /// proof.json (`verify` reads either)
+    #[arg(long, value_enum, default_value_t = ReceiptFormat::Bincode)]
+    pub receipt_format: ReceiptFormat,
+
+    /// Skip writing proof.hex (proof.bin already holds the same bytes)
+    #[arg(long)]
+    pub no_hex: bool,
+
+    /// Skip writing image_id.hex
+    #[arg(long)]
+    pub no_image_id: bool,
+
+    /// Reject inputs that omit score-affecting fields instead of
+    /// defaulting them with a warning
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Commit the input's content identifier (input_cid) into the journal
+    #[arg(long)]
+    pub input_cid: bool,
+
+    /// Sort records into nonce order before proving, which changes
+    /// `input_cid` and `--explain` indices but never the score
+    #[arg(long)]
+    pub auto_sort: bool,
+
+    /// In a batch, print each proven player's output to stdout as one
+    /// JSON line as soon as it is proven
+    #[arg(long, conflicts_with = "shard_size")]
+    pub ndjson: bool,
+
+    /// Accept the zero player address, which is otherwise rejected
+    #[arg(long)]
+    pub allow_zero_address: bool,
+
+    /// Drop records that exactly repeat an earlier record before proving.
+    /// Different records on the same cell are still rejected.
+    #[arg(long)]
+    pub dedupe: bool,
+
+    /// Cap the prover's thread pool (defaults to one thread per CPU).
+    /// Fewer threads leave room for other jobs on shared runners at the
+    /// cost of proportionally slower proving; the proof is unaffected.
+    #[arg(long)]
+    pub threads: Option<NonZeroUsize>,
+
+    /// Print a per-record score breakdown instead of proving
+    /// (`--explain` for a table, `--explain=json` for JSON)
+    #[arg(long, value_enum, num_args = 0..=1, require_equals = true, default_missing_value = "text")]
+    pub explain: Option<ExplainFormat>,
+
+    /// Execute the guest without proving and print its cycles per
+    /// segment and per phase (reading, map, records, ordering, roots,
+    /// output, commit)
+    #[arg(long, conflicts_with_all = ["explain", "shard_size"])]
+    pub dump_cycles_by_segment: bool,
+
+    /// Write the exact input bytes sent to the guest, after every host-side
+    /// override, to this file; `--input-format guest` proves from them
+    #[arg(long, value_name = "PATH", conflicts_with = "shard_size")]
+    pub dump_guest_input: Option<PathBuf>,
+
+    /// Prove each history in shards of at most this many records and
+    /// aggregate them into one proof, verified against the aggregator's
+    /// image ID. Sharded proofs bypass the proof cache.
+    #[arg(long)]
+    pub shard_size: Option<NonZeroUsize>,
+
+    /// Record `key=value` in meta.json (repeatable; a repeated key keeps
+    /// its last value). Tags are for tracing and never affect the proof.
+    #[arg(long = "tag", value_name = "KEY=VALUE", value_parser = meta::parse_tag)]
+    pub tags: Vec<(String, String)>,
+
+    /// Only prove the players in batch shard `i` of `n` (by address hash),
+    /// so `n` processes can split one batch between them
+    #[arg(long, value_name = "I/N", value_parser = batch::parse_batch_shard)]
+    pub shard: Option<BatchShard>,
+
+    /// In a batch, stop at the first player that fails (the manifest is
+    /// still written)
+    #[arg(long)]
+    pub fail_fast: bool,
+
+    /// In a batch, record failed players in the manifest and carry on
+    /// (the default)
+    #[arg(long = "continue", conflicts_with = "fail_fast")]
+    pub continue_on_failure: bool,
+
+    /// Append a row per proof to this CSV file (header written if new);
+    /// a batch appends one row per player it proves
+    #[arg(long, value_name = "PATH")]
+    pub csv: Option<PathBuf>,
+
+    /// Input settings (every field but `mining_history`), proven as one
+    /// input together with `--records` instead of the input paths
+    #[arg(long, requires = "records", conflicts_with_all = ["inputs", "shard"])]
+    pub header: Option<PathBuf>,
+
+    /// The `mining_history` list that goes with `--header`
+    #[arg(long, requires = "header")]
+    pub records: Option<PathBuf>,
+
+    /// Print the journal's bytes annotated with the field each range encodes
+    #[arg(long)]
+    pub dump_journal: bool,
+
+    /// Fail, before writing artifacts, if the serialized proof is larger
+    #[arg(long, value_name = "N")]
+    pub max_proof_bytes: Option<usize>,
+
+    /// Fail, before writing artifacts, if the journal is larger
+    #[arg(long, value_name = "N")]
+    pub max_journal_bytes: Option<usize>,
+
+    /// Abort before proving if executing the guest takes more cycles
+    /// (checked per shard with --shard-size)
+    #[arg(long, value_name = "N")]
+    pub max_cycles: Option<u64>,
+
+    /// Start every input from a built-in economy; economy fields the
+    /// input sets itself override the preset's
+    #[arg(long, value_enum)]
+    pub economy: Option<EconomyPreset>,
+
+    /// Stamp meta.json with the host's git commit, build time and
+    /// risc0-zkvm version
+    #[arg(long)]
+    pub build_info: bool,
+
+    /// INSECURE: skip per-record validation in the guest for inputs
+    /// validated elsewhere; the proof commits validated: false
+    #[arg(long)]
+    pub trust_input: bool,
+
+    /// If the succinct prover is unavailable, execute the guest without
+    /// proving: the journal is printed but no artifacts are written
+    #[arg(long, conflicts_with = "shard_size")]
+    pub allow_fallback: bool,
+}
+
+/// Summary of one successfully proven player
+pub struct ProvenPlayer {
+    pub output: JournalOutput,
+    pub proof_bytes: usize,
+    pub elapsed: Duration,
+    /// False when `--allow-fallback` only executed the guest
+    pub proved: bool,
+}
+
+pub fn run(args: &ProveArgs) -> Result<(), HostError> {
+    let split_input = match (&args.header, &args.records) {
+        (Some(header), Some(records)) => Some(apply_overrides(
+            args,
+            input::load_split_input(
+                header,
+                records,
+                args.input_format,
+                args.strict,
+                args.economy.map(EconomyPreset::economy).as_ref(),
+            )?,
+        )),
+        _ => None,
+    };
+
+    if let Some(format) = args.explain {
+        if let Some(input) = &split_input {
+            return explain::print(input, format);
+        }
+        for input_path in &args.inputs {
+            explain::print(&load_input(args, input_path)?, format)?;
+        }
+        return Ok(());
+    }
+    if args.dump_cycles_by_segment {
+        if let Some(input) = &split_input {
+            return cycles::print(input);
+        }
+        for input_path in &args.inputs {
+            cycles::print(&load_input(args, input_path)?)?;
+        }
+        return Ok(());
+    }
+
+    let image_id = match args.shard_size {
+        Some(_) => Digest::from(GRIDZERO_AGGREGATOR_ID),
+        None => Digest::from(GRIDZERO_GUEST_ID),
+    };
+    std::fs::create_dir_all(&args.output_dir).map_err(HostError::io(&args.output_dir))?;
+    on_thread_pool(args.threads, || prove_inputs(args, split_input, image_id))
+}
+
+/// Run `f` on a rayon pool of `threads` threads, or on the global pool
+/// when unset. The prover parallelizes on whichever pool it runs in, and
+/// a scoped pool, unlike the global one, can be sized again by a later
+/// call in the same process.
+fn on_thread_pool<T: Send>(
+    threads: Option<NonZeroUsize>,
+    f: impl FnOnce() -> Result<T, HostError> + Send,
+) -> Result<T, HostError> {
+    let Some(threads) = threads else {
+        return f();
+    };
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads.get())
+        .build()
+        .map_err(|e| HostError::ProvingFailed { message: format!("thread pool: {}", e) })?;
+    pool.install(f)
+}
+
+fn prove_inputs(
+    args: &ProveArgs,
+    split_input: Option<LeaderboardInput>,
+    image_id: Digest,
+) -> Result<(), HostError> {
+    println!("🎮 GridZero Leaderboard Proof Generator");
+    let input = match split_input {
+        Some(input) => input,
+        None => {
+            // A shard filter only makes sense as a batch, even over one input
+            let ([input_path], None) = (args.inputs.as_slice(), args.shard) else {
+                if args.dump_guest_input.is_some() {
+                    eprintln!("⚠️  --dump-guest-input needs a single input; ignored for the batch");
+                }
+                return batch::run(args, image_id);
+            };
+            load_input(args, input_path)?
+        }
+    };
+    if let Some(path) = &args.dump_guest_input {
+        let bytes = input::guest_input_bytes(&input);
+        artifacts::write_bytes(path, &bytes)?;
+        eprintln!("💾 Guest input ({} bytes) written to {}", bytes.len(), path.display());
+    }
+    let paths = ArtifactPaths::single(&args.output_dir).with_receipt_format(args.receipt_format);
+    let proven = prove_player(args, &input, &paths, image_id)?;
+    if !proven.proved {
+        println!("\n⚠️  Executed only: no proof or artifacts were written");
+        return Ok(());
+    }
+    let image_id_hex = write_image_id(args, image_id)?;
+
+    println!("\n✅ Proof generated!");
+    println!("  Proof: {} ({} bytes)", paths.proof.display(), proven.proof_bytes);
+    println!("  Image ID: {}", image_id_hex);
+    println!("\nNext: Submit to zkVerify using zkverifyjs");
+    Ok(())
+}
+
+/// Load an input and apply the command-line overrides to it
+pub fn load_input(args: &ProveArgs, path: &Path) -> Result<LeaderboardInput, HostError> {
+    let input = match args.economy {
+        Some(preset) => {
+            input::load_input_with_preset(path, args.input_format, args.strict, &preset.economy())?
+        }
+        None => input::load_input(path, args.input_format, args.strict)?,
+    };
+    Ok(apply_overrides(args, input))
+}
+
+fn apply_overrides(args: &ProveArgs, mut input: LeaderboardInput) -> LeaderboardInput {
+    if args.input_cid {
+        input.include_input_cid = true;
+    }
+    if args.allow_zero_address {
+        input.allow_zero_address = true;
+    }
+    let out_of_order = canonicalize::out_of_order_records(&input);
+    if out_of_order > 0 {
+        if args.auto_sort {
+            input = canonicalize::canonical(input);
+            eprintln!("↕️  Sorted records into nonce order ({} were out of order)", out_of_order);
+        } else {
+            eprintln!(
+                "⚠️  {} records are out of nonce order. Sessions, onboarding, comebacks and the \
+                 roots follow nonce order regardless, but input_cid and --explain indices follow \
+                 the given order; pass --auto-sort to sort them",
+                out_of_order
+            );
+        }
+    }
+    if args.dedupe {
+        let dropped = input::dedupe_records(&mut input.mining_history);
+        if dropped > 0 {
+            eprintln!("🧹 Dropped {} duplicate records", dropped);
+        }
+    }
+    if args.trust_input {
+        input.trust_input = true;
+    }
+    if input.trust_input {
+        eprintln!("⚠️  ════════════════════════════════════════════════════════════");
+        eprintln!("⚠️  TRUSTED INPUT: duplicate, map, rarity and cell-count checks are skipped.");
+        eprintln!("⚠️  The proof commits validated: false and proves nothing about whether");
+        eprintln!("⚠️  the history is legitimate. Use it for internal analytics only.");
+        eprintln!("⚠️  ════════════════════════════════════════════════════════════");
+    }
+    input
+}
+
+/// Save the image ID (verification key for zkVerify) unless disabled
+pub fn write_image_id(args: &ProveArgs, image_id: Digest) -> Result<String, HostError> {
+    let image_id_hex = hex::encode(image_id.as_bytes());
+    if !args.no_image_id {
+        artifacts::write_bytes(&args.output_dir.join("image_id.hex"), &image_id_hex)?;
+    }
+    Ok(image_id_hex)
+}
+
+/// Pre-check, prove (or load from cache) and write artifacts for one player
+pub fn prove_player(
+    args: &ProveArgs,
+    input: &LeaderboardInput,
+    paths: &ArtifactPaths,
+    image_id: Digest,
+) -> Result<ProvenPlayer, HostError> {
+    let started = Instant::now();
+    println!("\n  Player: 0x{}", hex::encode(input.player_address));
+    println!("  Mining records: {}", input.mining_history.len());
+    if let Some(preset) = args.economy {
+        let economy = &input.economy;
+        println!("  Economy: {:?} preset, resolved to", preset);
+        println!("    base_scores: {:?}", economy.base_scores);
+        println!("    rare_multipliers: {:?}", economy.rare_multipliers);
+        println!("    rarity_cutoffs: {:?}", economy.rarity_cutoffs);
+        println!("    score_caps: {:?}", economy.score_caps);
+        println!("    economy_hash: {}", economy::economy_hash_hex(economy));
+    }
+    
+    let validation_report = meta::validation_report(input);
+    let failed_checks = validation_report.failures();
+    if !failed_checks.is_empty() {
+        println!("  ⚠️  Host pre-checks failed: {}", failed_checks.join(", "));
+    }
+    
+    // Run the guest's rules natively first so a bad history fails here
+    // with a precise error instead of as a panic mid-proof
+    compute_leaderboard(input)?;
+    
+    // Shard boundaries change the composed fields (see gridzero_core::shard),
+    // so cached single-proof outputs cannot stand in for a sharded proof
+    let cache = match args.shard_size {
+        Some(_) => None,
+        None => args.cache_dir.as_deref().map(ProofCache::new),
+    };
+    let cache_key = ProofCache::key(input, &image_id);
+    let cached = match &cache {
+        Some(cache) => cache.load(&cache_key)?,
+        None => None,
+    };
+
+    let cache_hit = cached.is_some();
+    let (proof_bytes, output) = match cached {
+        Some(hit) => {
+            println!("\n♻️  cache hit ({})", cache_key);
+            hit
+        }
+        None => {
+            let proved = match args.shard_size {
+                Some(shard_size) => prove_sharded(input, shard_size.get(), args.max_cycles),
+                None => prove(input, args.max_cycles),
+            };
+            let (proof_bytes, output) = match proved {
+                Ok(proved) => proved,
+                Err(err) => match fallback_reason(&err, args.allow_fallback) {
+                    Some(reason) => return execute_only(input, reason, started),
+                    None => return Err(err),
+                },
+            };
+            if let Some(cache) = &cache {
+                cache.store(&cache_key, &proof_bytes, &output)?;
+            }
+            (proof_bytes, output)
+        }
+    };
+    
+    journal::warn_on_core_version(&output);
+    print_stats(&output);
+    if args.dump_journal {
+        let receipt = decode_receipt(&proof_bytes)?;
+        journal::dump(&receipt.journal, args.shard_size.is_some())?;
+    }
+    check_size_budgets(args, &proof_bytes)?;
+    
+    // Save proof artifacts
+    let meta = Meta {
+        image_id: hex::encode(image_id.as_bytes()),
+        proof_bytes: proof_bytes.len(),
+        cache_hit,
+        validation_report,
+        tags: meta::tags(&args.tags),
+        build: args.build_info.then(BuildInfo::current),
+    };
+    write_artifacts(paths, &proof_bytes, args.receipt_format, !args.no_hex, &output, &meta)?;
+    
+    let proven = ProvenPlayer {
+        output,
+        proof_bytes: proof_bytes.len(),
+        elapsed: started.elapsed(),
+        proved: true,
+    };
+    if let Some(csv) = &args.csv {
+        csv_log::append_row(csv, &proven.output, proven.proof_bytes, proven.elapsed)?;
+    }
+    Ok(proven)
+}
+
+/// Write one proof's receipt (in `format`), its hex copy if `write_hex`,
+/// its output and its meta to `paths`
+pub(crate) fn write_artifacts(
+    paths: &ArtifactPaths,
+    proof_bytes: &[u8],
+    format: ReceiptFormat,
+    write_hex: bool,
+    output: &JournalOutput,
+    meta: &Meta,
+) -> Result<(), HostError> {
+    artifacts::write_bytes(&paths.proof, artifacts::encode_receipt(proof_bytes, format)?)?;
+    if write_hex {
+        artifacts::write_bytes(&paths.proof_hex, hex::encode(proof_bytes))?;
+    }
+    artifacts::write_json(&paths.output, output)?;
+    artifacts::write_json(&paths.meta, meta)
+}
+
+/// With `--allow-fallback`, why a failed proof should give way to
+/// executing only: the prover could not be set up. A prover that ran
+/// and failed still fails the proof.
+fn fallback_reason(err: &HostError, allow_fallback: bool) -> Option<&str> {
+    match err {
+        HostError::ProverUnavailable { message } if allow_fallback => Some(message),
+        _ => None,
+    }
+}
+
+/// `--allow-fallback`: run the guest in the executor alone and report its
+/// journal, with no receipt to write
+fn execute_only(
+    input: &LeaderboardInput,
+    prover_error: &str,
+    started: Instant,
+) -> Result<ProvenPlayer, HostError> {
+    eprintln!("\n⚠️  ════════════════════════════════════════════════════════════");
+    eprintln!("⚠️  PROVER UNAVAILABLE ({}): executing without proving.", prover_error);
+    eprintln!("⚠️  The output below is NOT proven; no proof will be written.");
+    eprintln!("⚠️  ════════════════════════════════════════════════════════════");
+    check_guest_limits(input)?;
+    let env = ExecutorEnv::builder().write(input).unwrap().build().unwrap();
+    let session = execute(env, GRIDZERO_GUEST_ELF)?;
+    let journal = session.journal.as_ref().ok_or_else(|| HostError::InvalidProof {
+        message: "guest committed no journal".to_string(),
+    })?;
+    let output = journal::decode(journal)?;
+    print_stats(&output);
+    Ok(ProvenPlayer {
+        output,
+        proof_bytes: 0,
+        elapsed: started.elapsed(),
+        proved: false,
+    })
+}
+
+/// Enforce `--max-proof-bytes` and `--max-journal-bytes`, printing the
+/// actual sizes against each budget set
+fn check_size_budgets(args: &ProveArgs, proof_bytes: &[u8]) -> Result<(), HostError> {
+    if args.max_proof_bytes.is_none() && args.max_journal_bytes.is_none() {
+        return Ok(());
+    }
+    let journal_bytes = decode_receipt(proof_bytes)?.journal.bytes.len();
+    let sizes = [
+        ("proof", proof_bytes.len(), args.max_proof_bytes),
+        ("journal", journal_bytes, args.max_journal_bytes),
+    ];
+    for (what, actual, budget) in sizes {
+        let Some(budget) = budget else { continue };
+        println!("  Size: {} {} / {} bytes", what, actual, budget);
+        if actual > budget {
+            return Err(HostError::OverBudget { what, actual, budget });
+        }
+    }
+    Ok(())
+}
+
+/// Print the headline stats of a verified journal
+fn print_stats(output: &JournalOutput) {
+    println!("\n📊 Verified Leaderboard Stats:");
+    let output = match output {
+        JournalOutput::Full(output) => output,
+        JournalOutput::Minimal(output) => {
+            println!("  Score: {}", output.score);
+            println!("  History root: 0x{}", hex::encode(output.history_root));
+            println!("  (minimal journal: inventories not committed)");
+            return;
+        }
+    };
+    if !output.validated {
+        println!("  ⚠️  UNVALIDATED: proven with trust_input; per-record checks were skipped");
+    }
+    println!("  Total mined: {}", output.total_mined);
+    if output.score_hidden {
+        println!("  Score: hidden");
+    } else {
+        println!("  Score: {}", output.score);
+    }
+    if !output.tier_thresholds.is_empty() {
+        println!(
+            "  Tier: {} of {} (thresholds {:?})",
+            output.tier,
+            output.tier_thresholds.len(),
+            output.tier_thresholds
+        );
+    }
+    if let Some(checkpoint) = &output.checkpoint {
+        println!(
+            "  Score delta: {} since the checkpoint at {} mines",
+            output.score_delta, checkpoint.total_mined
+        );
+    }
+    println!("  Unique cells: {}", output.unique_cells);
+    println!("  Ore inventory: {:?}", output.ore_inventory);
+    println!("  Rare inventory: {:?}", output.rare_inventory);
+    println!(
+        "  Longest session: {} mines over {}s",
+        output.longest_session_len, output.longest_session_duration
+    );
+    println!("  Provenance root: 0x{}", hex::encode(output.provenance_root));
+    if output.input_cid != [0u8; 32] {
+        println!("  Input CID: 0x{}", hex::encode(output.input_cid));
+    }
+    if let Some(lease) = output.lease_range {
+        println!(
+            "  Lease: nonces {}..={} (token 0x{})",
+            lease.start,
+            lease.end,
+            hex::encode(output.lease_token)
+        );
+    }
+    for (member, address) in output.party_addresses.iter().enumerate() {
+        println!(
+            "  Party member {}: 0x{} ({} points over {} cells)",
+            member,
+            hex::encode(address),
+            output.member_scores[member],
+            output.member_cells[member]
+        );
+    }
+    if output.onboarding_bonus > 0 {
+        println!(
+            "  Onboarding: +{} for the first {} records",
+            output.onboarding_bonus, output.onboarding_count
+        );
+    }
+    if output.comeback_bonus_total > 0 {
+        println!(
+            "  Comeback: +{} for each return after {}s away, {} in total",
+            output.comeback_bonus, output.comeback_gap, output.comeback_bonus_total
+        );
+    }
+}
+
+/// Run the guest over `input` and return the serialized receipt and
+/// its decoded journal
+pub(crate) fn prove(
+    input: &LeaderboardInput,
+    max_cycles: Option<u64>,
+) -> Result<(Vec<u8>, JournalOutput), HostError> {
+    check_guest_limits(input)?;
+
+    // Build executor environment with input
+    let env = ExecutorEnv::builder()
+        .write(input)
+        .unwrap()
+        .build()
+        .unwrap();
+    
+    // Generate proof
+    println!("\n⚙️  Generating RISC Zero proof...");
+    let receipt = prove_elf(env, GRIDZERO_GUEST_ELF, max_cycles)?;
+    
+    // Extract public output
+    let output = journal::decode(&receipt.journal)?;
+    
+    // Serialize proof for zkVerify submission
+    let proof_bytes = serialize_receipt(&receipt)?;
+    Ok((proof_bytes, output))
+}
+
+/// Fail with `HostError::OverCycleBudget` if an execution of `cycles`
+/// total cycles is over `max_cycles`
+fn check_cycle_budget(cycles: u64, max_cycles: Option<u64>) -> Result<(), HostError> {
+    match max_cycles {
+        Some(budget) if cycles > budget => Err(HostError::OverCycleBudget { cycles, budget }),
+        _ => Ok(()),
+    }
+}
+
+/// The guest's input size and record limits, checked on the host so an
+/// oversized input fails before the zkVM starts
+fn check_guest_limits(input: &LeaderboardInput) -> Result<(), HostError> {
+    score::check_input_size(journal::words(input).len() * 4)?;
+    score::check_record_count(input.mining_history.len())?;
+    Ok(())
+}
+
+/// Prove `input` shard by shard, then run the aggregator guest over the
+/// shard receipts; returns the aggregate receipt and its composed output
+fn prove_sharded(
+    input: &LeaderboardInput,
+    shard_size: usize,
+    max_cycles: Option<u64>,
+) -> Result<(Vec<u8>, JournalOutput), HostError> {
+    check_shardable(input)?;
+    let shards = split_into_shards(input, shard_size);
+    let mut shard_outputs: Vec<ShardOutput> = Vec::with_capacity(shards.len());
+    let mut aggregate_env = ExecutorEnv::builder();
+
+    for (i, shard) in shards.iter().enumerate() {
+        println!(
+            "\n⚙️  Generating shard proof {}/{} ({} records)...",
+            i + 1,
+            shards.len(),
+            shard.mining_history.len()
+        );
+        check_guest_limits(shard)?;
+        let env = ExecutorEnv::builder()
+            .write(shard)
+            .unwrap()
+            .build()
+            .unwrap();
+        let receipt = prove_elf(env, GRIDZERO_GUEST_ELF, max_cycles)?;
+        shard_outputs.push(decode_journal_as(&receipt, "ShardOutput")?);
+        aggregate_env.add_assumption(receipt);
+    }
+
+    let aggregate_input = aggregate_input(input, GRIDZERO_GUEST_ID, shard_outputs);
+    let env = aggregate_env
+        .write(&aggregate_input)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    println!("\n⚙️  Aggregating {} shard proofs...", shards.len());
+    let receipt = prove_elf(env, GRIDZERO_AGGREGATOR_ELF, max_cycles)?;
+
+    let aggregate: AggregateOutput = decode_journal_as(&receipt, "AggregateOutput")?;
+    let proof_bytes = serialize_receipt(&receipt)?;
+    Ok((proof_bytes, JournalOutput::Full(Box::new(aggregate.output))))
+}
+
+/// Decode a fresh receipt's journal as the `T` its guest commits, named
+/// `type_name` in the error
+fn decode_journal_as<T: serde::de::DeserializeOwned>(
+    receipt: &Receipt,
+    type_name: &str,
+) -> Result<T, HostError> {
+    receipt.journal.decode().map_err(|e| HostError::InvalidProof {
+        message: format!("journal does not decode as {}: {}", type_name, e),
+    })
+}
+
+/// A receipt's bincode encoding, as written to `proof.bin`
+fn serialize_receipt(receipt: &Receipt) -> Result<Vec<u8>, HostError> {
+    bincode::serialize(receipt).map_err(|e| HostError::ProvingFailed {
+        message: format!("receipt does not serialize: {}", e),
+    })
+}
+
+/// Execute `elf` once, report its cycle count, then prove the recorded
+/// session directly. Proving through `Prover::prove` would execute the
+/// guest a second time just to regenerate the same session. A prover
+/// that cannot be set up is `HostError::ProverUnavailable`, the only
+/// error `--allow-fallback` falls back on; one that fails on the session
+/// is `HostError::ProvingFailed`. A session over `max_cycles` is
+/// `HostError::OverCycleBudget`, before any proving starts.
+pub(crate) fn prove_elf(
+    env: ExecutorEnv<'_>,
+    elf: &[u8],
+    max_cycles: Option<u64>,
+) -> Result<Receipt, HostError> {
+    let started = Instant::now();
+    let session = execute(env, elf)?;
+    let executed_in = started.elapsed();
+    println!(
+        "  Executed: {} cycles ({} user) in {} segments, {:.2?}",
+        session.total_cycles,
+        session.user_cycles,
+        session.segments.len(),
+        executed_in
+    );
+    check_cycle_budget(session.total_cycles, max_cycles)?;
+
+    let receipt = get_prover_server(&ProverOpts::succinct())
+        .map_err(|e| HostError::ProverUnavailable {
+            message: e.to_string(),
+        })?
+        .prove_session(&VerifierContext::default(), &session)
+        .map_err(|e| HostError::ProvingFailed {
+            message: e.to_string(),
+        })?
+        .receipt;
+    if session.journal.as_ref().map(|journal| &journal.bytes) != Some(&receipt.journal.bytes) {
+        return Err(HostError::ProvingFailed {
+            message: "proof journal differs from the executed session's".to_string(),
+        });
+    }
+    println!(
+        "  Proved in {:.2?}, reusing the executed session ({:.2?} of re-execution saved)",
+        started.elapsed() - executed_in,
+        executed_in
+    );
+    Ok(receipt)
+}
+
+/// Run `elf` in the executor; a guest that fails to load or panics (a
+/// record the host's checks let through, say) is `HostError::GuestFailed`
+pub(crate) fn execute(env: ExecutorEnv<'_>, elf: &[u8]) -> Result<Session, HostError> {
+    ExecutorImpl::from_elf(env, elf)
+        .and_then(|mut executor| executor.run())
+        .map_err(|e| HostError::GuestFailed {
+            message: e.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::canonical_output;
+    use crate::testing::{fake_receipt, prove_args, record, sample_input, temp_dir, try_prove_args};
+    use gridzero_core::ValidationError;
+
+    #[test]
+    fn an_oversized_proof_is_over_budget() {
+        let output = JournalOutput::Full(Box::new(canonical_output()));
+        let proof_bytes = fake_receipt(GRIDZERO_GUEST_ID, &output);
+        let proof_len = proof_bytes.len();
+        let journal_len = decode_receipt(&proof_bytes).unwrap().journal.bytes.len();
+        let budget = |flag: &str, budget: usize| {
+            check_size_budgets(&prove_args(&[flag, &budget.to_string()]), &proof_bytes)
+        };
+
+        assert!(check_size_budgets(&prove_args(&[]), &proof_bytes).is_ok());
+        assert!(budget("--max-proof-bytes", proof_len).is_ok());
+        assert!(budget("--max-journal-bytes", journal_len).is_ok());
+        let err = budget("--max-proof-bytes", proof_len - 1).unwrap_err();
+        let HostError::OverBudget { what, actual, budget: limit } = err else {
+            panic!("expected an over-budget error, got {:?}", err);
+        };
+        assert_eq!((what, actual, limit), ("proof", proof_len, proof_len - 1));
+        let err = budget("--max-journal-bytes", journal_len - 1).unwrap_err();
+        assert!(matches!(err, HostError::OverBudget { what: "journal", .. }), "{:?}", err);
+    }
+
+    #[test]
+    fn only_an_unavailable_prover_falls_back() {
+        let unavailable = HostError::ProverUnavailable {
+            message: "no succinct prover".to_string(),
+        };
+        assert_eq!(fallback_reason(&unavailable, true), Some("no succinct prover"));
+        assert_eq!(fallback_reason(&unavailable, false), None);
+
+        let failures = [
+            HostError::ProvingFailed {
+                message: "bad session".to_string(),
+            },
+            HostError::GuestFailed {
+                message: "guest panicked".to_string(),
+            },
+        ];
+        for failure in &failures {
+            assert_eq!(fallback_reason(failure, true), None, "{:?}", failure);
+        }
+    }
+
+    #[test]
+    fn the_zero_address_needs_the_flag() {
+        let zero = LeaderboardInput {
+            player_address: [0; 20],
+            ..sample_input()
+        };
+        let by_default = apply_overrides(&prove_args(&[]), zero.clone());
+        assert_eq!(compute_leaderboard(&by_default), Err(ValidationError::ZeroAddress));
+
+        let allowed = apply_overrides(&prove_args(&["--allow-zero-address"]), zero);
+        assert_eq!(compute_leaderboard(&allowed).unwrap().player_address, [0; 20]);
+    }
+
+    #[test]
+    fn only_executions_over_the_budget_fail() {
+        assert!(check_cycle_budget(u64::MAX, None).is_ok());
+        assert!(check_cycle_budget(1 << 20, Some(1 << 20)).is_ok());
+        let err = check_cycle_budget((1 << 20) + 1, Some(1 << 20)).unwrap_err();
+        assert!(matches!(err, HostError::OverCycleBudget { cycles, budget: 1_048_576 } if cycles == 1_048_577));
+        assert!(err.to_string().contains("1048577"), "{}", err);
+    }
+
+    /// Executes the built guest: run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn a_large_history_trips_a_budget_a_small_one_fits() {
+        let cycles = |records: u64| {
+            let input = LeaderboardInput {
+                mining_history: (0..records)
+                    .map(|nonce| record((nonce % 32) as u8, (nonce / 32) as u8, 0, nonce))
+                    .collect(),
+                ..sample_input()
+            };
+            let env = ExecutorEnv::builder().write(&input).unwrap().build().unwrap();
+            execute(env, GRIDZERO_GUEST_ELF).unwrap().total_cycles
+        };
+        let budget = cycles(8);
+        assert!(check_cycle_budget(budget, Some(budget)).is_ok());
+        assert!(matches!(
+            check_cycle_budget(cycles(1000), Some(budget)),
+            Err(HostError::OverCycleBudget { .. })
+        ));
+    }
+
+    #[test]
+    fn auto_sort_canonicalizes_the_input() {
+        let mut shuffled = sample_input();
+        shuffled.mining_history.reverse();
+        assert!(canonicalize::out_of_order_records(&shuffled) > 0);
+
+        let sorted = apply_overrides(&prove_args(&["--auto-sort"]), shuffled.clone());
+        assert_eq!(canonicalize::out_of_order_records(&sorted), 0);
+        assert_eq!(
+            canonicalize::to_canonical_json(&sorted),
+            canonicalize::to_canonical_json(&canonicalize::canonical(shuffled.clone()))
+        );
+        // Without the flag the order is only warned about
+        assert_eq!(apply_overrides(&prove_args(&[]), shuffled.clone()), shuffled);
+    }
+
+    #[test]
+    fn an_oversized_input_fails_before_the_guest_runs() {
+        assert!(check_guest_limits(&sample_input()).is_ok());
+
+        let oversized = LeaderboardInput {
+            map_layout: vec![0; gridzero_core::MAX_INPUT_BYTES],
+            ..sample_input()
+        };
+        let err = check_guest_limits(&oversized).unwrap_err();
+        let HostError::Validation(ValidationError::InputTooLarge { bytes }) = err else {
+            panic!("expected an oversized input, got {:?}", err);
+        };
+        assert!(bytes > gridzero_core::MAX_INPUT_BYTES);
+        assert_eq!(
+            err.to_string(),
+            format!("invalid mining history: input exceeds 16777216 bytes ({} given)", bytes)
+        );
+    }
+
+    /// Executes the built guest: run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn trusted_inputs_execute_in_fewer_cycles() {
+        let history: Vec<_> =
+            (0..1000u64).map(|nonce| record((nonce % 32) as u8, (nonce / 32) as u8, 0, nonce)).collect();
+        let cycles = |trust_input| {
+            let input = LeaderboardInput {
+                mining_history: history.clone(),
+                trust_input,
+                ..sample_input()
+            };
+            let env = ExecutorEnv::builder().write(&input).unwrap().build().unwrap();
+            execute(env, GRIDZERO_GUEST_ELF).unwrap().user_cycles
+        };
+        let (validated, trusted) = (cycles(false), cycles(true));
+        assert!(trusted < validated, "{} trusted cycles against {} validated", trusted, validated);
+    }
+
+    /// A host-normalized input (sorted, deduplicated) as `--dump-guest-input` saves it
+    fn dumped_input() -> (LeaderboardInput, Vec<u8>) {
+        let mut raw = sample_input();
+        raw.mining_history.reverse();
+        raw.mining_history.push(raw.mining_history[0].clone());
+        let input = apply_overrides(&prove_args(&["--auto-sort", "--dedupe"]), raw);
+        let bytes = input::guest_input_bytes(&input);
+        (input, bytes)
+    }
+
+    #[test]
+    fn a_dumped_guest_input_loads_as_the_proven_input() {
+        let (input, bytes) = dumped_input();
+        let reloaded = input::parse_input(&bytes, InputFormat::Guest).unwrap();
+        assert_eq!(reloaded, input);
+        let journal = |input: &LeaderboardInput| {
+            journal::words(&JournalOutput::new(compute_leaderboard(input).unwrap(), false))
+        };
+        assert_eq!(journal(&reloaded), journal(&input));
+    }
+
+    /// Executes the built guest: run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn re_proving_a_dumped_input_commits_the_same_journal() {
+        let (input, bytes) = dumped_input();
+        let reloaded = input::parse_input(&bytes, InputFormat::Guest).unwrap();
+        let journal = |input: &LeaderboardInput| {
+            let env = ExecutorEnv::builder().write(input).unwrap().build().unwrap();
+            execute(env, GRIDZERO_GUEST_ELF).unwrap().journal.unwrap().bytes
+        };
+        assert_eq!(journal(&reloaded), journal(&input));
+    }
+
+    #[test]
+    fn no_image_id_skips_only_the_image_id_file() {
+        let dir = temp_dir("no-image-id");
+        let output_dir = dir.to_str().unwrap();
+        let image_id = Digest::from(GRIDZERO_GUEST_ID);
+
+        let skipped = write_image_id(&prove_args(&["--output-dir", output_dir, "--no-image-id"]), image_id);
+        assert_eq!(skipped.unwrap(), hex::encode(image_id.as_bytes()));
+        assert!(!dir.join("image_id.hex").exists());
+
+        write_image_id(&prove_args(&["--output-dir", output_dir]), image_id).unwrap();
+        let written = std::fs::read_to_string(dir.join("image_id.hex")).unwrap();
+        assert_eq!(written, hex::encode(image_id.as_bytes()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn zero_threads_is_rejected() {
+        assert!(try_prove_args(&["--threads", "0"]).is_err());
+        assert_eq!(prove_args(&["--threads", "1"]).threads, NonZeroUsize::new(1));
+    }
+
+    #[test]
+    fn the_thread_pool_can_be_sized_more_than_once() {
+        for threads in [1, 2] {
+            let used = on_thread_pool(NonZeroUsize::new(threads), || Ok(rayon::current_num_threads()));
+            assert_eq!(used.unwrap(), threads);
+        }
+    }
+
+    /// Proves with the built guest: run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn one_thread_proves_the_same_as_many() {
+        let input = sample_input();
+        let prove_on = |threads| on_thread_pool(NonZeroUsize::new(threads), || prove(&input, None)).unwrap();
+        let (one_bytes, one_output) = prove_on(1);
+        let (many_bytes, many_output) = prove_on(4);
+        assert_eq!(one_output, many_output);
+        let (one, many) = (decode_receipt(&one_bytes).unwrap(), decode_receipt(&many_bytes).unwrap());
+        assert_eq!(one.journal.bytes, many.journal.bytes);
+        assert_eq!(one.inner.succinct().unwrap().seal, many.inner.succinct().unwrap().seal);
+    }
+
+    #[test]
+    fn an_undecodable_journal_is_an_error() {
+        let mut output = canonical_output();
+        output.version = gridzero_core::JOURNAL_VERSION + 1;
+        let proof_bytes = fake_receipt(GRIDZERO_GUEST_ID, &JournalOutput::Full(Box::new(output)));
+        let receipt = decode_receipt(&proof_bytes).unwrap();
+        assert!(matches!(
+            journal::decode(&receipt.journal),
+            Err(HostError::UnsupportedJournalVersion { .. })
+        ));
+
+        let truncated = Receipt::new(receipt.inner.clone(), receipt.journal.bytes[..8].to_vec());
+        let err = decode_journal_as::<AggregateOutput>(&truncated, "AggregateOutput").unwrap_err();
+        let HostError::InvalidProof { message } = &err else {
+            panic!("expected an invalid proof, got {:?}", err);
+        };
+        assert!(message.starts_with("journal does not decode as AggregateOutput"), "{}", message);
+    }
+}
@@ -0,0 +1,133 @@
+//! `gridzero-host replay`: audit saved artifacts against their input
+//!
+//! Recomputes the output natively from an input (as `compute` does),
+//! then checks a saved `output.json` and the journal of a saved proof
+//! (verified as `verify` does) against it, listing each field that
+//! differs. Either artifact may be left out, but not both. A proof that
+//! does not verify exits with 2 and any field mismatch with 3, as in
+//! `verify --expect-output`. Inputs that name a shard are not supported,
+//! since their proofs commit a `ShardOutput`. Prove-time overrides such
+//! as `--input-cid` are not replayed; an input proven with one flags the
+//! fields it changed.
+
+use clap::Args;
+use gridzero_core::{compute_leaderboard, JournalOutput};
+use risc0_zkvm::sha::Digest;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::HostError;
+use crate::image_id::parse_image_id;
+use crate::input::{self, InputFormat};
+use crate::verify;
+use gridzero_methods::GRIDZERO_GUEST_ID;
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// Input the artifacts were produced from; `-` reads stdin
+    input: PathBuf,
+
+    /// Encoding of the input file
+    #[arg(long, value_enum, default_value_t = InputFormat::Json)]
+    input_format: InputFormat,
+
+    /// Saved output.json to check
+    #[arg(long, required_unless_present = "proof")]
+    output: Option<PathBuf>,
+
+    /// Saved proof to verify and check (proof.bin or proof.json)
+    #[arg(long)]
+    proof: Option<PathBuf>,
+
+    /// Image ID to verify the proof against, as hex or a path to
+    /// image_id.hex (defaults to the built guest)
+    #[arg(long, requires = "proof")]
+    image_id: Option<String>,
+}
+
+pub fn run(args: &ReplayArgs) -> Result<(), HostError> {
+    let input = input::load_input(&args.input, args.input_format, false)?;
+    let expected = JournalOutput::new(compute_leaderboard(&input)?, input.minimal_journal);
+    println!("🔁 Replaying {}", args.input.display());
+
+    let mut mismatches = Vec::new();
+    if let Some(path) = &args.output {
+        let json = fs::read(path).map_err(HostError::io(path))?;
+        let saved: JournalOutput = serde_json::from_slice(&json).map_err(|e| HostError::Parse {
+            format: InputFormat::Json,
+            message: format!("{}: {}", path.display(), e),
+        })?;
+        report("output.json", &expected, &saved, &mut mismatches);
+    }
+    if let Some(path) = &args.proof {
+        let image_id = match &args.image_id {
+            Some(arg) => parse_image_id(arg)?,
+            None => Digest::from(GRIDZERO_GUEST_ID),
+        };
+        let proof_bytes = fs::read(path).map_err(HostError::io(path))?;
+        let journal = verify::verify_receipt(&proof_bytes, image_id)?;
+        println!("  ✅ proof verifies against {}", hex::encode(image_id.as_bytes()));
+        report("journal", &expected, &journal, &mut mismatches);
+    }
+
+    if !mismatches.is_empty() {
+        return Err(HostError::OutputMismatch { fields: mismatches });
+    }
+    println!("\n✅ Artifacts agree with the input");
+    Ok(())
+}
+
+/// Print whether `artifact` equals the native output, collecting each
+/// differing field as `<artifact>.<field>`
+fn report(
+    artifact: &str,
+    expected: &JournalOutput,
+    actual: &JournalOutput,
+    mismatches: &mut Vec<String>,
+) {
+    let fields = verify::differing_fields(expected, actual);
+    if fields.is_empty() {
+        println!("  ✅ {} matches the native output", artifact);
+        return;
+    }
+    println!("  ❌ {} differs from the native output in:", artifact);
+    for field in fields {
+        println!("     {}", field);
+        mismatches.push(format!("{}.{}", artifact, field));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifacts;
+    use crate::testing::{sample_input, temp_dir};
+
+    #[test]
+    fn replay_flags_a_tampered_output() {
+        let dir = temp_dir("replay");
+        let input = sample_input();
+        let args = ReplayArgs {
+            input: dir.join("input.json"),
+            input_format: InputFormat::Json,
+            output: Some(dir.join("output.json")),
+            proof: None,
+            image_id: None,
+        };
+        artifacts::write_json(&args.input, &input).unwrap();
+        let mut output = compute_leaderboard(&input).unwrap();
+        artifacts::write_json(&dir.join("output.json"), &JournalOutput::new(output.clone(), false)).unwrap();
+        run(&args).unwrap();
+
+        output.score += 100;
+        output.unique_cells -= 1;
+        artifacts::write_json(&dir.join("output.json"), &JournalOutput::new(output, false)).unwrap();
+        let err = run(&args).unwrap_err();
+        let HostError::OutputMismatch { fields } = &err else {
+            panic!("expected an output mismatch, got {:?}", err);
+        };
+        assert_eq!(fields, &["output.json.score", "output.json.unique_cells"]);
+        assert_eq!(err.exit_code(), 3);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+//! Proof server
+//!
+//! `gridzero-host serve --addr 127.0.0.1:8080` (built with the `server`
+//! feature) runs the prover as a long-lived HTTP service:
+//!
+//! - `POST /prove` takes a `LeaderboardInput` as JSON and answers with
+//!   `{"proof": <hex receipt>, "output": <journal>}`, the same bytes and
+//!   output `prove_leaderboard` returns.
+//! - `GET /healthz` answers `ok` while the server is accepting requests.
+//!
+//! At most `--max-concurrent` proofs run at once; a request beyond that
+//! is turned away with 429 rather than queued, so a burst cannot pile up
+//! unbounded work. Bodies over `--max-body-bytes` get 413. Failures use
+//! the same JSON shape as `--error-format json`.
+
+use clap::Args;
+use gridzero_core::LeaderboardInput;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::error::HostError;
+use crate::input::InputFormat;
+use crate::prove_leaderboard;
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Socket address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: String,
+
+    /// Proofs allowed to run at the same time
+    #[arg(long, default_value_t = 1)]
+    max_concurrent: usize,
+
+    /// Largest request body accepted, in bytes
+    #[arg(long, default_value_t = 16 * 1024 * 1024)]
+    max_body_bytes: usize,
+}
+
+/// Counting semaphore over in-flight proofs that refuses rather than waits
+struct ProveSlots {
+    in_use: AtomicUsize,
+    limit: usize,
+}
+
+/// One acquired slot, released on drop (including when a prove panics)
+struct Slot(Arc<ProveSlots>);
+
+impl ProveSlots {
+    fn try_acquire(slots: &Arc<ProveSlots>) -> Option<Slot> {
+        slots
+            .in_use
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |in_use| {
+                (in_use < slots.limit).then_some(in_use + 1)
+            })
+            .ok()
+            .map(|_| Slot(Arc::clone(slots)))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.in_use.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub fn run(args: &ServeArgs) -> Result<(), HostError> {
+    let server = Server::http(&args.addr).map_err(|e| HostError::Listen {
+        addr: args.addr.clone(),
+        message: e.to_string(),
+    })?;
+    let slots = Arc::new(ProveSlots {
+        in_use: AtomicUsize::new(0),
+        limit: args.max_concurrent.max(1),
+    });
+    println!("🛰️  Serving proofs on http://{} (max {} at once)", args.addr, slots.limit);
+
+    for request in server.incoming_requests() {
+        let slots = Arc::clone(&slots);
+        let max_body_bytes = args.max_body_bytes;
+        thread::spawn(move || handle(request, &slots, max_body_bytes));
+    }
+    Ok(())
+}
+
+fn handle(mut request: Request, slots: &Arc<ProveSlots>, max_body_bytes: usize) {
+    let (status, body) = match (request.method(), request.url()) {
+        (Method::Get, "/healthz") => (200, json!("ok")),
+        (Method::Post, "/prove") => match read_body(&mut request, max_body_bytes) {
+            Ok(body) => prove_request(&body, slots),
+            Err(response) => response,
+        },
+        _ => (404, json!({ "error": "not found" })),
+    };
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(
+            Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                .expect("static header is valid"),
+        );
+    if let Err(err) = request.respond(response) {
+        eprintln!("⚠️  Could not send response: {}", err);
+    }
+}
+
+/// The request body, or the error response if it is too large or unreadable
+fn read_body(request: &mut Request, max_body_bytes: usize) -> Result<Vec<u8>, (u16, Value)> {
+    let too_large = || (413, json!({ "error": format!("body exceeds {} bytes", max_body_bytes) }));
+    if request.body_length().is_some_and(|length| length > max_body_bytes) {
+        return Err(too_large());
+    }
+    // Read one byte past the limit to catch bodies without a length
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(max_body_bytes as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| (400, json!({ "error": e.to_string() })))?;
+    if body.len() > max_body_bytes {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+fn prove_request(body: &[u8], slots: &Arc<ProveSlots>) -> (u16, Value) {
+    let input: LeaderboardInput = match serde_json::from_slice(body) {
+        Ok(input) => input,
+        Err(e) => {
+            let err = HostError::Parse {
+                format: InputFormat::Json,
+                message: e.to_string(),
+            };
+            return (400, err.to_json());
+        }
+    };
+    let Some(_slot) = ProveSlots::try_acquire(slots) else {
+        return (429, json!({ "error": "all proving slots are busy; retry later" }));
+    };
+
+    println!("⚙️  Proving 0x{}", hex::encode(input.player_address));
+    // The prover panics on internal failures; answer 500 instead of
+    // dropping the connection
+    match panic::catch_unwind(AssertUnwindSafe(|| prove_leaderboard(&input))) {
+        Ok(Ok((proof_bytes, output))) => (
+            200,
+            json!({
+                "proof": hex::encode(&proof_bytes),
+                "output": output,
+            }),
+        ),
+        Ok(Err(err @ HostError::Validation(_))) => (422, err.to_json()),
+        Ok(Err(err @ HostError::ProverUnavailable { .. })) => (503, err.to_json()),
+        Ok(Err(err)) => (500, err.to_json()),
+        Err(_) => (500, json!({ "error": "proving failed" })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_input;
+
+    fn slots(limit: usize) -> Arc<ProveSlots> {
+        Arc::new(ProveSlots {
+            in_use: AtomicUsize::new(0),
+            limit,
+        })
+    }
+
+    #[test]
+    fn slots_refuse_past_the_limit_and_free_on_drop() {
+        let slots = slots(2);
+        let first = ProveSlots::try_acquire(&slots).unwrap();
+        let _second = ProveSlots::try_acquire(&slots).unwrap();
+        assert!(ProveSlots::try_acquire(&slots).is_none());
+        drop(first);
+        assert!(ProveSlots::try_acquire(&slots).is_some());
+    }
+
+    #[test]
+    fn bad_requests_get_their_status() {
+        let (status, body) = prove_request(b"{", &slots(1));
+        assert_eq!((status, body["kind"].as_str()), (400, Some("ParseError")));
+
+        let zero = LeaderboardInput {
+            player_address: [0; 20],
+            ..sample_input()
+        };
+        let body = serde_json::to_vec(&zero).unwrap();
+        let (status, response) = prove_request(&body, &slots(1));
+        assert_eq!((status, response["kind"].as_str()), (422, Some("ValidationError")));
+
+        let busy = slots(1);
+        let _held = ProveSlots::try_acquire(&busy).unwrap();
+        assert_eq!(prove_request(&serde_json::to_vec(&sample_input()).unwrap(), &busy).0, 429);
+    }
+}
@@ -0,0 +1,133 @@
+//! Tamper test (diagnostic)
+//!
+//! `gridzero-host tamper-test <proof.bin> <image_id>` is a living check
+//! on verification itself, not a production feature: it flips one bit
+//! in the journal and, separately, one bit in the seal, requires both
+//! mutants to be rejected, and then requires the untouched proof to
+//! still verify. CI can run it against a fresh proof to catch anything
+//! that quietly weakens verification. Hidden from `--help`.
+
+use clap::Args;
+use risc0_zkvm::{sha::Digest, InnerReceipt, Receipt};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::HostError;
+use crate::image_id::parse_image_id;
+use crate::verify::decode_receipt;
+
+#[derive(Args)]
+pub struct TamperTestArgs {
+    /// Serialized receipt (proof.bin) that verifies against `image_id`
+    proof: PathBuf,
+
+    /// Image ID as hex or a path to image_id.hex
+    image_id: String,
+}
+
+pub fn run(args: &TamperTestArgs) -> Result<(), HostError> {
+    let image_id = parse_image_id(&args.image_id)?;
+    let proof_bytes = fs::read(&args.proof).map_err(HostError::io(&args.proof))?;
+    let receipt = decode_receipt(&proof_bytes)?;
+
+    println!("🧪 Tamper test: {}", args.proof.display());
+
+    let mut journal_mutant = receipt.clone();
+    let journal = &mut journal_mutant.journal.bytes;
+    if journal.is_empty() {
+        return Err(HostError::InvalidProof {
+            message: "journal is empty, nothing to tamper with".to_string(),
+        });
+    }
+    let middle = journal.len() / 2;
+    journal[middle] ^= 1;
+    expect_rejected("journal bit flip", &journal_mutant, image_id)?;
+
+    let mut seal_mutant = receipt.clone();
+    flip_seal_bit(&mut seal_mutant)?;
+    expect_rejected("seal bit flip", &seal_mutant, image_id)?;
+
+    receipt.verify(image_id).map_err(|e| HostError::InvalidProof {
+        message: format!("untouched proof does not verify: {}", e),
+    })?;
+    println!("  ✅ untouched proof: verifies");
+    Ok(())
+}
+
+fn expect_rejected(mutation: &'static str, receipt: &Receipt, image_id: Digest) -> Result<(), HostError> {
+    match receipt.verify(image_id) {
+        Err(err) => {
+            println!("  ✅ {}: rejected ({})", mutation, err);
+            Ok(())
+        }
+        Ok(()) => Err(HostError::TamperUndetected { mutation }),
+    }
+}
+
+/// Flip a bit in the middle of the receipt's seal
+fn flip_seal_bit(receipt: &mut Receipt) -> Result<(), HostError> {
+    let flipped = match &mut receipt.inner {
+        InnerReceipt::Composite(inner) => inner
+            .segments
+            .first_mut()
+            .and_then(|segment| flip_middle(&mut segment.seal)),
+        InnerReceipt::Succinct(inner) => flip_middle(&mut inner.seal),
+        InnerReceipt::Groth16(inner) => {
+            let middle = inner.seal.len() / 2;
+            inner.seal.get_mut(middle).map(|byte| *byte ^= 1)
+        }
+        _ => None,
+    };
+    flipped.ok_or_else(|| HostError::InvalidProof {
+        message: "receipt has no seal to tamper with (fake or empty receipt)".to_string(),
+    })
+}
+
+fn flip_middle(seal: &mut [u32]) -> Option<()> {
+    let middle = seal.len() / 2;
+    seal.get_mut(middle).map(|word| *word ^= 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::canonical_output;
+    use crate::testing::{fake_receipt, sample_input, temp_dir};
+    use gridzero_core::JournalOutput;
+    use gridzero_methods::GRIDZERO_GUEST_ID;
+
+    fn tamper_test(proof_bytes: &[u8], name: &str) -> Result<(), HostError> {
+        let dir = temp_dir(name);
+        let proof = dir.join("proof.bin");
+        fs::write(&proof, proof_bytes).unwrap();
+        let result = run(&TamperTestArgs {
+            proof,
+            image_id: hex::encode(Digest::from(GRIDZERO_GUEST_ID).as_bytes()),
+        });
+        fs::remove_dir_all(dir).unwrap();
+        result
+    }
+
+    #[test]
+    fn seal_bits_flip_in_the_middle() {
+        let mut seal = [0u32; 5];
+        assert_eq!(flip_middle(&mut seal), Some(()));
+        assert_eq!(seal, [0, 0, 1, 0, 0]);
+        assert_eq!(flip_middle(&mut []), None);
+    }
+
+    #[test]
+    fn a_receipt_without_a_seal_cannot_be_tamper_tested() {
+        let proof_bytes = fake_receipt(GRIDZERO_GUEST_ID, &JournalOutput::Full(Box::new(canonical_output())));
+        let err = tamper_test(&proof_bytes, "tamper-fake").unwrap_err();
+        assert!(matches!(err, HostError::InvalidProof { message } if message.contains("no seal")));
+    }
+
+    /// Proves with the built guest: run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn a_real_proof_rejects_both_mutations() {
+        let (proof_bytes, _) = crate::prove::prove(&sample_input(), None).unwrap();
+        tamper_test(&proof_bytes, "tamper-real").unwrap();
+    }
+}
@@ -0,0 +1,71 @@
+//! Inputs for unit tests
+
+use clap::Parser;
+use gridzero_core::{JournalOutput, LeaderboardInput, MiningRecord};
+use risc0_zkvm::{FakeReceipt, InnerReceipt, Receipt, ReceiptClaim};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::journal;
+use crate::prove::ProveArgs;
+
+/// A surface record of `ore_type` at `(x, y)`, authorized at block
+/// `nonce` and mined at second `nonce`
+pub fn record(x: u8, y: u8, ore_type: u8, nonce: u64) -> MiningRecord {
+    MiningRecord {
+        grid_x: x,
+        grid_y: y,
+        ore_type,
+        is_rare: false,
+        random_output: [nonce as u8; 32],
+        nonce,
+        block_height: nonce,
+        tx_hash: [0; 32],
+        timestamp: nonce,
+        grid_z: 0,
+        miner_index: 0,
+    }
+}
+
+/// A solo player's valid input over a few records of different ores
+pub fn sample_input() -> LeaderboardInput {
+    LeaderboardInput {
+        player_address: [0x11; 20],
+        mining_history: vec![record(0, 0, 0, 1), record(1, 0, 3, 2), record(2, 5, 7, 3)],
+        ..LeaderboardInput::default()
+    }
+}
+
+/// A fresh, empty directory for one test under the system temp dir
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("gridzero-host-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Serialized receipt of `image_id` committing `output`, with no seal:
+/// it verifies only in dev mode
+pub fn fake_receipt(image_id: [u32; 8], output: &JournalOutput) -> Vec<u8> {
+    let journal: Vec<u8> =
+        journal::words(output).iter().flat_map(|word| word.to_le_bytes()).collect();
+    let claim = ReceiptClaim::ok(image_id, journal.clone());
+    let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal);
+    bincode::serialize(&receipt).unwrap()
+}
+
+#[derive(Parser)]
+struct ProveCli {
+    #[command(flatten)]
+    args: ProveArgs,
+}
+
+/// `ProveArgs` as the prove command would parse `args`
+pub fn prove_args(args: &[&str]) -> ProveArgs {
+    try_prove_args(args).unwrap()
+}
+
+/// `ProveArgs` as the prove command would parse `args`, or clap's error
+pub fn try_prove_args(args: &[&str]) -> Result<ProveArgs, clap::Error> {
+    ProveCli::try_parse_from(std::iter::once("gridzero-host").chain(args.iter().copied())).map(|cli| cli.args)
+}
@@ -0,0 +1,380 @@
+//! Proof verification
+//!
+//! `gridzero-host verify <proof.bin> [image_id]` checks a serialized
+//! receipt against a guest image ID and prints the decoded journal.
+//! With `--expect-output expected.json` it also asserts the journal
+//! equals a pinned output in the same layout; an invalid proof exits with 2
+//! and a valid proof with the wrong output exits with 3.
+//!
+//! Receipts are read as bincode or, if they start with `{`, as the serde
+//! JSON `--receipt-format json` writes.
+//!
+//! `verify --batch <dir>` verifies every `proof*.bin` or `proof*.json` in a directory
+//! against the built guest and prints a pass/fail table; any failure
+//! exits with 2.
+//!
+//! `--image-id-allowlist <file>` accepts a receipt that verifies against
+//! any image ID in the file instead of a single one, for rolling guest
+//! upgrades where proofs from old and new images coexist; the ID that
+//! matched is reported.
+//!
+//! A proof against the aggregator's image ID (from `--shard-size`) is
+//! decoded as an `AggregateOutput` and checked on its composed output;
+//! its shards must have been proven by the built guest.
+
+use clap::Args;
+use risc0_zkvm::{sha::Digest, Receipt, VerifierContext};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::HostError;
+use crate::image_id::{parse_allowlist, parse_image_id};
+use crate::input::InputFormat;
+use crate::journal;
+use gridzero_core::{AggregateOutput, JournalOutput};
+use gridzero_methods::{GRIDZERO_AGGREGATOR_ID, GRIDZERO_GUEST_ID};
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Serialized receipt (proof.bin or proof.json)
+    #[arg(required_unless_present = "batch")]
+    proof: Option<PathBuf>,
+
+    /// Image ID as hex or a path to image_id.hex (defaults to the built guest)
+    image_id: Option<String>,
+
+    /// Accept any image ID listed in this file (one hex ID per line)
+    #[arg(long, value_name = "FILE", conflicts_with = "image_id")]
+    image_id_allowlist: Option<PathBuf>,
+
+    /// Also require the decoded journal to equal this output.json
+    #[arg(long)]
+    expect_output: Option<PathBuf>,
+
+    /// Verify every proof*.bin and proof*.json in this directory instead of one proof
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["proof", "expect_output"])]
+    batch: Option<PathBuf>,
+}
+
+pub fn run(args: &VerifyArgs) -> Result<(), HostError> {
+    let image_ids = match (&args.image_id, &args.image_id_allowlist) {
+        (_, Some(path)) => parse_allowlist(path)?,
+        (Some(arg), None) => vec![parse_image_id(arg)?],
+        (None, None) => vec![Digest::from(GRIDZERO_GUEST_ID)],
+    };
+    let proof = match (&args.batch, &args.proof) {
+        (Some(dir), _) => return run_batch(dir, &image_ids, &VerifierContext::default()),
+        (None, Some(proof)) => proof,
+        (None, None) => unreachable!("clap requires a proof unless --batch is given"),
+    };
+
+    let expected: Option<JournalOutput> = match &args.expect_output {
+        Some(path) => {
+            let json = fs::read(path).map_err(HostError::io(path))?;
+            let expected = serde_json::from_slice(&json).map_err(|e| HostError::Parse {
+                format: InputFormat::Json,
+                message: format!("{}: {}", path.display(), e),
+            })?;
+            Some(expected)
+        }
+        None => None,
+    };
+
+    let proof_bytes = fs::read(proof).map_err(HostError::io(proof))?;
+    let (image_id, output) = verify_receipt_any(&proof_bytes, &image_ids, &VerifierContext::default())?;
+
+    if let Some(expected) = &expected {
+        check_expected(expected, &output)?;
+    }
+
+    println!("✅ Proof verified");
+    if args.expect_output.is_some() {
+        println!("  Journal matches expected output");
+    }
+    if args.image_id_allowlist.is_some() {
+        println!(
+            "  Image ID: {} (allowlisted, 1 of {})",
+            hex::encode(image_id.as_bytes()),
+            image_ids.len()
+        );
+    } else {
+        println!("  Image ID: {}", hex::encode(image_id.as_bytes()));
+    }
+    println!("  Player: 0x{}", hex::encode(output.player_address()));
+    println!("  Score: {}", output.score());
+    match &output {
+        JournalOutput::Full(output) => {
+            println!("  Total mined: {}", output.total_mined);
+            if !output.validated {
+                println!("  ⚠️  UNVALIDATED: proven with trust_input; per-record checks were skipped");
+            }
+        }
+        JournalOutput::Minimal(output) => {
+            println!("  History root: 0x{}", hex::encode(output.history_root))
+        }
+    }
+    Ok(())
+}
+
+/// Verify every receipt in `dir`, reporting each and failing if any do
+fn run_batch(dir: &Path, image_ids: &[Digest], ctx: &VerifierContext) -> Result<(), HostError> {
+    let mut proofs: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(HostError::io(dir))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with("proof") && (name.ends_with(".bin") || name.ends_with(".json"))
+                })
+        })
+        .collect();
+    proofs.sort();
+
+    println!("🔎 Verifying {} proofs in {}", proofs.len(), dir.display());
+    println!("  {:<6}  {:<44}  {:>10}  {:<8}  proof", "result", "player", "score", "image");
+    let mut failed = 0;
+    for path in &proofs {
+        let verified = fs::read(path)
+            .map_err(HostError::io(path))
+            .and_then(|bytes| verify_receipt_any(&bytes, image_ids, ctx));
+        match verified {
+            Ok((image_id, output)) => println!(
+                "  {:<6}  0x{:<42}  {:>10}  {:<8}  {}",
+                "PASS",
+                hex::encode(output.player_address()),
+                output.score(),
+                hex::encode(&image_id.as_bytes()[..4]),
+                path.display()
+            ),
+            Err(err) => {
+                failed += 1;
+                println!(
+                    "  {:<6}  {:<44}  {:>10}  {:<8}  {} ({})",
+                    "FAIL",
+                    "-",
+                    "-",
+                    "-",
+                    path.display(),
+                    err
+                );
+            }
+        }
+    }
+
+    println!("\n  Passed: {}/{}", proofs.len() - failed, proofs.len());
+    if failed > 0 {
+        return Err(HostError::VerifyFailed {
+            failed,
+            total: proofs.len(),
+        });
+    }
+    Ok(())
+}
+
+/// `--expect-output`: fail unless a verified journal equals `expected`
+fn check_expected(expected: &JournalOutput, output: &JournalOutput) -> Result<(), HostError> {
+    let fields = differing_fields(expected, output);
+    if !fields.is_empty() {
+        return Err(HostError::OutputMismatch { fields });
+    }
+    Ok(())
+}
+
+/// Names of the top-level fields on which two outputs disagree
+pub(crate) fn differing_fields(expected: &JournalOutput, actual: &JournalOutput) -> Vec<String> {
+    let expected = serde_json::to_value(expected).expect("outputs always serialize");
+    let actual = serde_json::to_value(actual).expect("outputs always serialize");
+    match (expected.as_object(), actual.as_object()) {
+        (Some(expected), Some(actual)) => expected
+            .iter()
+            .filter(|(name, value)| actual.get(name.as_str()) != Some(value))
+            .map(|(name, _)| name.clone())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Deserialize a receipt, verify it against `image_id` and decode the journal
+pub(crate) fn verify_receipt(proof_bytes: &[u8], image_id: Digest) -> Result<JournalOutput, HostError> {
+    let receipt = decode_receipt(proof_bytes)?;
+    receipt.verify(image_id).map_err(|e| HostError::InvalidProof {
+        message: e.to_string(),
+    })?;
+    decode_journal(&receipt, image_id)
+}
+
+/// `verify_receipt` against the first of `image_ids` the receipt
+/// verifies under in `ctx`, returning that ID with the journal
+fn verify_receipt_any(
+    proof_bytes: &[u8],
+    image_ids: &[Digest],
+    ctx: &VerifierContext,
+) -> Result<(Digest, JournalOutput), HostError> {
+    let receipt = decode_receipt(proof_bytes)?;
+    if let [image_id] = image_ids {
+        receipt.verify_with_context(ctx, *image_id).map_err(|e| HostError::InvalidProof {
+            message: e.to_string(),
+        })?;
+        return decode_journal(&receipt, *image_id).map(|output| (*image_id, output));
+    }
+    let image_id = image_ids
+        .iter()
+        .copied()
+        .find(|image_id| receipt.verify_with_context(ctx, *image_id).is_ok())
+        .ok_or_else(|| HostError::InvalidProof {
+            message: format!(
+                "receipt verifies against none of the {} allowlisted image IDs",
+                image_ids.len()
+            ),
+        })?;
+    decode_journal(&receipt, image_id).map(|output| (image_id, output))
+}
+
+/// Decode a verified receipt's journal as the guest `image_id` commits it
+fn decode_journal(receipt: &Receipt, image_id: Digest) -> Result<JournalOutput, HostError> {
+    if image_id == Digest::from(GRIDZERO_AGGREGATOR_ID) {
+        let aggregate: AggregateOutput = receipt.journal.decode().map_err(|e| HostError::InvalidProof {
+            message: format!("journal does not decode as AggregateOutput: {}", e),
+        })?;
+        check_aggregate(&aggregate)?;
+        let output = JournalOutput::Full(Box::new(aggregate.output));
+        journal::warn_on_core_version(&output);
+        return Ok(output);
+    }
+    let output = journal::decode(&receipt.journal)?;
+    journal::warn_on_core_version(&output);
+    Ok(output)
+}
+
+/// Reject an aggregate whose shards were proven by anything but the
+/// built guest. The aggregator verifies its shard receipts against
+/// whichever image ID the prover passed in, so without this a guest that
+/// commits made-up shard outputs would aggregate into a genuine receipt.
+pub(crate) fn check_aggregate(aggregate: &AggregateOutput) -> Result<(), HostError> {
+    check_member_image(aggregate.shard_image_id)?;
+    journal::check_version(aggregate.output.version)
+}
+
+/// Reject a composed receipt's inner image ID unless it is the built guest
+pub(crate) fn check_member_image(image_id: [u32; 8]) -> Result<(), HostError> {
+    let guest = Digest::from(GRIDZERO_GUEST_ID);
+    let actual = Digest::from(image_id);
+    if actual != guest {
+        return Err(HostError::ImageMismatch {
+            expected: hex::encode(guest.as_bytes()),
+            actual: hex::encode(actual.as_bytes()),
+        });
+    }
+    Ok(())
+}
+
+/// Deserialize a receipt without verifying it, as JSON if it looks like
+/// JSON and as bincode otherwise
+pub fn decode_receipt(proof_bytes: &[u8]) -> Result<Receipt, HostError> {
+    let is_json = proof_bytes.iter().find(|byte| !byte.is_ascii_whitespace()) == Some(&b'{');
+    let decoded = if is_json {
+        serde_json::from_slice(proof_bytes).map_err(|e| e.to_string())
+    } else {
+        bincode::deserialize(proof_bytes).map_err(|e| e.to_string())
+    };
+    decoded.map_err(|e| HostError::InvalidProof {
+        message: format!("not a serialized receipt: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::canonical_output;
+    use crate::testing::{fake_receipt, temp_dir};
+
+    fn aggregate(shard_image_id: [u32; 8]) -> AggregateOutput {
+        AggregateOutput {
+            shard_image_id,
+            shard_count: 2,
+            output: canonical_output(),
+        }
+    }
+
+    #[test]
+    fn accepts_shards_of_the_built_guest() {
+        assert!(check_aggregate(&aggregate(GRIDZERO_GUEST_ID)).is_ok());
+    }
+
+    #[test]
+    fn rejects_shards_of_a_foreign_image() {
+        let mut foreign = GRIDZERO_GUEST_ID;
+        foreign[0] ^= 1;
+        let err = check_aggregate(&aggregate(foreign)).unwrap_err();
+        assert!(matches!(err, HostError::ImageMismatch { .. }));
+        assert_eq!(err.exit_code(), crate::error::EXIT_IMAGE_MISMATCH);
+    }
+
+    #[test]
+    fn an_invalid_proof_fails_as_invalid() {
+        let err = verify_receipt(b"not a receipt", Digest::from(GRIDZERO_GUEST_ID)).unwrap_err();
+        assert!(matches!(err, HostError::InvalidProof { .. }));
+        assert_eq!(err.exit_code(), 2);
+    }
+
+    #[test]
+    fn a_wrong_output_fails_as_a_mismatch() {
+        let expected = JournalOutput::Full(Box::new(canonical_output()));
+        assert!(check_expected(&expected, &expected.clone()).is_ok());
+
+        let mut output = canonical_output();
+        output.score += 1;
+        let err = check_expected(&expected, &JournalOutput::Full(Box::new(output))).unwrap_err();
+        let HostError::OutputMismatch { fields } = &err else {
+            panic!("expected an output mismatch, got {:?}", err);
+        };
+        assert_eq!(fields, &["score".to_string()]);
+        assert_eq!(err.exit_code(), 3);
+    }
+
+    #[test]
+    fn a_batch_reports_a_corrupt_proof_among_valid_ones() {
+        let dir = temp_dir("verify-batch");
+        let output = JournalOutput::Full(Box::new(canonical_output()));
+        let valid = fake_receipt(GRIDZERO_GUEST_ID, &output);
+        fs::write(dir.join("proof_0xaa.bin"), &valid).unwrap();
+        fs::write(dir.join("proof_0xbb.bin"), &valid[..valid.len() / 2]).unwrap();
+        fs::write(dir.join("notes.txt"), "not a proof").unwrap();
+        let image_ids = [Digest::from(GRIDZERO_GUEST_ID)];
+
+        // Fake receipts only verify in dev mode, where the whole one passes
+        let dev_mode = VerifierContext::default().with_dev_mode(true);
+        let err = run_batch(&dir, &image_ids, &dev_mode).unwrap_err();
+        assert!(matches!(err, HostError::VerifyFailed { failed: 1, total: 2 }), "{:?}", err);
+
+        let production = VerifierContext::default().with_dev_mode(false);
+        let err = run_batch(&dir, &image_ids, &production).unwrap_err();
+        assert!(matches!(err, HostError::VerifyFailed { failed: 2, total: 2 }), "{:?}", err);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn an_allowlist_accepts_only_the_ids_it_names() {
+        let dir = temp_dir("verify-allowlist");
+        let release = Digest::from([42; 8]);
+        let allowlist = dir.join("allowlist.txt");
+        let lines = [Digest::from(GRIDZERO_GUEST_ID), release].map(|id| hex::encode(id.as_bytes()));
+        let contents = format!("# current\n{}\n\n# previous release\n{}\n", lines[0], lines[1]);
+        fs::write(&allowlist, contents).unwrap();
+        let image_ids = parse_allowlist(&allowlist).unwrap();
+        let dev_mode = VerifierContext::default().with_dev_mode(true);
+
+        let output = JournalOutput::Full(Box::new(canonical_output()));
+        let (image_id, verified) =
+            verify_receipt_any(&fake_receipt([42; 8], &output), &image_ids, &dev_mode).unwrap();
+        assert_eq!((image_id, verified), (release, output.clone()));
+
+        let err = verify_receipt_any(&fake_receipt([43; 8], &output), &image_ids, &dev_mode).unwrap_err();
+        let HostError::InvalidProof { message } = &err else {
+            panic!("expected an invalid proof, got {:?}", err);
+        };
+        assert!(message.contains("none of the 2 allowlisted"), "{}", message);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
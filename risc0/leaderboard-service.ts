@@ -114,4 +114,8 @@ export interface MiningRecord {
   is_rare: boolean;
   random_output: number[]; // 32 bytes
   nonce: number;
+  block_height?: number;
+  tx_hash?: number[]; // 32 bytes
+  timestamp?: number; // unix seconds
+  grid_z?: number; // layer, 0 on flat maps
 }
@@ -0,0 +1,44 @@
+//! GridZero Shard Aggregator
+//!
+//! RISC Zero zkVM program that composes shard proofs of one player's
+//! history into a single leaderboard output.
+//!
+//! This proves:
+//! 1. Every shard output was committed by a verified receipt of the
+//!    shard guest
+//! 2. The shards tile one contiguous nonce range without gaps or overlaps
+//! 3. No cell is mined in more than one shard
+//! 4. The totals are the sums of the shard totals
+//!
+//! See `gridzero_core::shard` for which output fields compose exactly.
+//!
+//! The shard image ID is part of the input and committed as
+//! `shard_image_id`, so a verifier must reject any other than the
+//! leaderboard guest's (the host's `verify` and `check-image` do). It is
+//! not pinned here because both guests are built by the same
+//! `gridzero-methods` build script, before the shard guest's ID exists.
+
+use gridzero_core::{aggregate_shards, AggregateInput, AggregateOutput};
+use risc0_zkvm::guest::env;
+
+fn main() {
+    let input: AggregateInput = env::read();
+
+    // Each shard's journal must match a receipt supplied as an assumption
+    for shard in &input.shards {
+        let words = risc0_zkvm::serde::to_vec(shard).expect("shard outputs always serialize");
+        let journal: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        env::verify(input.shard_image_id, &journal).expect("shard receipt does not verify");
+    }
+
+    let output = match aggregate_shards(&input) {
+        Ok(output) => output,
+        Err(err) => panic!("{}", err),
+    };
+
+    env::commit(&AggregateOutput {
+        shard_image_id: input.shard_image_id,
+        shard_count: input.shards.len() as u64,
+        output,
+    });
+}
@@ -0,0 +1,35 @@
+//! GridZero Disjointness Prover
+//!
+//! RISC Zero zkVM program that proves whether two players mined
+//! disjoint sets of cells, for anti-Sybil checks.
+//!
+//! This proves:
+//! 1. Both member outputs were committed by verified receipts of the
+//!    shard guest
+//! 2. Each member covers its player's whole history
+//! 3. `disjoint` says whether any cell appears in both histories
+//!
+//! See `gridzero_core::disjoint` for how the cell sets are compared.
+//!
+//! Like the aggregator's shard image, `member_image_id` is the prover's
+//! choice and is committed, so a verifier must check it is the
+//! leaderboard guest's (`check-image` does).
+
+use gridzero_core::{check_disjoint, DisjointInput};
+use risc0_zkvm::guest::env;
+
+fn main() {
+    let input: DisjointInput = env::read();
+
+    // Each member's journal must match a receipt supplied as an assumption
+    for member in &input.members {
+        let words = risc0_zkvm::serde::to_vec(member).expect("shard outputs always serialize");
+        let journal: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        env::verify(input.member_image_id, &journal).expect("member receipt does not verify");
+    }
+
+    match check_disjoint(&input) {
+        Ok(output) => env::commit(&output),
+        Err(err) => panic!("{}", err),
+    }
+}
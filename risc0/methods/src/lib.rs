@@ -0,0 +1,3 @@
+//! Embedded guest ELFs and image IDs generated by `risc0-build`
+
+include!(concat!(env!("OUT_DIR"), "/methods.rs"));